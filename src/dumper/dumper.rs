@@ -166,7 +166,15 @@ pub mod dumper {
         fn dump_elem_sec(&self) {
             println!("Element[{}]:", self.module.elem_sec.len());
             for (index, elem) in self.module.elem_sec.iter().enumerate() {
-                println!("  elem[{}]: table = {}", index, elem.table);
+                match elem.mode {
+                    ElemMode::Active => {
+                        println!("  elem[{}]: table = {}", index, elem.table)
+                    }
+                    ElemMode::Passive => println!("  elem[{}]: passive", index),
+                    ElemMode::Declarative => {
+                        println!("  elem[{}]: declarative", index)
+                    }
+                }
             }
         }
 
@@ -198,189 +206,162 @@ pub mod dumper {
         fn dump_custom_sec(&self) {
             println!("Custom[{}]:", self.module.custom_sec.len());
             for (index, cs) in self.module.custom_sec.iter().enumerate() {
-                println!("  custom[{}]: name = {}", index, cs.name);
+                if cs.is_debug() {
+                    println!(
+                        "  custom[{}]: name = {}, size = {} (debug)",
+                        index,
+                        cs.name,
+                        cs.bytes.len()
+                    );
+                } else {
+                    println!("  custom[{}]: name = {}", index, cs.name);
+                }
             }
         }
 
         fn dump_expr(&self, indentation: &str, expr: &Expr) {
+            let inner = indentation.to_owned() + "  ";
             for instruction in expr {
+                println!(
+                    "{}{}",
+                    indentation,
+                    instr_text(self.module, instruction)
+                );
                 match instruction.opcode {
                     OpCode::Block | OpCode::Loop => {
-                        let args = &instruction.args;
-                        let block_args = args
-                            .as_ref()
-                            .unwrap()
-                            .downcast_ref::<BlockArgs>()
-                            .unwrap();
-                        let block_type =
-                            self.module.get_block_type(block_args.block_type);
-                        println!(
-                            "{}{} {}",
-                            indentation,
-                            instruction.get_op_name(),
-                            block_type
-                        );
-                        self.dump_expr(
-                            (indentation.to_owned() + "  ").as_ref(),
-                            &block_args.instructions,
-                        );
+                        let block_args =
+                            instruction.args_as::<BlockArgs>().unwrap();
+                        self.dump_expr(&inner, &block_args.instructions);
                         println!("{}{}", indentation, "end");
                     }
                     OpCode::If => {
-                        let args = &instruction.args;
-                        let block_args = args
-                            .as_ref()
-                            .unwrap()
-                            .downcast_ref::<IfArgs>()
-                            .unwrap();
-                        let block_type =
-                            self.module.get_block_type(block_args.block_type);
-                        println!("{}{} {}", indentation, "if", block_type);
-                        self.dump_expr(
-                            (indentation.to_owned() + "  ").as_ref(),
-                            &block_args.instructions_1,
-                        );
+                        let if_args = instruction.args_as::<IfArgs>().unwrap();
+                        self.dump_expr(&inner, &if_args.instructions_1);
                         println!("{}{}", indentation, "else");
-                        self.dump_expr(
-                            (indentation.to_owned() + "  ").as_ref(),
-                            &block_args.instructions_2,
-                        );
+                        self.dump_expr(&inner, &if_args.instructions_2);
                         println!("{}{}", indentation, "end");
                     }
-                    OpCode::Br
-                    | OpCode::BrIf
-                    | OpCode::LocalGet
-                    | OpCode::LocalSet
-                    | OpCode::LocalTee
-                    | OpCode::GlobalGet
-                    | OpCode::GlobalSet
-                    | OpCode::Call
-                    | OpCode::CallIndirect => {
-                        let args = &instruction.args;
-                        let param = args
-                            .as_ref()
-                            .unwrap()
-                            .downcast_ref::<u32>()
-                            .unwrap();
-                        println!(
-                            "{}{} {}",
-                            indentation,
-                            instruction.get_op_name(),
-                            param
-                        );
-                    }
-                    OpCode::BrTable => {
-                        let args = &instruction.args;
-                        let param = args
-                            .as_ref()
-                            .unwrap()
-                            .downcast_ref::<BrTableArgs>()
-                            .unwrap();
-                        println!(
-                            "{}{} {}",
-                            indentation,
-                            instruction.get_op_name(),
-                            param
-                        );
-                    }
-                    OpCode::MemorySize
-                    | OpCode::MemoryGrow
-                    | OpCode::TruncSat => {
-                        let args = &instruction.args;
-                        let param = args
-                            .as_ref()
-                            .unwrap()
-                            .downcast_ref::<u8>()
-                            .unwrap();
-                        println!(
-                            "{}{} {}",
-                            indentation,
-                            instruction.get_op_name(),
-                            param
-                        );
-                    }
-                    OpCode::I32Const => {
-                        let args = &instruction.args;
-                        let param = args
-                            .as_ref()
-                            .unwrap()
-                            .downcast_ref::<i32>()
-                            .unwrap();
-                        println!(
-                            "{}{} {}",
-                            indentation,
-                            instruction.get_op_name(),
-                            param
-                        );
-                    }
-                    OpCode::I64Const => {
-                        let args = &instruction.args;
-                        let param = args
-                            .as_ref()
-                            .unwrap()
-                            .downcast_ref::<i64>()
-                            .unwrap();
-                        println!(
-                            "{}{} {}",
-                            indentation,
-                            instruction.get_op_name(),
-                            param
-                        );
-                    }
-                    OpCode::F32Const => {
-                        let args = &instruction.args;
-                        let param = args
-                            .as_ref()
-                            .unwrap()
-                            .downcast_ref::<f32>()
-                            .unwrap();
-                        println!(
-                            "{}{} {}",
-                            indentation,
-                            instruction.get_op_name(),
-                            param
-                        );
-                    }
-                    OpCode::F64Const => {
-                        let args = &instruction.args;
-                        let param = args
-                            .as_ref()
-                            .unwrap()
-                            .downcast_ref::<f64>()
-                            .unwrap();
-                        println!(
-                            "{}{} {}",
-                            indentation,
-                            instruction.get_op_name(),
-                            param
-                        );
-                    }
-                    _ => {
-                        if instruction.opcode >= OpCode::I32Load
-                            && instruction.opcode <= OpCode::I64Store32
-                        {
-                            let args = &instruction.args;
-                            let mem_arg = args
-                                .as_ref()
-                                .unwrap()
-                                .downcast_ref::<MemArg>()
-                                .unwrap();
-                            println!(
-                                "{}{} {}",
-                                indentation,
-                                instruction.get_op_name(),
-                                mem_arg
-                            );
-                        } else {
-                            println!(
-                                "{}{}",
-                                indentation,
-                                instruction.get_op_name()
-                            );
-                        }
-                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// 单条指令的反汇编文本，与 dump 输出中的一行相同，块指令只有开头一行
+    pub fn instr_text(module: &Module, instruction: &Instruction) -> String {
+        let name = instruction.get_op_name();
+        match instruction.opcode {
+            OpCode::Block | OpCode::Loop => {
+                let block_args = instruction.args_as::<BlockArgs>().unwrap();
+                let block_type = module.get_block_type(block_args.block_type);
+                format!("{} {}", name, block_type)
+            }
+            OpCode::If => {
+                let if_args = instruction.args_as::<IfArgs>().unwrap();
+                let block_type = module.get_block_type(if_args.block_type);
+                format!("{} {}", "if", block_type)
+            }
+            OpCode::Br
+            | OpCode::BrIf
+            | OpCode::LocalGet
+            | OpCode::LocalSet
+            | OpCode::LocalTee
+            | OpCode::GlobalGet
+            | OpCode::GlobalSet
+            | OpCode::TableGet
+            | OpCode::TableSet
+            | OpCode::RefFunc
+            | OpCode::Call => {
+                format!("{} {}", name, instruction.args_as::<u32>().unwrap())
+            }
+            OpCode::CallIndirect => {
+                let param = instruction.args_as::<CallIndirectArgs>().unwrap();
+                format!("{} {}", name, param)
+            }
+            OpCode::BrTable => {
+                let param = instruction.args_as::<BrTableArgs>().unwrap();
+                format!("{} {}", name, param)
+            }
+            OpCode::TruncSat => {
+                let param = instruction.args_as::<PrefixedArgs>().unwrap();
+                format!("{} {}", name, param)
+            }
+            OpCode::MemorySize | OpCode::MemoryGrow => {
+                format!("{} {}", name, instruction.args_as::<u8>().unwrap())
+            }
+            OpCode::I32Const => {
+                format!("{} {}", name, instruction.args_as::<i32>().unwrap())
+            }
+            OpCode::I64Const => {
+                format!("{} {}", name, instruction.args_as::<i64>().unwrap())
+            }
+            OpCode::F32Const => {
+                format!("{} {}", name, instruction.args_as::<f32>().unwrap())
+            }
+            OpCode::F64Const => {
+                format!("{} {}", name, instruction.args_as::<f64>().unwrap())
+            }
+            _ => {
+                if instruction.opcode >= OpCode::I32Load
+                    && instruction.opcode <= OpCode::I64Store32
+                {
+                    mem_instr_text(instruction)
+                } else {
+                    name
                 }
             }
         }
     }
+
+    // load/store 指令及其内存参数，对齐按字节数显示
+    fn mem_instr_text(instruction: &Instruction) -> String {
+        let mem_arg = instruction.args_as::<MemArg>().unwrap();
+        format!("{} {}", instruction.get_op_name(), mem_arg)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_mem_instr_text() {
+            // i32.load align=2 offset=4，对齐显示为 4 字节而不是指数 2
+            let instr = Instruction::with_args(
+                OpCode::I32Load,
+                MemArg {
+                    align: 2,
+                    offset: 4,
+                },
+            );
+            assert_eq!(mem_instr_text(&instr), "I32Load offset=4 align=4");
+        }
+
+        #[test]
+        fn test_instr_text() {
+            let module = Module::default();
+            let call = Instruction::with_index(OpCode::Call, 3);
+            assert_eq!(instr_text(&module, &call), "Call 3");
+            let load = Instruction::with_mem_arg(
+                OpCode::I64Load,
+                MemArg {
+                    align: 3,
+                    offset: 0,
+                },
+            );
+            assert_eq!(instr_text(&module, &load), "I64Load offset=0 align=8");
+            let block = Instruction::with_args(
+                OpCode::Block,
+                BlockArgs {
+                    block_type: BLOCK_TYPE_I32,
+                    instructions: vec![Instruction::new(OpCode::Nop)],
+                },
+            );
+            // 块指令只显示开头一行
+            assert!(!instr_text(&module, &block).contains('\n'));
+            assert_eq!(
+                instr_text(&module, &Instruction::new(OpCode::Drop)),
+                "Drop"
+            );
+        }
+    }
 }
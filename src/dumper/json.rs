@@ -0,0 +1,230 @@
+pub mod json {
+    use crate::module::*;
+    use std::io::{self, Write};
+
+    /// 将模块转换为 JSON 文档，便于脚本和其他工具处理
+    pub fn to_json(module: &Module) -> String {
+        let mut out = Vec::new();
+        write_json(module, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// 将 JSON 文档逐项写入 w，不在内存中构造完整文档，适合函数很多的大模块
+    pub fn write_json<W: Write>(module: &Module, w: &mut W) -> io::Result<()> {
+        let imported_func_count = module
+            .import_sec
+            .iter()
+            .filter(|import| matches!(import.desc, ImportDesc::Func(_)))
+            .count();
+        let types = module.type_sec.iter().map(func_type_json);
+        let imports = module.import_sec.iter().map(|import| {
+            let desc = match &import.desc {
+                ImportDesc::Func(type_idx) => {
+                    format!("\"kind\":\"func\",\"type\":{}", type_idx)
+                }
+                ImportDesc::Table(table) => {
+                    format!("\"kind\":\"table\",{}", limits_json(&table.limits))
+                }
+                ImportDesc::Mem(mem) => {
+                    format!("\"kind\":\"memory\",{}", limits_json(mem))
+                }
+                ImportDesc::Global(global) => {
+                    format!("\"kind\":\"global\",{}", global_type_json(global))
+                }
+            };
+            format!(
+                "{{\"module\":{},\"name\":{},{}}}",
+                string(&import.module_name),
+                string(&import.member_name),
+                desc
+            )
+        });
+        let functions = module.func_sec.iter().enumerate().map(|(i, t)| {
+            format!("{{\"index\":{},\"type\":{}}}", imported_func_count + i, t)
+        });
+        let tables = module
+            .table_sec
+            .iter()
+            .map(|table| format!("{{{}}}", limits_json(&table.limits)));
+        let memories = module
+            .mem_sec
+            .iter()
+            .map(|mem| format!("{{{}}}", limits_json(mem)));
+        let globals = module.global_sec.iter().map(|global| {
+            format!("{{{}}}", global_type_json(&global.global_type))
+        });
+        let exports = module.export_sec.iter().map(|exp| {
+            let (kind, idx) = match exp.desc {
+                ExportDesc::Func(idx) => ("func", idx),
+                ExportDesc::Table(idx) => ("table", idx),
+                ExportDesc::Mem(idx) => ("memory", idx),
+                ExportDesc::Global(idx) => ("global", idx),
+            };
+            format!(
+                "{{\"name\":{},\"kind\":\"{}\",\"index\":{}}}",
+                string(&exp.name),
+                kind,
+                idx
+            )
+        });
+        let start = match module.start_sec {
+            Some(idx) => idx.to_string(),
+            None => "null".to_string(),
+        };
+        write!(w, "{{\"version\":{}", module.version)?;
+        write_array(w, "types", types)?;
+        write_array(w, "imports", imports)?;
+        write_array(w, "functions", functions)?;
+        write_array(w, "tables", tables)?;
+        write_array(w, "memories", memories)?;
+        write_array(w, "globals", globals)?;
+        write_array(w, "exports", exports)?;
+        write!(w, ",\"start\":{}}}", start)
+    }
+
+    // 写入 ,"name":[...]，每次只写一个元素
+    fn write_array<W: Write, I: Iterator<Item = String>>(
+        w: &mut W,
+        name: &str,
+        items: I,
+    ) -> io::Result<()> {
+        write!(w, ",\"{}\":[", name)?;
+        for (i, item) in items.enumerate() {
+            if i > 0 {
+                w.write_all(b",")?;
+            }
+            w.write_all(item.as_bytes())?;
+        }
+        w.write_all(b"]")
+    }
+
+    fn array<I: Iterator<Item = String>>(items: I) -> String {
+        format!("[{}]", items.collect::<Vec<_>>().join(","))
+    }
+
+    fn string(s: &str) -> String {
+        let mut out = String::from("\"");
+        for c in s.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if (c as u32) < 0x20 => {
+                    out.push_str(&format!("\\u{:04x}", c as u32))
+                }
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+        out
+    }
+
+    fn val_types_json(val_types: &[ValType]) -> String {
+        array(val_types.iter().map(|vt| format!("\"{}\"", vt)))
+    }
+
+    fn func_type_json(func_type: &FuncType) -> String {
+        format!(
+            "{{\"params\":{},\"results\":{}}}",
+            val_types_json(&func_type.params_types),
+            val_types_json(&func_type.result_types)
+        )
+    }
+
+    fn limits_json(limits: &Limits) -> String {
+        let max = match limits.max {
+            Some(max) => max.to_string(),
+            None => "null".to_string(),
+        };
+        format!("\"min\":{},\"max\":{}", limits.min, max)
+    }
+
+    fn global_type_json(global_type: &GlobalType) -> String {
+        format!(
+            "\"type\":\"{}\",\"mutable\":{}",
+            global_type.val_type, global_type.mutable
+        )
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::module::module::module::{Export, Import};
+
+        #[test]
+        fn test_to_json() {
+            let module = Module {
+                version: 1,
+                type_sec: vec![FuncType {
+                    params_types: vec![ValType::I32, ValType::I32],
+                    result_types: vec![ValType::I32],
+                }],
+                import_sec: vec![Import {
+                    module_name: "env".to_string(),
+                    member_name: "memory".to_string(),
+                    desc: ImportDesc::Mem(Limits {
+                        min: 1,
+                        ..Default::default()
+                    }),
+                }],
+                func_sec: vec![0],
+                export_sec: vec![Export {
+                    name: "add".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                ..Default::default()
+            };
+            let json = to_json(&module);
+            assert!(json.contains(
+                "\"types\":[{\"params\":[\"i32\",\"i32\"],\"results\":[\"i32\"]}]"
+            ));
+            assert!(json.contains(
+                "\"imports\":[{\"module\":\"env\",\"name\":\"memory\",\"kind\":\"memory\",\"min\":1,\"max\":null}]"
+            ));
+            assert!(json.contains(
+                "\"exports\":[{\"name\":\"add\",\"kind\":\"func\",\"index\":0}]"
+            ));
+            assert!(json.contains("\"functions\":[{\"index\":0,\"type\":0}]"));
+        }
+
+        // 只统计写入的字节数和单次写入的最大长度，不保存内容
+        #[derive(Default)]
+        struct CountingWriter {
+            total: usize,
+            max_write: usize,
+        }
+
+        impl Write for CountingWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.total += buf.len();
+                self.max_write = self.max_write.max(buf.len());
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn test_write_json_streaming() {
+            let module = Module {
+                type_sec: vec![FuncType::default()],
+                func_sec: vec![0; 10_000],
+                ..Default::default()
+            };
+            let mut w = CountingWriter::default();
+            write_json(&module, &mut w).unwrap();
+            assert_eq!(w.total, to_json(&module).len());
+            assert!(w.total > 200_000);
+            assert!(w.max_write < 100);
+        }
+
+        #[test]
+        fn test_string_escape() {
+            assert_eq!(string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod dumper;
-pub use dumper::dumper::Dumper;
+pub mod json;
+pub use dumper::dumper::{instr_text, Dumper};
+pub use json::json::{to_json, write_json};
@@ -0,0 +1,61 @@
+pub mod error {
+    use crate::interpreter::Trap;
+    use crate::validator::ValidationError;
+    use std::{fmt, io};
+
+    /// 解码、校验、实例化和调用过程中可能出现的错误
+    #[derive(Debug)]
+    pub enum Error {
+        Io(io::Error),
+        Decode(String), // 模块二进制格式错误
+        Validation(ValidationError),
+        Trap(Trap),
+        ExportNotFound(String),
+        ArgumentMismatch(String),
+        InvalidCache(String),
+        Link(String),     // 实例化时导入无法链接
+        Debugger(String), // invoke_debug 和 resume 的调用顺序不正确
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Error::Io(e) => write!(f, "io error: {}", e),
+                Error::Decode(msg) => write!(f, "decode error: {}", msg),
+                Error::Validation(e) => write!(f, "{}", e),
+                Error::Trap(trap) => write!(f, "trap: {}", trap),
+                Error::ExportNotFound(name) => {
+                    write!(f, "export not found: {}", name)
+                }
+                Error::ArgumentMismatch(msg) => {
+                    write!(f, "argument mismatch: {}", msg)
+                }
+                Error::InvalidCache(msg) => {
+                    write!(f, "invalid module cache: {}", msg)
+                }
+                Error::Link(msg) => write!(f, "link error: {}", msg),
+                Error::Debugger(msg) => write!(f, "debugger error: {}", msg),
+            }
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    impl From<io::Error> for Error {
+        fn from(e: io::Error) -> Error {
+            Error::Io(e)
+        }
+    }
+
+    impl From<ValidationError> for Error {
+        fn from(e: ValidationError) -> Error {
+            Error::Validation(e)
+        }
+    }
+
+    impl From<Trap> for Error {
+        fn from(trap: Trap) -> Error {
+            Error::Trap(trap)
+        }
+    }
+}
@@ -1,15 +1,34 @@
 pub mod interpreter {
-    use std::{any::Any, rc::Rc, vec};
-
-    use crate::module::{instruction::instruction::BrArgs, *};
+    use std::{
+        any::Any,
+        collections::{HashMap, HashSet, VecDeque},
+        rc::Rc,
+        vec,
+    };
+
+    use crate::error::Error;
+    use crate::interpreter::{
+        FrameInfo, PostMortem, Trap, WasmValue, NULL_REF,
+    };
+    use crate::module::*;
 
     struct OperandStack {
         slots: Vec<u64>,
+        peak: usize,                 // 栈深度的峰值
+        types: Option<Vec<ValType>>, // 开启 typed_stack 时记录每个槽的类型，与 slots 一一对应
     }
 
     impl OperandStack {
         fn new() -> OperandStack {
-            OperandStack { slots: Vec::new() }
+            OperandStack {
+                slots: Vec::new(),
+                peak: 0,
+                types: None,
+            }
+        }
+
+        fn set_typed(&mut self, typed: bool) {
+            self.types = if typed { Some(Vec::new()) } else { None };
         }
 
         fn length(&self) -> usize {
@@ -24,70 +43,119 @@ pub mod interpreter {
             self.slots[idx] = val;
         }
 
-        fn push_u64s(&mut self, vals: &mut Vec<u64>) {
-            self.slots.append(vals)
+        // 把 slots[idx] 连同类型复制到栈顶
+        fn dup_operand(&mut self, idx: usize) {
+            let val = self.slots[idx];
+            match self.types.as_ref() {
+                Some(types) => self.push_typed(val, types[idx]),
+                None => self.push_u64(val),
+            }
+        }
+
+        fn remove_operand(&mut self, idx: usize) {
+            self.slots.remove(idx);
+            if let Some(types) = self.types.as_mut() {
+                types.remove(idx);
+            }
+        }
+
+        // 保留栈顶 n 个值，删除它们与 bp 之间的所有值
+        fn drop_below_top(&mut self, bp: usize, n: usize) {
+            let end = self.slots.len() - n;
+            self.slots.drain(bp..end);
+            if let Some(types) = self.types.as_mut() {
+                types.drain(bp..end);
+            }
+        }
+
+        fn truncate(&mut self, len: usize) {
+            self.slots.truncate(len);
+            if let Some(types) = self.types.as_mut() {
+                types.truncate(len);
+            }
+        }
+
+        // reinterpret 指令不改变位模式，只改变栈顶的类型
+        fn set_top_type(&mut self, val_type: ValType) {
+            if let Some(types) = self.types.as_mut() {
+                *types.last_mut().unwrap() = val_type;
+            }
         }
 
         fn pop_u64s(&mut self, n: usize) -> Vec<u64> {
-            let ret = self.slots[(self.slots.len() - n)..].to_vec();
-            self.slots.drain(self.slots.len() - n..);
-            ret
+            let len = self.slots.len();
+            if let Some(types) = self.types.as_mut() {
+                types.truncate(len - n);
+            }
+            self.slots.drain(len - n..).collect()
         }
 
-        fn push_u64(&mut self, val: u64) {
+        fn push_typed(&mut self, val: u64, val_type: ValType) {
             self.slots.push(val);
+            if let Some(types) = self.types.as_mut() {
+                types.push(val_type);
+            }
+            self.peak = self.peak.max(self.slots.len());
+        }
+
+        fn push_u64(&mut self, val: u64) {
+            self.push_typed(val, ValType::I64);
         }
 
         fn pop_u64(&mut self) -> u64 {
+            if let Some(types) = self.types.as_mut() {
+                types.pop();
+            }
             self.slots.pop().unwrap()
         }
 
         fn push_i64(&mut self, val: i64) {
-            self.slots.push(val as u64);
+            self.push_u64(val as u64);
         }
 
         fn pop_i64(&mut self) -> i64 {
-            self.slots.pop().unwrap() as i64
+            self.pop_u64() as i64
         }
 
         fn push_u32(&mut self, val: u32) {
-            self.slots.push(val as u64);
+            self.push_typed(val as u64, ValType::I32);
         }
 
         fn pop_u32(&mut self) -> u32 {
-            self.slots.pop().unwrap() as u32
+            self.pop_u64() as u32
         }
 
         fn push_i32(&mut self, val: i32) {
-            self.slots.push(val as u64);
+            self.push_typed(val as u64, ValType::I32);
         }
 
         fn pop_i32(&mut self) -> i32 {
-            self.slots.pop().unwrap() as i32
+            self.pop_u64() as i32
         }
 
+        // 浮点数在槽中按位模式保存，与内存中的小端编码无关
         fn push_f32(&mut self, val: f32) {
-            self.push_u32(u32::from_ne_bytes(val.to_ne_bytes()));
+            self.push_typed(val.to_bits() as u64, ValType::F32);
         }
 
         fn pop_f32(&mut self) -> f32 {
-            f32::from_ne_bytes(u32::to_ne_bytes(self.pop_u32()))
+            f32::from_bits(self.pop_u32())
         }
 
         fn push_f64(&mut self, val: f64) {
-            self.slots.push(u64::from_ne_bytes(f64::to_ne_bytes(val)));
+            self.push_typed(val.to_bits(), ValType::F64);
         }
 
         fn pop_f64(&mut self) -> f64 {
-            f64::from_ne_bytes(u64::to_ne_bytes(self.slots.pop().unwrap()))
+            f64::from_bits(self.pop_u64())
         }
 
         fn push_bool(&mut self, val: bool) {
-            self.slots.push(val as u64);
+            self.push_typed(val as u64, ValType::I32);
         }
 
         fn pop_bool(&mut self) -> bool {
-            self.slots.pop().unwrap() != 0
+            self.pop_u64() != 0
         }
     }
 
@@ -95,8 +163,11 @@ pub mod interpreter {
         opcode: OpCode,
         block_type: FuncType,
         instrs: Vec<Instruction>,
-        bp: usize, // base pointer
-        pc: i32,   // program counter
+        bp: usize,        // base pointer
+        pc: i32,          // program counter
+        func_idx: u32,    // 控制帧所属函数的索引
+        flat_base: usize, // 第一条指令在函数展开后指令序列中的位置
+        flat_pc: usize,   // 下一条指令在函数展开后指令序列中的位置
     }
 
     impl ControlFrame {
@@ -105,6 +176,8 @@ pub mod interpreter {
             block_type: FuncType,
             instrs: Vec<Instruction>,
             bp: usize,
+            func_idx: u32,
+            flat_base: usize,
         ) -> ControlFrame {
             ControlFrame {
                 opcode,
@@ -112,21 +185,99 @@ pub mod interpreter {
                 instrs,
                 bp,
                 pc: 0,
+                func_idx,
+                flat_base,
+                flat_pc: flat_base,
+            }
+        }
+    }
+
+    // wasm 的 min/max：任一操作数为 NaN 时结果为 NaN，-0 小于 +0。
+    // Rust 的 min/max 在一个操作数为 NaN 时返回另一个操作数，不能直接使用
+    fn min_f32(a: f32, b: f32) -> f32 {
+        if a.is_nan() || b.is_nan() {
+            f32::NAN
+        } else if a == b {
+            f32::from_bits(a.to_bits() | b.to_bits())
+        } else {
+            a.min(b)
+        }
+    }
+
+    fn max_f32(a: f32, b: f32) -> f32 {
+        if a.is_nan() || b.is_nan() {
+            f32::NAN
+        } else if a == b {
+            f32::from_bits(a.to_bits() & b.to_bits())
+        } else {
+            a.max(b)
+        }
+    }
+
+    fn min_f64(a: f64, b: f64) -> f64 {
+        if a.is_nan() || b.is_nan() {
+            f64::NAN
+        } else if a == b {
+            f64::from_bits(a.to_bits() | b.to_bits())
+        } else {
+            a.min(b)
+        }
+    }
+
+    fn max_f64(a: f64, b: f64) -> f64 {
+        if a.is_nan() || b.is_nan() {
+            f64::NAN
+        } else if a == b {
+            f64::from_bits(a.to_bits() & b.to_bits())
+        } else {
+            a.max(b)
+        }
+    }
+
+    // mock 或回放提供的返回值个数和类型都必须与函数签名一致
+    fn results_match(results: &[WasmValue], types: &[ValType]) -> bool {
+        results.len() == types.len()
+            && results.iter().zip(types).all(|(r, vt)| r.val_type() == *vt)
+    }
+
+    // 函数体按先序展开后的指令条数，块指令本身算一条，end 和 else 不计入
+    fn flat_len(instrs: &[Instruction]) -> usize {
+        instrs.iter().map(instr_flat_len).sum()
+    }
+
+    fn instr_flat_len(instr: &Instruction) -> usize {
+        let args = instr.args.as_ref();
+        match instr.opcode {
+            OpCode::Block | OpCode::Loop => {
+                let block_args =
+                    args.unwrap().downcast_ref::<BlockArgs>().unwrap();
+                1 + flat_len(&block_args.instructions)
             }
+            OpCode::If => {
+                let if_args = args.unwrap().downcast_ref::<IfArgs>().unwrap();
+                1 + flat_len(&if_args.instructions_1)
+                    + flat_len(&if_args.instructions_2)
+            }
+            _ => 1,
         }
     }
 
     struct ControlStack {
         frames: Vec<ControlFrame>,
+        peak: usize, // 栈深度的峰值
     }
 
     impl ControlStack {
         fn new() -> ControlStack {
-            ControlStack { frames: vec![] }
+            ControlStack {
+                frames: vec![],
+                peak: 0,
+            }
         }
 
         fn push_control_frame(&mut self, cf: ControlFrame) {
-            self.frames.push(cf)
+            self.frames.push(cf);
+            self.peak = self.peak.max(self.frames.len());
         }
 
         fn pop_control_frame(&mut self) -> ControlFrame {
@@ -155,20 +306,59 @@ pub mod interpreter {
     struct Memory {
         mem_type: MemType,
         data: Vec<u8>,
+        read_only: bool, // 冻结后只能读取，写入陷入，增长失败
+        written: Option<Vec<u64>>, // 毒化模式下的位图，每个字节对应一位，记录是否被写过
     }
 
+    // 毒化模式下未写过的内存的填充值
+    const POISON_BYTE: u8 = 0xCD;
+
     impl Memory {
         fn new(mem_type: MemType) -> Memory {
-            let min_page_size = mem_type.min;
             Memory {
                 mem_type,
-                data: vec![0; (min_page_size * PAGE_SIZE) as usize],
+                data: vec![0; mem_type.min * mem_type.page_size()],
+                read_only: false,
+                written: None,
+            }
+        }
+
+        /// 开启毒化模式：内存填充为毒化值，之后按字节记录是否写过
+        fn set_poison(&mut self) {
+            self.data.fill(POISON_BYTE);
+            self.written = Some(vec![0; self.data.len().div_ceil(64)]);
+        }
+
+        fn mark_written(&mut self, offset: usize, n: usize) {
+            if let Some(written) = self.written.as_mut() {
+                for i in offset..offset + n {
+                    written[i / 64] |= 1 << (i % 64);
+                }
+            }
+        }
+
+        /// [offset, offset + n) 是否都写过，未开启毒化模式时总是 true
+        fn is_written(&self, offset: usize, n: usize) -> bool {
+            match self.written.as_ref() {
+                Some(written) => (offset..offset + n)
+                    .all(|i| written[i / 64] & (1 << (i % 64)) != 0),
+                None => true,
+            }
+        }
+
+        /// 按最大页数预先分配内存，之后增长时不再重新分配，页数仍为当前页数
+        fn reserve_max(&mut self) {
+            if let Some(max) = self.mem_type.max {
+                let max_len = max * self.mem_type.page_size();
+                if max_len > self.data.len() {
+                    self.data.reserve_exact(max_len - self.data.len());
+                }
             }
         }
 
         /// 已分配内存的页数
         fn size(&self) -> usize {
-            self.data.len() / (PAGE_SIZE as usize)
+            self.data.len() / self.mem_type.page_size()
         }
 
         /// 增长内存, 返回增长前的内存页数
@@ -177,27 +367,87 @@ pub mod interpreter {
             if n == 0 {
                 return old_size;
             }
-            let max_page_count = self.mem_type.max.unwrap_or(MAX_PAGE_COUNT);
+            if self.read_only {
+                return 0xFFFFFFFF;
+            }
+            // 未声明上限时，地址空间仍限制在 4GiB 以内
+            let page_size = self.mem_type.page_size();
+            let max_page_count = self
+                .mem_type
+                .max
+                .unwrap_or(MAX_PAGE_COUNT * PAGE_SIZE / page_size);
             if old_size + n > max_page_count {
                 return 0xFFFFFFFF;
             }
-            self.data.extend(vec![0; n * PAGE_SIZE].iter());
+            match self.written.as_mut() {
+                Some(written) => {
+                    self.data
+                        .resize(self.data.len() + n * page_size, POISON_BYTE);
+                    written.resize(self.data.len().div_ceil(64), 0);
+                }
+                None => self.data.extend(vec![0; n * page_size].iter()),
+            }
             old_size
         }
 
-        fn read(&mut self, offset: usize, buf: &mut [u8]) {
-            self.check_offset(offset, buf.len());
+        fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), Trap> {
+            self.check_offset(offset, buf.len())?;
             buf.copy_from_slice(&self.data[offset..offset + buf.len()]);
+            Ok(())
         }
 
-        fn write(&mut self, offset: usize, data: &[u8]) {
-            self.check_offset(offset, data.len());
+        fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Trap> {
+            self.check_writable()?;
+            self.check_offset(offset, data.len())?;
             self.data[offset..offset + data.len()].copy_from_slice(data);
+            self.mark_written(offset, data.len());
+            Ok(())
         }
 
-        fn check_offset(&mut self, offset: usize, length: usize) {
-            if self.data.len() - length < offset {
-                panic!("Memory out of bounds");
+        /// 将 [src, src + n) 复制到 [dst, dst + n)，两个区域重叠时与 memmove 一致
+        fn copy(
+            &mut self,
+            dst: usize,
+            src: usize,
+            n: usize,
+        ) -> Result<(), Trap> {
+            self.check_writable()?;
+            self.check_offset(src, n)?;
+            self.check_offset(dst, n)?;
+            self.data.copy_within(src..src + n, dst);
+            self.mark_written(dst, n);
+            Ok(())
+        }
+
+        /// 将 [offset, offset + n) 范围内的字节填充为 val
+        fn fill(
+            &mut self,
+            offset: usize,
+            n: usize,
+            val: u8,
+        ) -> Result<(), Trap> {
+            self.check_writable()?;
+            self.check_offset(offset, n)?;
+            self.data[offset..offset + n].fill(val);
+            self.mark_written(offset, n);
+            Ok(())
+        }
+
+        fn check_writable(&self) -> Result<(), Trap> {
+            if self.read_only {
+                return Err(Trap::MemoryReadOnly);
+            }
+            Ok(())
+        }
+
+        fn check_offset(
+            &mut self,
+            offset: usize,
+            length: usize,
+        ) -> Result<(), Trap> {
+            match offset.checked_add(length) {
+                Some(end) if end <= self.data.len() => Ok(()),
+                _ => Err(Trap::MemoryOutOfBounds),
             }
         }
     }
@@ -224,31 +474,45 @@ pub mod interpreter {
         }
     }
 
-    type WasmVal = Box<dyn Any>;
-    type NativeFunc = fn(Vec<WasmVal>) -> Vec<WasmVal>;
+    pub type WasmVal = Box<dyn Any>;
+    /// 宿主函数，参数和返回值按类型分别为 i32、i64、f32、f64 或 Option<FuncIdx>
+    pub type NativeFunc = fn(Vec<WasmVal>) -> Vec<WasmVal>;
 
     #[derive(Clone, Default)]
     struct VMFunc {
+        idx: u32, // 函数在函数索引空间中的索引
         func_type: FuncType,
+        sig_id: u32, // 签名 ID，签名相同的函数 ID 相同
         code: Option<Code>,
         native_func: Option<NativeFunc>,
     }
 
     impl VMFunc {
-        fn new_internal_func(func_type: FuncType, code: Code) -> VMFunc {
+        fn new_internal_func(
+            idx: u32,
+            func_type: FuncType,
+            sig_id: u32,
+            code: Code,
+        ) -> VMFunc {
             VMFunc {
+                idx,
                 func_type,
+                sig_id,
                 code: Some(code),
                 native_func: None,
             }
         }
 
         fn new_external_func(
+            idx: u32,
             func_type: FuncType,
+            sig_id: u32,
             native_func: NativeFunc,
         ) -> VMFunc {
             VMFunc {
+                idx,
                 func_type,
+                sig_id,
                 code: None,
                 native_func: Some(native_func),
             }
@@ -291,6 +555,77 @@ pub mod interpreter {
         }
     }
 
+    /// 运行过程中资源使用的峰值，用于估算所需的栈和内存上限
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    pub struct RunStats {
+        pub peak_operand_stack: usize,
+        pub peak_control_stack: usize,
+        pub peak_memory_pages: usize,
+    }
+
+    /// 越界访问线性内存时的处理方式
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum OobMode {
+        Trap,     // 陷入，规范规定的行为
+        Sentinel, // 越界读返回 0，越界写被忽略
+    }
+
+    /// 影响数值结果可复现性的选项，默认值符合规范
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    pub struct VmConfig {
+        pub canonicalize_nans: bool, // 浮点运算产生的 NaN 统一为正的规范 NaN
+        pub strict_alignment: bool,  // 访问地址必须按操作的自然对齐，否则陷入
+        pub saturating_arithmetic: bool, // 整数除零和除法溢出时饱和而不是陷入
+    }
+
+    /// 调试执行的结果：调用返回，或在断点处暂停
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum StepResult {
+        Done(Vec<WasmValue>),
+        BreakpointHit { func_idx: FuncIdx, pc: usize }, // pc 为断点指令在函数体先序展开后的位置
+    }
+
+    // 在断点处暂停的调用，记录返回或陷入时恢复栈所需的状态
+    struct SuspendedCall {
+        func_type: FuncType,
+        depth: usize,
+        sp: usize,
+        local_0_idx: usize,
+        skip_break: bool, // 从断点恢复时，第一条指令不再触发同一个断点
+    }
+
+    /// 替代函数调用的模拟实现，接收参数并返回结果
+    pub type MockFunc = Box<dyn FnMut(Vec<WasmValue>) -> Vec<WasmValue>>;
+
+    /// 一次线性内存写入：地址、写入后的字节，以及执行写入的函数和指令位置，
+    /// pc 为指令在函数体先序展开后的位置
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct MemoryWrite {
+        pub addr: usize,
+        pub data: Vec<u8>,
+        pub func_idx: FuncIdx,
+        pub pc: usize,
+    }
+
+    /// 接收内存写入记录的回调
+    pub type WriteSink = Box<dyn FnMut(&MemoryWrite)>;
+
+    /// 一次宿主函数调用的参数和结果
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct HostCall {
+        pub module_name: String,
+        pub member_name: String,
+        pub args: Vec<WasmValue>,
+        pub results: Vec<WasmValue>,
+    }
+
+    // 宿主函数调用的记录和回放状态
+    enum HostCallLog {
+        Off,
+        Record(Vec<HostCall>),
+        Replay(VecDeque<HostCall>), // 回放时按顺序取出记录的结果
+    }
+
     pub struct VM<'a> {
         operand_stack: OperandStack,
         module: &'a Module,
@@ -299,17 +634,47 @@ pub mod interpreter {
         local_0_idx: usize,
         globals: Vec<GlobalVar>,
         vm_funcs: Vec<VMFunc>,
-        table: Option<Table>,
+        sig_ids: Vec<u32>, // 类型索引到签名 ID 的映射，实例化时计算
+        tables: Vec<Table>, // 模块内部定义的表，按表索引排列
+        config: VmConfig,
+        oob_mode: OobMode,
+        instantiated: bool, // 是否已经实例化，实例化只进行一次
+        instantiate_trap: Option<Trap>, // 实例化失败时的陷入，之后的调用都返回它
+        reserve_max_memory: bool,
+        mocks: HashMap<FuncIdx, MockFunc>,
+        host_funcs: HashMap<(String, String), NativeFunc>, // 用户注册的宿主函数
+        host_sigs: HashMap<(String, String), FuncType>, // 注册时声明了签名的宿主函数
+        coverage: Option<HashMap<FuncIdx, Vec<bool>>>, // 每个函数展开后的指令是否执行过
+        cur_flat_pc: usize, // 当前指令在函数展开后指令序列中的位置
+        instr_budget: Option<u64>, // 实例整个生命周期内剩余可执行的指令数
+        dropped_data: HashSet<u32>, // 已被 data.drop 丢弃的数据段
+        post_mortem: Option<PostMortem>, // 最近一次陷入时的状态快照
+        host_call_log: HostCallLog,
+        under_aligned_count: u64, // 对齐提示小于自然对齐的内存访问次数
+        tail_calls: bool,         // 尾位置的 call 复用当前函数的控制帧
+        breakpoints: HashSet<(FuncIdx, usize)>, // 函数索引和指令在函数体展开后的位置
+        suspended: Option<SuspendedCall>,       // 停在断点处的调用
+        debugging: bool, // 只有 invoke_debug 和 resume 执行时检查断点
+        write_audit: Option<WriteSink>, // 指令对线性内存的每次写入都交给回调
     }
 
     impl<'a> VM<'a> {
-        fn new(module: &Module) -> VM {
-            let memory: Memory;
-            if module.mem_sec.len() > 0 {
-                memory = Memory::new(module.mem_sec[0]);
-            } else {
-                memory = Memory::new(MemType { min: 0, max: None });
-            }
+        pub fn new(module: &Module) -> VM {
+            VM::with_config(module, VmConfig::default())
+        }
+
+        pub fn with_config(module: &Module, config: VmConfig) -> VM<'_> {
+            // 内存索引空间中导入的内存排在内部内存之前，导入的内存由实例自己创建
+            let imported_mem =
+                module.import_sec.iter().find_map(|imp| match imp.desc {
+                    ImportDesc::Mem(mem_type) => Some(mem_type),
+                    _ => None,
+                });
+            let memory = match imported_mem.or(module.mem_sec.first().copied())
+            {
+                Some(mem_type) => Memory::new(mem_type),
+                None => Memory::new(MemType::default()),
+            };
             let operand_stack = OperandStack::new();
             VM {
                 operand_stack,
@@ -319,56 +684,301 @@ pub mod interpreter {
                 globals: vec![],
                 control_stack: ControlStack::new(),
                 vm_funcs: vec![],
-                table: None,
+                sig_ids: vec![],
+                tables: vec![],
+                config,
+                oob_mode: OobMode::Trap,
+                instantiated: false,
+                instantiate_trap: None,
+                reserve_max_memory: false,
+                mocks: HashMap::new(),
+                host_funcs: HashMap::new(),
+                host_sigs: HashMap::new(),
+                coverage: None,
+                cur_flat_pc: 0,
+                instr_budget: None,
+                dropped_data: HashSet::new(),
+                post_mortem: None,
+                host_call_log: HostCallLog::Off,
+                under_aligned_count: 0,
+                tail_calls: false,
+                breakpoints: HashSet::new(),
+                suspended: None,
+                debugging: false,
+                write_audit: None,
             }
         }
 
-        fn init_table(&mut self) {
-            if self.module.table_sec.len() > 0 {
-                self.table = Some(Table::new(self.module.table_sec[0]));
-                for elem in &self.module.elem_sec {
-                    for instr in &elem.offset {
-                        self.exec_instr(instr);
-                    }
-                    let offset = self.operand_stack.pop_u32();
-                    for (idx, func_idx) in elem.init.iter().enumerate() {
-                        self.table.as_mut().unwrap().set_elem(
-                            offset as usize + idx,
-                            self.vm_funcs[*func_idx as usize].clone(),
-                        );
-                    }
+        /// 开启后，任何未按自然对齐（1/2/4/8 字节）的内存访问都会陷入，用于调试
+        pub fn strict_alignment(mut self, strict: bool) -> VM<'a> {
+            self.config.strict_alignment = strict;
+            self
+        }
+
+        pub fn config(&self) -> VmConfig {
+            self.config
+        }
+
+        /// 注册宿主函数，供模块以 module_name.name 导入，需要在实例化之前注册
+        pub fn register_host_func(
+            &mut self,
+            module_name: &str,
+            name: &str,
+            func: NativeFunc,
+        ) {
+            let key = (module_name.to_string(), name.to_string());
+            self.host_sigs.remove(&key);
+            self.host_funcs.insert(key, func);
+        }
+
+        /// 与 register_host_func 相同，同时声明宿主函数的签名。实例化时检查模块
+        /// 导入声明的类型与之一致，不一致时返回链接错误，避免按错误的类型传递参数
+        pub fn register_host_func_with_type(
+            &mut self,
+            module_name: &str,
+            name: &str,
+            func_type: FuncType,
+            func: NativeFunc,
+        ) {
+            self.register_host_func(module_name, name, func);
+            self.host_sigs
+                .insert((module_name.to_string(), name.to_string()), func_type);
+        }
+
+        /// 一次注册同一个模块名下的多个宿主函数，funcs 可以是数组、Vec 或 HashMap
+        pub fn register_host_module<'n, I>(
+            &mut self,
+            module_name: &str,
+            funcs: I,
+        ) where
+            I: IntoIterator<Item = (&'n str, NativeFunc)>,
+        {
+            for (name, func) in funcs {
+                self.register_host_func(module_name, name, func);
+            }
+        }
+
+        /// 设置实例生命周期内所有调用累计可执行的指令数，耗尽后调用以 OutOfFuel 陷入
+        pub fn set_total_instruction_budget(&mut self, budget: u64) {
+            self.instr_budget = Some(budget);
+        }
+
+        /// 开始记录宿主函数调用的参数和结果
+        pub fn record_host_calls(&mut self) {
+            self.host_call_log = HostCallLog::Record(vec![]);
+        }
+
+        /// 记录到的宿主函数调用，未开启记录时为空
+        pub fn host_calls(&self) -> &[HostCall] {
+            match &self.host_call_log {
+                HostCallLog::Record(calls) => calls,
+                _ => &[],
+            }
+        }
+
+        /// 用记录的结果代替宿主函数调用，使运行可以确定地重现；
+        /// 调用的函数、参数或记录的结果类型与当前模块不一致时以 ReplayDivergence 陷入
+        pub fn replay_host_calls(&mut self, calls: Vec<HostCall>) {
+            self.host_call_log = HostCallLog::Replay(calls.into());
+        }
+
+        /// 冻结线性内存，之后的写入以 MemoryReadOnly 陷入，memory.grow 返回 -1，读取不受影响
+        pub fn freeze_memory(&mut self) {
+            self.memory.read_only = true;
+        }
+
+        /// 解除冻结，恢复写入和增长
+        pub fn unfreeze_memory(&mut self) {
+            self.memory.read_only = false;
+        }
+
+        /// 用 mock 替换对指定函数的调用（包括间接调用），无需修改模块即可隔离测试
+        pub fn mock_function(&mut self, func_idx: FuncIdx, mock: MockFunc) {
+            self.mocks.insert(func_idx, mock);
+        }
+
+        /// 开启内存写入审计：store、memory.fill、memory.copy 和 memory.init
+        /// 成功写入后都会调用 sink，未开启时不做任何记录
+        pub fn audit_memory_writes(&mut self, sink: WriteSink) {
+            self.write_audit = Some(sink);
+        }
+
+        /// 开启后，实例化时按内存声明的最大页数一次性分配，避免增长时重新分配带来的延迟抖动
+        pub fn reserve_max_memory(mut self, reserve: bool) -> VM<'a> {
+            self.reserve_max_memory = reserve;
+            self
+        }
+
+        /// 开启毒化模式：线性内存初始为毒化值，load 读取从未写过的字节时以
+        /// UninitializedRead 陷入，数据段初始化也算作写入。用于调试，会额外占用内存
+        pub fn poison_memory(mut self, poison: bool) -> VM<'a> {
+            if poison {
+                self.memory.set_poison();
+            }
+            self
+        }
+
+        /// 开启后，函数体最后一条指令为 call 时复用当前函数的控制帧，
+        /// 尾递归函数的控制栈不再随递归深度增长，但陷入时的调用栈会缺少被替换的帧
+        pub fn optimize_tail_calls(mut self, enable: bool) -> VM<'a> {
+            self.tail_calls = enable;
+            self
+        }
+
+        /// 开启后操作数栈为每个槽额外记录值类型，便于调试类型混淆，关闭时不占用额外存储
+        pub fn typed_stack(mut self, typed: bool) -> VM<'a> {
+            self.operand_stack.set_typed(typed);
+            self
+        }
+
+        /// 返回操作数栈中每个槽的类型，未开启 typed_stack 时返回 None
+        pub fn operand_types(&self) -> Option<&[ValType]> {
+            self.operand_stack.types.as_deref()
+        }
+
+        /// 设置越界访问内存时的处理方式，默认为 OobMode::Trap
+        pub fn oob_memory_mode(mut self, mode: OobMode) -> VM<'a> {
+            self.oob_mode = mode;
+            self
+        }
+
+        fn init_table(&mut self) -> Result<(), Trap> {
+            self.tables = self
+                .module
+                .table_sec
+                .iter()
+                .map(|t| Table::new(*t))
+                .collect();
+            // 被动段和声明段不初始化表
+            let active = self
+                .module
+                .elem_sec
+                .iter()
+                .filter(|elem| elem.mode == ElemMode::Active);
+            for elem in active {
+                let offset = self.eval_const_expr(&elem.offset)? as u32;
+                let table = match self.tables.get_mut(elem.table as usize) {
+                    Some(table) => table,
+                    None => return Err(Trap::TableOutOfBounds),
+                };
+                // 写入前检查整个段，越界时表保持不变
+                if offset as usize + elem.init.len() > table.size() {
+                    return Err(Trap::TableOutOfBounds);
+                }
+                for (idx, func_idx) in elem.init.iter().enumerate() {
+                    let func = match self.vm_funcs.get(*func_idx as usize) {
+                        Some(func) => func.clone(),
+                        None => return Err(Trap::UndefinedElement),
+                    };
+                    table.set_elem(offset as usize + idx, func);
                 }
             }
+            Ok(())
         }
 
-        fn init_memory(&mut self) {
+        fn init_memory(&mut self) -> Result<(), Trap> {
             for data in &self.module.data_sec {
-                for instr in &data.offset {
-                    self.exec_instr(instr);
-                }
-                self.memory.write(
-                    self.operand_stack.pop_u64() as usize,
-                    &data.init[..],
-                );
+                let offset = self.eval_const_expr(&data.offset)? as u32;
+                self.memory.write(offset as usize, &data.init[..])?;
             }
+            Ok(())
         }
 
-        fn init_globals(&mut self) {
+        fn init_globals(&mut self) -> Result<(), Trap> {
             for global in &self.module.global_sec {
-                for instr in &global.init_expr {
-                    self.exec_instr(instr);
+                let val = self.eval_const_expr(&global.init_expr)?;
+                self.globals.push(GlobalVar::new(global.global_type, val));
+            }
+            Ok(())
+        }
+
+        // 常量表达式中除常量指令和 global.get 外，还允许扩展常量提案中的整数加、减、乘，
+        // 指令范围由校验器保证，这里直接在操作数栈上求值
+        fn eval_const_expr(&mut self, expr: &Expr) -> Result<u64, Trap> {
+            for instr in expr {
+                self.exec_instr(instr)?;
+            }
+            Ok(self.operand_stack.pop_u64())
+        }
+
+        /// 返回到目前为止的资源使用峰值，线性内存只会增长，因此当前页数即为峰值
+        pub fn run_stats(&self) -> RunStats {
+            RunStats {
+                peak_operand_stack: self.operand_stack.peak,
+                peak_control_stack: self.control_stack.peak,
+                peak_memory_pages: self.memory.size(),
+            }
+        }
+
+        /// 执行过的对齐提示（MemArg.align）小于操作自然对齐的 load/store 次数，
+        /// 次数多说明模块的内存布局可能存在非对齐访问
+        pub fn unaligned_access_count(&self) -> u64 {
+            self.under_aligned_count
+        }
+
+        /// 返回最近一次陷入时保存的解释器状态，没有发生过陷入时返回当前状态
+        pub fn post_mortem(&self) -> PostMortem {
+            match self.post_mortem.as_ref() {
+                Some(post_mortem) => post_mortem.clone(),
+                None => self.snapshot(None),
+            }
+        }
+
+        fn snapshot(&self, trap: Option<Trap>) -> PostMortem {
+            PostMortem {
+                trap,
+                operand_stack: self.operand_stack.slots.clone(),
+                frames: self
+                    .control_stack
+                    .frames
+                    .iter()
+                    .map(|cf| FrameInfo {
+                        func_idx: cf.func_idx,
+                        pc: cf.pc as usize,
+                        call: cf.opcode == OpCode::Call,
+                    })
+                    .collect(),
+                block: self
+                    .control_stack
+                    .frames
+                    .last()
+                    .map(|cf| cf.instrs.clone())
+                    .unwrap_or_default(),
+                globals: self.globals.iter().map(|g| g.get_as_u64()).collect(),
+                // 只复制字节，十六进制转储在查看时才生成
+                memory: self.memory.data.clone(),
+            }
+        }
+
+        /// 根据导出名查找函数索引
+        pub fn find_exported_func(&self, name: &str) -> Option<FuncIdx> {
+            for exp in &self.module.export_sec {
+                match exp.desc {
+                    ExportDesc::Func(idx) if exp.name == name => {
+                        return Some(idx)
+                    }
+                    _ => {}
                 }
-                self.globals.push(GlobalVar::new(
-                    global.global_type,
-                    self.operand_stack.pop_u64(),
-                ));
             }
+            None
         }
 
-        fn get_main_idx(&self) -> Option<u32> {
+        /// 开启后记录每个函数中哪些指令至少执行过一次，见 coverage()
+        pub fn track_coverage(mut self, enabled: bool) -> VM<'a> {
+            self.coverage = if enabled { Some(HashMap::new()) } else { None };
+            self
+        }
+
+        /// 指令覆盖情况，按函数索引分组，下标为指令在函数体先序展开后的位置
+        pub fn coverage(&self) -> HashMap<FuncIdx, Vec<bool>> {
+            self.coverage.clone().unwrap_or_default()
+        }
+
+        /// 根据导出名查找内存索引
+        pub fn find_exported_memory(&self, name: &str) -> Option<MemIdx> {
             for exp in &self.module.export_sec {
                 match exp.desc {
-                    ExportDesc::Func(idx) if exp.name == "main" => {
+                    ExportDesc::Mem(idx) if exp.name == name => {
                         return Some(idx)
                     }
                     _ => {}
@@ -377,37 +987,362 @@ pub mod interpreter {
             None
         }
 
-        pub fn exec_main(module: &Module) {
-            let mut vm = VM::new(module);
-            vm.init_memory();
-            vm.init_globals();
-            vm.init_funcs();
-            vm.init_table();
-            if let Some(start_sec_id) = module.start_sec {
-                vm.call(&Some(Rc::new(start_sec_id)));
+        /// 读取内存，name 为内存的导出名，为 None 时读取默认内存
+        pub fn read_memory(
+            &mut self,
+            name: Option<&str>,
+            offset: usize,
+            buf: &mut [u8],
+        ) -> Result<(), Error> {
+            self.get_memory(name)?.read(offset, buf)?;
+            Ok(())
+        }
+
+        /// 写入内存，name 为内存的导出名，为 None 时写入默认内存
+        pub fn write_memory(
+            &mut self,
+            name: Option<&str>,
+            offset: usize,
+            data: &[u8],
+        ) -> Result<(), Error> {
+            self.get_memory(name)?.write(offset, data)?;
+            Ok(())
+        }
+
+        fn get_memory(
+            &mut self,
+            name: Option<&str>,
+        ) -> Result<&mut Memory, Error> {
+            let idx = match name {
+                Some(name) => match self.find_exported_memory(name) {
+                    Some(idx) => idx,
+                    None => {
+                        return Err(Error::ExportNotFound(name.to_string()))
+                    }
+                },
+                None => 0,
+            };
+            // 目前只支持一个内存，唯一有效的内存索引是 0
+            if idx != 0 {
+                return Err(Error::Link(format!(
+                    "unknown memory index: {}",
+                    idx
+                )));
+            }
+            Ok(&mut self.memory)
+        }
+
+        /// 实例化模块：初始化内存、全局变量、函数和表，如果有起始函数则执行起始函数。
+        /// 重复调用不会再次初始化
+        pub fn instantiate(&mut self) -> Result<(), Error> {
+            if self.instantiated {
+                return match self.instantiate_trap {
+                    Some(trap) => Err(trap.into()),
+                    None => Ok(()),
+                };
+            }
+            self.check_imports()?;
+            self.instantiated = true;
+            // 初始化中途陷入时实例只构建了一部分，不能再次初始化，也不能执行
+            if let Err(trap) = self.init_instance() {
+                self.instantiate_trap = Some(trap);
+                return Err(trap.into());
+            }
+            Ok(())
+        }
+
+        fn init_instance(&mut self) -> Result<(), Trap> {
+            if self.reserve_max_memory {
+                self.memory.reserve_max();
+            }
+            self.init_memory()?;
+            self.init_globals()?;
+            self.init_sig_ids();
+            self.init_funcs();
+            self.init_table()?;
+            if let Some(start_idx) = self.module.start_sec {
+                self.exec_func(start_idx)?;
+            }
+            Ok(())
+        }
+
+        /// 实例化模块，如果模块没有起始函数，则执行导出的 main 函数
+        pub fn run_main(&mut self) -> Result<(), Error> {
+            self.instantiate()?;
+            if self.module.start_sec.is_none() {
+                self.invoke("main", &[])?;
+            }
+            Ok(())
+        }
+
+        /// 调用导出函数，如果模块尚未实例化则先实例化，多次调用共享同一个实例的状态
+        pub fn invoke(
+            &mut self,
+            name: &str,
+            args: &[WasmValue],
+        ) -> Result<Vec<WasmValue>, Error> {
+            let (idx, func_type) = self.push_invoke_args(name, args)?;
+            self.exec_func(idx)?;
+            Ok(self.pop_results(&func_type))
+        }
+
+        /// 在函数 func_idx 的第 pc 条指令执行前暂停，pc 是指令在函数体先序展开后的位置，
+        /// 与 coverage() 的下标一致。断点可以在调用开始前或暂停期间设置
+        pub fn set_breakpoint(&mut self, func_idx: FuncIdx, pc: usize) {
+            self.breakpoints.insert((func_idx, pc));
+        }
+
+        pub fn clear_breakpoint(&mut self, func_idx: FuncIdx, pc: usize) {
+            self.breakpoints.remove(&(func_idx, pc));
+        }
+
+        /// 与 invoke 相同，但遇到断点时暂停并返回 StepResult::BreakpointHit，
+        /// 暂停期间可以查看局部变量和内存，之后用 resume 继续执行。
+        /// 已有调用暂停时不能再次调用
+        pub fn invoke_debug(
+            &mut self,
+            name: &str,
+            args: &[WasmValue],
+        ) -> Result<StepResult, Error> {
+            if self.suspended.is_some() {
+                return Err(Error::Debugger(
+                    "a debug call is already suspended".to_string(),
+                ));
+            }
+            let (idx, func_type) = self.push_invoke_args(name, args)?;
+            let depth = self.control_stack.control_depth();
+            let sp = self.operand_stack.length() - args.len();
+            let local_0_idx = self.local_0_idx;
+            self.suspended = Some(SuspendedCall {
+                func_type,
+                depth,
+                sp,
+                local_0_idx,
+                skip_break: false,
+            });
+            if let Err(trap) = self.call(&Some(Rc::new(idx))) {
+                self.suspended = None;
+                self.unwind(depth, sp, local_0_idx, trap);
+                return Err(trap.into());
+            }
+            self.resume()
+        }
+
+        /// 从断点处继续执行，直到遇到下一个断点或调用返回。没有暂停的调用时返回错误
+        pub fn resume(&mut self) -> Result<StepResult, Error> {
+            let (depth, sp, local_0_idx) = match &self.suspended {
+                Some(call) => (call.depth, call.sp, call.local_0_idx),
+                None => {
+                    return Err(Error::Debugger(
+                        "no suspended call to resume".to_string(),
+                    ))
+                }
+            };
+            self.debugging = true;
+            let result = if self.control_stack.control_depth() > depth {
+                self.main_loop(depth + 1)
             } else {
-                if let Some(idx) = vm.get_main_idx() {
-                    vm.call(&Some(Rc::new(idx)));
-                } else {
-                    panic!("No start sec!");
+                Ok(())
+            };
+            self.debugging = false;
+            if let Err(trap) = result {
+                self.suspended = None;
+                self.unwind(depth, sp, local_0_idx, trap);
+                return Err(trap.into());
+            }
+            // 控制帧还没有全部退出，说明停在了断点处
+            if self.control_stack.control_depth() > depth {
+                let cf = self.control_stack.frames.last().unwrap();
+                let hit = StepResult::BreakpointHit {
+                    func_idx: cf.func_idx,
+                    pc: cf.flat_pc,
+                };
+                self.suspended.as_mut().unwrap().skip_break = true;
+                return Ok(hit);
+            }
+            let call = self.suspended.take().unwrap();
+            Ok(StepResult::Done(self.pop_results(&call.func_type)))
+        }
+
+        /// 当前函数的第 idx 个局部变量（包括参数），用于在断点处查看状态
+        pub fn local(&self, idx: u32) -> Option<WasmValue> {
+            let cf = self.control_stack.top_call_frame().0?;
+            let f = &self.vm_funcs[cf.func_idx as usize];
+            let locals = f.code.as_ref()?.locals.iter().flat_map(|locals| {
+                std::iter::repeat_n(locals.val_type, locals.n as usize)
+            });
+            let val_type = f
+                .func_type
+                .params_types
+                .iter()
+                .copied()
+                .chain(locals)
+                .nth(idx as usize)?;
+            let val = self.operand_stack.get_operand(cf.bp + idx as usize);
+            Some(WasmValue::from_u64(val_type, val))
+        }
+
+        // 检查导出函数和参数，并将参数压入操作数栈
+        fn push_invoke_args(
+            &mut self,
+            name: &str,
+            args: &[WasmValue],
+        ) -> Result<(FuncIdx, FuncType), Error> {
+            self.instantiate()?;
+            let idx = match self.find_exported_func(name) {
+                Some(idx) => idx,
+                None => return Err(Error::ExportNotFound(name.to_string())),
+            };
+            let func_type = self.vm_funcs[idx as usize].func_type.clone();
+            if args.len() != func_type.params_types.len()
+                || args
+                    .iter()
+                    .zip(func_type.params_types.iter())
+                    .any(|(arg, val_type)| arg.val_type() != *val_type)
+            {
+                return Err(Error::ArgumentMismatch(format!(
+                    "{} expects {}",
+                    name, func_type
+                )));
+            }
+            // 函数引用必须指向函数索引空间中的函数
+            for arg in args {
+                if let WasmValue::FuncRef(Some(func_idx)) = arg {
+                    if *func_idx as usize >= self.vm_funcs.len() {
+                        return Err(Error::ArgumentMismatch(format!(
+                            "{} is not a valid function reference",
+                            func_idx
+                        )));
+                    }
                 }
             }
-            vm.main_loop();
+            for arg in args {
+                self.operand_stack.push_typed(arg.to_u64(), arg.val_type());
+            }
+            Ok((idx, func_type))
+        }
+
+        fn pop_results(&mut self, func_type: &FuncType) -> Vec<WasmValue> {
+            let results =
+                self.operand_stack.pop_u64s(func_type.result_types.len());
+            results
+                .into_iter()
+                .zip(func_type.result_types.iter())
+                .map(|(val, val_type)| WasmValue::from_u64(*val_type, val))
+                .collect()
         }
 
-        fn main_loop(&mut self) {
+        // 执行函数直到其返回，参数已在栈顶。陷入时将栈恢复到调用前的状态
+        fn exec_func(&mut self, idx: FuncIdx) -> Result<(), Trap> {
             let depth = self.control_stack.control_depth();
+            let param_count =
+                self.vm_funcs[idx as usize].func_type.params_types.len();
+            let sp = self.operand_stack.length() - param_count;
+            let local_0_idx = self.local_0_idx;
+            let mut result = self.call(&Some(Rc::new(idx)));
+            // 调用外部函数时不会进入新的控制帧
+            if result.is_ok() && self.control_stack.control_depth() > depth {
+                result = self.main_loop(depth + 1);
+            }
+            if let Err(trap) = result {
+                self.unwind(depth, sp, local_0_idx, trap);
+            }
+            result
+        }
+
+        // 陷入时保存快照，并将栈恢复到调用前的状态
+        fn unwind(
+            &mut self,
+            depth: usize,
+            sp: usize,
+            local_0_idx: usize,
+            trap: Trap,
+        ) {
+            self.post_mortem = Some(self.snapshot(Some(trap)));
+            self.control_stack.frames.truncate(depth);
+            self.operand_stack.truncate(sp);
+            self.local_0_idx = local_0_idx;
+        }
+
+        // 执行指令直到控制栈深度低于 depth，调试执行时遇到断点提前返回
+        fn main_loop(&mut self, depth: usize) -> Result<(), Trap> {
             // 执行栈帧中的每条指令
             while self.control_stack.control_depth() >= depth {
                 let cf = self.control_stack.top_control_frame();
                 if cf.pc as usize == cf.instrs.len() {
                     self.exit_block(); // 已经执行完了一个control frame
                 } else {
+                    let loc = (cf.func_idx, cf.flat_pc);
+                    if self.debugging && self.check_breakpoint(loc) {
+                        return Ok(());
+                    }
+                    let cf = self.control_stack.top_control_frame();
+                    if let Some(budget) = self.instr_budget.as_mut() {
+                        if *budget == 0 {
+                            return Err(Trap::OutOfFuel);
+                        }
+                        *budget -= 1;
+                    }
                     let instr = cf.instrs[cf.pc as usize].clone();
                     cf.pc += 1;
-                    self.exec_instr(&instr);
+                    // 覆盖率、断点和写入审计都按指令在函数体展开后的位置定位，
+                    // 调试执行时始终记录，暂停期间新设置的断点也能命中
+                    if self.coverage.is_some()
+                        || !self.breakpoints.is_empty()
+                        || self.write_audit.is_some()
+                        || self.debugging
+                    {
+                        self.cur_flat_pc = cf.flat_pc;
+                        cf.flat_pc += instr_flat_len(&instr);
+                    }
+                    if let Some(coverage) = self.coverage.as_mut() {
+                        let covered = coverage.get_mut(&cf.func_idx).unwrap();
+                        covered[self.cur_flat_pc] = true;
+                    }
+                    self.exec_instr(&instr)?;
+                    if self.config.canonicalize_nans {
+                        self.canonicalize_nan(instr.opcode);
+                    }
                 }
             }
+            Ok(())
+        }
+
+        // 从断点恢复后的第一条指令不再暂停
+        fn check_breakpoint(&mut self, loc: (FuncIdx, usize)) -> bool {
+            let call = self.suspended.as_mut().unwrap();
+            if call.skip_break {
+                call.skip_break = false;
+                return false;
+            }
+            self.breakpoints.contains(&loc)
+        }
+
+        // 浮点算术指令的结果为 NaN 时替换为规范 NaN，abs、neg 和 copysign 只操作符号位，不做处理
+        fn canonicalize_nan(&mut self, opcode: OpCode) {
+            let is_f32 = (opcode >= OpCode::F32Ceil
+                && opcode <= OpCode::F32Max)
+                || opcode == OpCode::F32DemoteF64;
+            let is_f64 = (opcode >= OpCode::F64Ceil
+                && opcode <= OpCode::F64Max)
+                || opcode == OpCode::F64PromoteF32;
+            if is_f32 {
+                let val = self.operand_stack.pop_f32();
+                let val = if val.is_nan() {
+                    f32::from_bits(0x7fc0_0000)
+                } else {
+                    val
+                };
+                self.operand_stack.push_f32(val);
+            } else if is_f64 {
+                let val = self.operand_stack.pop_f64();
+                let val = if val.is_nan() {
+                    f64::from_bits(0x7ff8_0000_0000_0000)
+                } else {
+                    val
+                };
+                self.operand_stack.push_f64(val);
+            }
         }
 
         fn enter_block(
@@ -415,10 +1350,13 @@ pub mod interpreter {
             opcode: OpCode,
             bt: FuncType,
             instrs: Vec<Instruction>,
+            func_idx: u32,
+            flat_base: usize,
         ) {
             // enter_block 时参数已在栈顶(调用方将参数入栈)
             let bp = self.operand_stack.length() - bt.params_types.len();
-            let cf = ControlFrame::new(opcode, bt, instrs, bp);
+            let cf =
+                ControlFrame::new(opcode, bt, instrs, bp, func_idx, flat_base);
             self.control_stack.push_control_frame(cf);
             if opcode == OpCode::Call {
                 self.local_0_idx = bp;
@@ -431,15 +1369,9 @@ pub mod interpreter {
         }
 
         fn clear_block(&mut self, cf: ControlFrame) {
-            // 弹出结果，结果已在栈顶
-            let mut results = self
-                .operand_stack
-                .pop_u64s(cf.block_type.result_types.len());
-            // 清除其他变量（比如局部变量和参数）
+            // 结果已在栈顶，保留结果并清除其他变量（比如局部变量和参数）
             self.operand_stack
-                .pop_u64s(self.operand_stack.length() - cf.bp);
-            // 将结果放回到栈顶
-            self.operand_stack.push_u64s(&mut results);
+                .drop_below_top(cf.bp, cf.block_type.result_types.len());
             if cf.opcode == OpCode::Call
                 && self.control_stack.control_depth() > 0
             {
@@ -450,17 +1382,13 @@ pub mod interpreter {
         }
 
         fn _reset_block(&mut self, cf: &ControlFrame) {
-            let mut results = self
-                .operand_stack
-                .pop_u64s(cf.block_type.params_types.len());
             self.operand_stack
-                .pop_u64s(self.operand_stack.length() - cf.bp);
-            self.operand_stack.push_u64s(&mut results);
+                .drop_below_top(cf.bp, cf.block_type.params_types.len());
         }
 
-        fn exec_instr(&mut self, instr: &Instruction) {
+        fn exec_instr(&mut self, instr: &Instruction) -> Result<(), Trap> {
             match instr.opcode {
-                OpCode::Call => self.call(&instr.args),
+                OpCode::Call => self.call_instr(&instr.args),
                 OpCode::Drop => self.drop_value(&instr.args),
                 OpCode::Select => self.select(&instr.args),
                 OpCode::I32Const => self.i32_const(&instr.args),
@@ -633,6 +1561,12 @@ pub mod interpreter {
                 OpCode::LocalTee => self.local_tee(&instr.args),
                 OpCode::GlobalGet => self.global_get(&instr.args),
                 OpCode::GlobalSet => self.global_set(&instr.args),
+                OpCode::TableGet => self.table_get(&instr.args),
+                OpCode::TableSet => self.table_set(&instr.args),
+                OpCode::RefNull => self.ref_null(&instr.args),
+                OpCode::RefFunc => self.ref_func(&instr.args),
+                // GC 提案中的指令目前只能解码，不能执行
+                OpCode::Gc => Err(Trap::UnsupportedInstruction),
                 OpCode::Br => self.br(&instr.args),
                 OpCode::BrTable => self.br_table(&instr.args),
                 OpCode::BrIf => self.br_if(&instr.args),
@@ -643,7 +1577,59 @@ pub mod interpreter {
                 OpCode::CallIndirect => self.call_indrect(&instr.args),
                 OpCode::Unreachable => self.unreachable(&instr.args),
                 OpCode::Nop => self.nop(&instr.args),
-                _ => {}
+                OpCode::TruncSat => self.prefixed_instr(&instr.args),
+                _ => Ok(()),
+            }
+        }
+
+        // 链接前检查导入函数声明的类型索引，避免畸形模块在链接时越界
+        fn check_imports(&self) -> Result<(), Error> {
+            let type_count = self.module.type_sec.len();
+            for imp in &self.module.import_sec {
+                if let ImportDesc::Func(type_idx) = imp.desc {
+                    if type_idx as usize >= type_count {
+                        return Err(Error::Link(format!(
+                            "import {}.{}: type index {} out of range ({} types)",
+                            imp.module_name,
+                            imp.member_name,
+                            type_idx,
+                            type_count
+                        )));
+                    }
+                    let key =
+                        (imp.module_name.clone(), imp.member_name.clone());
+                    // 未解析的导入不能跳过，否则之后的函数索引都会错位
+                    if self.resolve_import(&key).is_none() {
+                        return Err(Error::Link(format!(
+                            "unresolved import {}.{}",
+                            imp.module_name, imp.member_name
+                        )));
+                    }
+                    let declared = &self.module.type_sec[type_idx as usize];
+                    match self.host_sigs.get(&key) {
+                        Some(expected) if expected != declared => {
+                            return Err(Error::Link(format!(
+                                "import {}.{}: module declares {}, host function has {}",
+                                imp.module_name,
+                                imp.member_name,
+                                declared,
+                                expected
+                            )));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        // 为每个不同的函数类型分配一个 ID，间接调用时只需比较整数
+        fn init_sig_ids(&mut self) {
+            let mut ids: HashMap<&FuncType, u32> = HashMap::new();
+            for func_type in &self.module.type_sec {
+                let next_id = ids.len() as u32;
+                let id = *ids.entry(func_type).or_insert(next_id);
+                self.sig_ids.push(id);
             }
         }
 
@@ -651,7 +1637,9 @@ pub mod interpreter {
             self.link_native_funcs();
             for (idx, func_idx) in self.module.func_sec.iter().enumerate() {
                 self.vm_funcs.push(VMFunc::new_internal_func(
+                    self.vm_funcs.len() as u32,
                     self.module.type_sec[*func_idx as usize].clone(),
+                    self.sig_ids[*func_idx as usize],
                     self.module.code_sec[idx].clone(),
                 ));
             }
@@ -710,99 +1698,186 @@ pub mod interpreter {
             vec![]
         }
 
+        // 导入函数的实现：优先使用注册的宿主函数，其次是 env 中的内置函数
+        fn resolve_import(&self, key: &(String, String)) -> Option<NativeFunc> {
+            if let Some(native_func) = self.host_funcs.get(key) {
+                return Some(*native_func);
+            }
+            if key.0 != "env" {
+                return None;
+            }
+            let native_func: NativeFunc = match key.1.as_str() {
+                "print_char" => VM::print_char,
+                "assert_true" => VM::assert_true,
+                "assert_false" => VM::assert_false,
+                "assert_eq_i32" => VM::assert_eq_i32,
+                "assert_eq_i64" => VM::assert_eq_i64,
+                "assert_eq_f32" => VM::assert_eq_f32,
+                "assert_eq_f64" => VM::assert_eq_f64,
+                _ => return None,
+            };
+            Some(native_func)
+        }
+
+        // check_imports 已保证每个导入函数都能解析
         fn link_native_funcs(&mut self) {
             for imp in &self.module.import_sec {
-                if imp.module_name == "env" {
-                    match imp.desc {
-                        ImportDesc::Func(func_idx) => {
-                            let ft =
-                                self.module.type_sec[func_idx as usize].clone();
-                            match imp.member_name.as_str() {
-                                "print_char" => {
-                                    self.vm_funcs.push(
-                                        VMFunc::new_external_func(
-                                            ft,
-                                            VM::print_char,
-                                        ),
-                                    );
-                                }
-                                "assert_true" => {
-                                    self.vm_funcs.push(
-                                        VMFunc::new_external_func(
-                                            ft,
-                                            VM::assert_true,
-                                        ),
-                                    );
-                                }
-                                "assert_false" => {
-                                    self.vm_funcs.push(
-                                        VMFunc::new_external_func(
-                                            ft,
-                                            VM::assert_false,
-                                        ),
-                                    );
-                                }
-                                "assert_eq_i32" => {
-                                    self.vm_funcs.push(
-                                        VMFunc::new_external_func(
-                                            ft,
-                                            VM::assert_eq_i32,
-                                        ),
-                                    );
-                                }
-                                "assert_eq_i64" => {
-                                    self.vm_funcs.push(
-                                        VMFunc::new_external_func(
-                                            ft,
-                                            VM::assert_eq_i64,
-                                        ),
-                                    );
-                                }
-                                "assert_eq_f32" => {
-                                    self.vm_funcs.push(
-                                        VMFunc::new_external_func(
-                                            ft,
-                                            VM::assert_eq_f32,
-                                        ),
-                                    );
-                                }
-                                "assert_eq_f64" => {
-                                    self.vm_funcs.push(
-                                        VMFunc::new_external_func(
-                                            ft,
-                                            VM::assert_eq_f64,
-                                        ),
-                                    );
-                                }
-                                _ => {
-                                    panic!("Should not reach here.");
-                                }
-                            }
-                        }
-                        _ => {}
+                if let ImportDesc::Func(type_idx) = imp.desc {
+                    let key =
+                        (imp.module_name.clone(), imp.member_name.clone());
+                    if let Some(native_func) = self.resolve_import(&key) {
+                        let ft =
+                            self.module.type_sec[type_idx as usize].clone();
+                        self.vm_funcs.push(VMFunc::new_external_func(
+                            self.vm_funcs.len() as u32,
+                            ft,
+                            self.sig_ids[type_idx as usize],
+                            native_func,
+                        ));
                     }
                 }
             }
         }
 
         fn call_internal_func(&mut self, func: &VMFunc) {
+            let expr = func.code.clone().unwrap().expr;
+            if let Some(coverage) = self.coverage.as_mut() {
+                coverage
+                    .entry(func.idx)
+                    .or_insert_with(|| vec![false; flat_len(&expr)]);
+            }
             self.enter_block(
                 OpCode::Call,
                 func.func_type.clone(),
-                func.code.clone().unwrap().expr,
+                expr,
+                func.idx,
+                0,
             );
             // alloc locals
-            let local_cnt = func.code.as_ref().unwrap().get_local_count();
-            for _ in 0..local_cnt {
-                self.operand_stack.push_u64(0);
+            for locals in &func.code.as_ref().unwrap().locals {
+                for _ in 0..locals.n {
+                    self.operand_stack.push_typed(0, locals.val_type);
+                }
             }
         }
 
-        fn call_external_func(&mut self, f: &VMFunc) {
-            let args = self.pop_args(&f.func_type);
+        fn call_external_func(&mut self, f: &VMFunc) -> Result<(), Trap> {
+            let ft = &f.func_type;
+            let logged = match self.host_call_log {
+                HostCallLog::Off => None,
+                _ => Some(self.top_values(&ft.params_types)),
+            };
+            if let HostCallLog::Replay(calls) = &mut self.host_call_log {
+                let call = calls.pop_front();
+                return self.replay_host_call(f, logged.unwrap(), call);
+            }
+            let args = self.pop_args(ft);
             let results = f.native_func.unwrap()(args);
-            self.push_results(&f.func_type, results);
-        }
+            // 返回值个数与声明不符时直接陷入，避免破坏操作数栈
+            if results.len() != ft.result_types.len() {
+                return Err(Trap::HostResultMismatch);
+            }
+            self.push_results(ft, results);
+            if let Some(args) = logged {
+                let (module_name, member_name) = self.import_name(f.idx);
+                let results = self.top_values(&ft.result_types);
+                if let HostCallLog::Record(calls) = &mut self.host_call_log {
+                    calls.push(HostCall {
+                        module_name,
+                        member_name,
+                        args,
+                        results,
+                    });
+                }
+            }
+            Ok(())
+        }
+
+        fn replay_host_call(
+            &mut self,
+            f: &VMFunc,
+            args: Vec<WasmValue>,
+            call: Option<HostCall>,
+        ) -> Result<(), Trap> {
+            let ft = &f.func_type;
+            let (module_name, member_name) = self.import_name(f.idx);
+            // 按位比较参数，NaN 也能匹配；记录的结果必须符合函数签名，否则会破坏操作数栈
+            let call = match call {
+                Some(call)
+                    if call.module_name == module_name
+                        && call.member_name == member_name
+                        && call
+                            .args
+                            .iter()
+                            .map(WasmValue::to_u64)
+                            .eq(args.iter().map(WasmValue::to_u64))
+                        && results_match(&call.results, &ft.result_types) =>
+                {
+                    call
+                }
+                _ => return Err(Trap::ReplayDivergence),
+            };
+            let len = self.operand_stack.length();
+            self.operand_stack.truncate(len - args.len());
+            for result in call.results {
+                self.operand_stack
+                    .push_typed(result.to_u64(), result.val_type());
+            }
+            Ok(())
+        }
+
+        // 栈顶的若干个值，不出栈
+        fn top_values(&self, types: &[ValType]) -> Vec<WasmValue> {
+            let base = self.operand_stack.length() - types.len();
+            types
+                .iter()
+                .enumerate()
+                .map(|(i, vt)| {
+                    WasmValue::from_u64(
+                        *vt,
+                        self.operand_stack.get_operand(base + i),
+                    )
+                })
+                .collect()
+        }
+
+        // 导入函数的模块名和成员名，func_idx 必须是导入函数
+        fn import_name(&self, func_idx: FuncIdx) -> (String, String) {
+            let imp = self
+                .module
+                .import_sec
+                .iter()
+                .filter(|imp| matches!(imp.desc, ImportDesc::Func(_)))
+                .nth(func_idx as usize)
+                .unwrap();
+            (imp.module_name.clone(), imp.member_name.clone())
+        }
+
+        // 如果函数被 mock，则调用 mock 并返回 true
+        fn call_mocked_func(&mut self, f: &VMFunc) -> Result<bool, Trap> {
+            let mock = match self.mocks.get_mut(&f.idx) {
+                Some(mock) => mock,
+                None => return Ok(false),
+            };
+            let ft = &f.func_type;
+            let args = self.operand_stack.pop_u64s(ft.params_types.len());
+            let args = ft
+                .params_types
+                .iter()
+                .zip(args)
+                .map(|(vt, val)| WasmValue::from_u64(*vt, val))
+                .collect();
+            let results = mock(args);
+            // 与宿主函数一样，返回值和签名不符时陷入
+            if !results_match(&results, &ft.result_types) {
+                return Err(Trap::HostResultMismatch);
+            }
+            for result in results {
+                self.operand_stack
+                    .push_typed(result.to_u64(), result.val_type());
+            }
+            Ok(true)
+        }
 
         fn pop_args(&mut self, ft: &FuncType) -> Vec<Box<dyn Any>> {
             let mut args = Vec::with_capacity(ft.params_types.len());
@@ -814,9 +1889,9 @@ pub mod interpreter {
         }
 
         fn push_results(&mut self, ft: &FuncType, results: Vec<Box<dyn Any>>) {
-            for result in results {
-                let val = self.unwrap_u64(&ft.result_types[0], result);
-                self.operand_stack.push_u64(val);
+            for (result_type, result) in ft.result_types.iter().zip(results) {
+                let val = self.unwrap_u64(result_type, result);
+                self.operand_stack.push_typed(val, *result_type);
             }
         }
 
@@ -828,7 +1903,11 @@ pub mod interpreter {
                     Box::new(f32::from_le_bytes((val as u32).to_le_bytes()))
                 }
                 ValType::F64 => Box::new(f64::from_le_bytes(val.to_le_bytes())),
-                ValType::FuncRef => panic!("Unreachable."),
+                // 函数引用在宿主侧表示为 Option<FuncIdx>，None 为空引用
+                ValType::FuncRef if val == NULL_REF => {
+                    Box::new(None::<FuncIdx>)
+                }
+                ValType::FuncRef => Box::new(Some(val as FuncIdx)),
             }
         }
 
@@ -841,778 +1920,1261 @@ pub mod interpreter {
                 ValType::I64 => {
                     val_ref.downcast_ref::<i64>().unwrap().to_owned() as u64
                 }
-                ValType::F32 => u64::from_le_bytes(
-                    (val_ref.downcast_ref::<f32>().unwrap().to_owned() as f64)
-                        .to_le_bytes(),
-                ),
+                // f32 在操作数栈中保存的是它自己的位模式
+                ValType::F32 => {
+                    val_ref.downcast_ref::<f32>().unwrap().to_bits() as u64
+                }
                 ValType::F64 => u64::from_le_bytes(
                     val_ref.downcast_ref::<f64>().unwrap().to_le_bytes(),
                 ),
-                ValType::FuncRef => panic!("Unreachable."),
+                ValType::FuncRef => {
+                    match val_ref.downcast_ref::<Option<FuncIdx>>().unwrap() {
+                        Some(idx) => *idx as u64,
+                        None => NULL_REF,
+                    }
+                }
+            }
+        }
+
+        fn call_instr(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let idx = args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
+            let f = &self.vm_funcs[*idx as usize];
+            // 只优化调用内部函数的情况，宿主函数和模拟函数不会进入新的控制帧
+            if self.tail_calls
+                && f.code.is_some()
+                && !self.mocks.contains_key(&f.idx)
+                && self.in_tail_position(f)
+            {
+                let f = f.clone();
+                // 被调函数的参数已在栈顶，丢弃当前函数的局部变量后替换控制帧
+                let cf = self.control_stack.pop_control_frame();
+                self.operand_stack
+                    .drop_below_top(cf.bp, f.func_type.params_types.len());
+                self.call_internal_func(&f);
+                return Ok(());
+            }
+            self.call(args)
+        }
+
+        // 栈顶是函数体的控制帧，且刚执行的是其最后一条指令；调用者与被调函数的
+        // 结果类型相同，栈上除被调函数的参数外只有调用者的参数和局部变量
+        fn in_tail_position(&self, callee: &VMFunc) -> bool {
+            let cf = match self.control_stack.frames.last() {
+                Some(cf) => cf,
+                None => return false,
+            };
+            if cf.opcode != OpCode::Call || cf.pc as usize != cf.instrs.len() {
+                return false;
+            }
+            let caller = &self.vm_funcs[cf.func_idx as usize];
+            if caller.func_type.result_types != callee.func_type.result_types {
+                return false;
             }
+            let local_count: usize = match caller.code.as_ref() {
+                Some(code) => code.locals.iter().map(|l| l.n as usize).sum(),
+                None => return false,
+            };
+            let frame_len = caller.func_type.params_types.len() + local_count;
+            let params = callee.func_type.params_types.len();
+            self.operand_stack.length().checked_sub(params)
+                == Some(cf.bp + frame_len)
         }
 
-        fn call(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn call(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let idx = args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
             let f = self.vm_funcs[*idx as usize].clone();
+            if self.call_mocked_func(&f)? {
+                return Ok(());
+            }
             if f.code.is_some() {
                 self.call_internal_func(&f);
             } else if f.native_func.is_some() {
-                self.call_external_func(&f);
+                self.call_external_func(&f)?;
             }
+            Ok(())
         }
 
         // 参数指令实现
-        fn drop_value(&mut self, _arg: &Option<Rc<dyn Any>>) {
+        fn drop_value(
+            &mut self,
+            _arg: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             self.operand_stack.pop_u64();
+            Ok(())
         }
 
-        fn select(&mut self, _arg: &Option<Rc<dyn Any>>) {
+        fn select(&mut self, _arg: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
+            // 条件为真时保留第一个操作数，否则保留第二个
             let v1 = self.operand_stack.pop_bool();
-            let v2 = self.operand_stack.pop_u64();
-            let v3 = self.operand_stack.pop_u64();
+            let len = self.operand_stack.length();
             if v1 {
-                self.operand_stack.push_u64(v3);
+                self.operand_stack.remove_operand(len - 1);
             } else {
-                self.operand_stack.push_u64(v2);
+                self.operand_stack.remove_operand(len - 2);
             }
+            Ok(())
         }
 
         // 数值指令实现
         // part 1: 常量指令，共4条
-        fn i32_const(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn i32_const(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let arg = args.as_ref().unwrap().downcast_ref::<i32>().unwrap();
             self.operand_stack.push_i32(*arg);
+            Ok(())
         }
 
-        fn i64_const(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn i64_const(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let arg = args.as_ref().unwrap().downcast_ref::<i64>().unwrap();
             self.operand_stack.push_i64(*arg);
+            Ok(())
         }
 
-        fn f32_const(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn f32_const(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let arg = args.as_ref().unwrap().downcast_ref::<f32>().unwrap();
             self.operand_stack.push_f32(*arg);
+            Ok(())
         }
 
-        fn f64_const(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn f64_const(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let arg = args.as_ref().unwrap().downcast_ref::<f64>().unwrap();
             self.operand_stack.push_f64(*arg);
+            Ok(())
         }
 
         // part2: 测试指令
-        fn i32_eqz(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_eqz(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let value = self.operand_stack.pop_i32();
             self.operand_stack.push_bool(value == 0);
+            Ok(())
         }
 
-        fn i64_eqz(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_eqz(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let value = self.operand_stack.pop_i64();
             self.operand_stack.push_bool(value == 0);
+            Ok(())
         }
 
         // part2: 比较指令，共32条
         // i32 相关
-        fn i32_eq(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_eq(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u32();
             let v1 = self.operand_stack.pop_u32();
             self.operand_stack.push_bool(v1 == v2);
+            Ok(())
         }
 
-        fn i32_neq(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_neq(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u32();
             let v1 = self.operand_stack.pop_u32();
             self.operand_stack.push_bool(v1 != v2);
+            Ok(())
         }
 
-        fn i32_lts(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_lts(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
             self.operand_stack.push_bool(v1 < v2);
+            Ok(())
         }
 
-        fn i32_ltu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_ltu(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u32();
             let v1 = self.operand_stack.pop_u32();
             self.operand_stack.push_bool(v1 < v2);
+            Ok(())
         }
 
-        fn i32_gts(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_gts(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
             self.operand_stack.push_bool(v1 > v2);
+            Ok(())
         }
 
-        fn i32_gtu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_gtu(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u32();
             let v1 = self.operand_stack.pop_u32();
             self.operand_stack.push_bool(v1 > v2);
+            Ok(())
         }
 
-        fn i32_les(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_les(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
             self.operand_stack.push_bool(v1 <= v2);
+            Ok(())
         }
 
-        fn i32_leu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_leu(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u32();
             let v1 = self.operand_stack.pop_u32();
             self.operand_stack.push_bool(v1 <= v2);
+            Ok(())
         }
 
-        fn i32_ges(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_ges(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
             self.operand_stack.push_bool(v1 >= v2);
+            Ok(())
         }
 
-        fn i32_geu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_geu(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u32();
             let v1 = self.operand_stack.pop_u32();
             self.operand_stack.push_bool(v1 >= v2);
+            Ok(())
         }
 
         // i64 相关
-        fn i64_eq(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_eq(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u64();
             let v1 = self.operand_stack.pop_u64();
             self.operand_stack.push_bool(v1 == v2);
+            Ok(())
         }
 
-        fn i64_neq(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_neq(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u64();
             let v1 = self.operand_stack.pop_u64();
             self.operand_stack.push_bool(v1 != v2);
+            Ok(())
         }
 
-        fn i64_lts(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_lts(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
             self.operand_stack.push_bool(v1 < v2);
+            Ok(())
         }
 
-        fn i64_ltu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_ltu(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u64();
             let v1 = self.operand_stack.pop_u64();
             self.operand_stack.push_bool(v1 < v2);
+            Ok(())
         }
 
-        fn i64_gts(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_gts(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
             self.operand_stack.push_bool(v1 > v2);
+            Ok(())
         }
 
-        fn i64_gtu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_gtu(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u64();
             let v1 = self.operand_stack.pop_u64();
             self.operand_stack.push_bool(v1 > v2);
+            Ok(())
         }
 
-        fn i64_les(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_les(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
             self.operand_stack.push_bool(v1 <= v2);
+            Ok(())
         }
 
-        fn i64_leu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_leu(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u64();
             let v1 = self.operand_stack.pop_u64();
             self.operand_stack.push_bool(v1 <= v2);
+            Ok(())
         }
 
-        fn i64_ges(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_ges(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
             self.operand_stack.push_bool(v1 >= v2);
+            Ok(())
         }
 
-        fn i64_geu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_geu(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u64();
             let v1 = self.operand_stack.pop_u64();
             self.operand_stack.push_bool(v1 >= v2);
+            Ok(())
         }
 
         // f32 相关
-        fn f32_eq(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_eq(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_bool(v1 == v2);
+            Ok(())
         }
 
-        fn f32_neq(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_neq(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_bool(v1 != v2);
+            Ok(())
         }
 
-        fn f32_lt(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_lt(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_bool(v1 < v2);
+            Ok(())
         }
 
-        fn f32_gt(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_gt(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_bool(v1 > v2);
+            Ok(())
         }
 
-        fn f32_le(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_le(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_bool(v1 <= v2);
+            Ok(())
         }
 
-        fn f32_ge(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_ge(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_bool(v1 >= v2);
+            Ok(())
         }
 
         // f64 相关
-        fn f64_eq(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_eq(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_bool(v1 == v2);
+            Ok(())
         }
 
-        fn f64_neq(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_neq(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_bool(v1 != v2);
+            Ok(())
         }
 
-        fn f64_lt(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_lt(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_bool(v1 < v2);
+            Ok(())
         }
 
-        fn f64_gt(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_gt(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_bool(v1 > v2);
+            Ok(())
         }
 
-        fn f64_le(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_le(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_bool(v1 <= v2);
+            Ok(())
         }
 
-        fn f64_ge(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_ge(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_bool(v1 >= v2);
+            Ok(())
         }
 
         // 一元算术指令，共6条
-        fn i32_clz(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_clz(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u32();
             self.operand_stack.push_u32(val.leading_zeros());
+            Ok(())
         }
 
-        fn i32_ctz(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_ctz(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u32();
             self.operand_stack.push_u32(val.trailing_zeros());
+            Ok(())
         }
 
-        fn i32_pop_cnt(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_pop_cnt(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u32();
             self.operand_stack.push_u32(val.count_ones());
+            Ok(())
         }
 
-        fn i64_clz(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_clz(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u64();
             self.operand_stack.push_u32(val.leading_zeros());
+            Ok(())
         }
 
-        fn i64_ctz(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_ctz(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u64();
             self.operand_stack.push_u32(val.trailing_zeros());
+            Ok(())
         }
 
-        fn i64_pop_cnt(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_pop_cnt(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u64();
             self.operand_stack.push_u32(val.count_ones());
+            Ok(())
         }
 
-        fn f32_abs(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_abs(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(val.abs());
+            Ok(())
         }
 
-        fn f32_neg(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_neg(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(-val);
+            Ok(())
         }
 
-        fn f32_ceil(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_ceil(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(val.ceil());
+            Ok(())
         }
 
-        fn f32_floor(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_floor(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(val.floor());
+            Ok(())
         }
 
-        fn f32_trunc(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_trunc(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(val.trunc());
+            Ok(())
         }
 
-        fn f32_nearest(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_nearest(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(val.round());
+            Ok(())
         }
 
-        fn f32_sqrt(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_sqrt(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(val.sqrt());
+            Ok(())
         }
 
-        fn f64_abs(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_abs(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(val.abs());
+            Ok(())
         }
 
-        fn f64_neg(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_neg(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(-val);
+            Ok(())
         }
 
-        fn f64_ceil(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_ceil(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(val.ceil());
+            Ok(())
         }
 
-        fn f64_floor(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_floor(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(val.floor());
+            Ok(())
         }
 
-        fn f64_trunc(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_trunc(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(val.trunc());
+            Ok(())
         }
 
-        fn f64_nearest(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_nearest(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(val.round());
+            Ok(())
         }
 
-        fn f64_sqrt(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_sqrt(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(val.sqrt());
+            Ok(())
         }
 
         // 二元算术指令
         // part1: 整形算术运算，共30条
-        fn i32_add(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_add(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
-            self.operand_stack.push_i32(v1 + v2);
+            self.operand_stack.push_i32(v1.wrapping_add(v2));
+            Ok(())
         }
 
-        fn i32_sub(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_sub(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
-            self.operand_stack.push_i32(v1 - v2);
+            self.operand_stack.push_i32(v1.wrapping_sub(v2));
+            Ok(())
         }
 
-        fn i32_mul(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_mul(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
-            self.operand_stack.push_i32(v1 * v2);
+            self.operand_stack.push_i32(v1.wrapping_mul(v2));
+            Ok(())
+        }
+
+        // 除数为零时，默认陷入，饱和模式下由调用方给出饱和结果
+        fn check_div_by_zero(&self) -> Result<(), Trap> {
+            if self.config.saturating_arithmetic {
+                Ok(())
+            } else {
+                Err(Trap::IntegerDivideByZero)
+            }
         }
 
-        fn i32_divs(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_divs(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
-            self.operand_stack.push_i32(v1 / v2);
+            if v2 == 0 {
+                self.check_div_by_zero()?;
+                // 饱和模式下按被除数的符号取极值
+                let val = match v1.signum() {
+                    1 => i32::MAX,
+                    -1 => i32::MIN,
+                    _ => 0,
+                };
+                self.operand_stack.push_i32(val);
+            } else if self.config.saturating_arithmetic {
+                self.operand_stack.push_i32(v1.saturating_div(v2));
+            } else {
+                // MIN / -1 的结果无法表示，规范要求陷入
+                let val = v1.checked_div(v2).ok_or(Trap::IntegerOverflow)?;
+                self.operand_stack.push_i32(val);
+            }
+            Ok(())
         }
 
-        fn i32_divu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_divu(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u32();
             let v1 = self.operand_stack.pop_u32();
-            self.operand_stack.push_u32(v1 / v2);
+            let val = match v1.checked_div(v2) {
+                Some(val) => val,
+                None => {
+                    self.check_div_by_zero()?;
+                    if v1 == 0 {
+                        0
+                    } else {
+                        u32::MAX
+                    }
+                }
+            };
+            self.operand_stack.push_u32(val);
+            Ok(())
         }
 
-        fn i32_rems(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_rems(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
-            self.operand_stack.push_i32(v1 % v2);
+            if v2 == 0 {
+                self.check_div_by_zero()?;
+                self.operand_stack.push_i32(0);
+            } else {
+                // 与除法不同，MIN % -1 不陷入，结果为 0
+                self.operand_stack.push_i32(v1.wrapping_rem(v2));
+            }
+            Ok(())
         }
 
-        fn i32_remu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_remu(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u32();
             let v1 = self.operand_stack.pop_u32();
-            self.operand_stack.push_u32(v1 % v2);
+            if v2 == 0 {
+                self.check_div_by_zero()?;
+                self.operand_stack.push_u32(0);
+            } else {
+                self.operand_stack.push_u32(v1 % v2);
+            }
+            Ok(())
         }
 
-        fn i32_and(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_and(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
             self.operand_stack.push_i32(v1 & v2);
+            Ok(())
         }
 
-        fn i32_or(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_or(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
             self.operand_stack.push_i32(v1 | v2);
+            Ok(())
         }
 
-        fn i32_xor(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_xor(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
             self.operand_stack.push_i32(v1 ^ v2);
+            Ok(())
         }
 
-        fn i32_shl(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_shl(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
-            self.operand_stack.push_i32(v1 << (v2 % 64));
+            self.operand_stack.push_i32(v1.wrapping_shl(v2 as u32));
+            Ok(())
         }
 
-        fn i32_shrs(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_shrs(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
-            self.operand_stack.push_i32(v1 >> (v2 % 64));
+            self.operand_stack.push_i32(v1.wrapping_shr(v2 as u32));
+            Ok(())
         }
 
-        fn i32_shru(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_shru(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u32();
             let v1 = self.operand_stack.pop_u32();
-            self.operand_stack.push_u32(v1 >> (v2 % 64));
+            self.operand_stack.push_u32(v1.wrapping_shr(v2));
+            Ok(())
         }
 
-        fn i32_rotl(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_rotl(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
             self.operand_stack.push_i32(v1.rotate_left(v2 as u32));
+            Ok(())
         }
 
-        fn i32_rotr(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_rotr(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i32();
             let v1 = self.operand_stack.pop_i32();
             self.operand_stack.push_i32(v1.rotate_right(v2 as u32));
+            Ok(())
         }
 
-        fn i64_add(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_add(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
-            self.operand_stack.push_i64(v1 + v2);
+            self.operand_stack.push_i64(v1.wrapping_add(v2));
+            Ok(())
         }
 
-        fn i64_sub(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_sub(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
-            self.operand_stack.push_i64(v1 - v2);
+            self.operand_stack.push_i64(v1.wrapping_sub(v2));
+            Ok(())
         }
 
-        fn i64_mul(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_mul(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
-            self.operand_stack.push_i64(v1 * v2);
+            self.operand_stack.push_i64(v1.wrapping_mul(v2));
+            Ok(())
         }
 
-        fn i64_divs(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_divs(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
-            self.operand_stack.push_i64(v1 / v2);
+            if v2 == 0 {
+                self.check_div_by_zero()?;
+                // 饱和模式下按被除数的符号取极值
+                let val = match v1.signum() {
+                    1 => i64::MAX,
+                    -1 => i64::MIN,
+                    _ => 0,
+                };
+                self.operand_stack.push_i64(val);
+            } else if self.config.saturating_arithmetic {
+                self.operand_stack.push_i64(v1.saturating_div(v2));
+            } else {
+                // MIN / -1 的结果无法表示，规范要求陷入
+                let val = v1.checked_div(v2).ok_or(Trap::IntegerOverflow)?;
+                self.operand_stack.push_i64(val);
+            }
+            Ok(())
         }
 
-        fn i64_divu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_divu(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u64();
             let v1 = self.operand_stack.pop_u64();
-            self.operand_stack.push_u64(v1 / v2);
+            let val = match v1.checked_div(v2) {
+                Some(val) => val,
+                None => {
+                    self.check_div_by_zero()?;
+                    if v1 == 0 {
+                        0
+                    } else {
+                        u64::MAX
+                    }
+                }
+            };
+            self.operand_stack.push_u64(val);
+            Ok(())
         }
 
-        fn i64_rems(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_rems(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
-            self.operand_stack.push_i64(v1 % v2);
+            if v2 == 0 {
+                self.check_div_by_zero()?;
+                self.operand_stack.push_i64(0);
+            } else {
+                // 与除法不同，MIN % -1 不陷入，结果为 0
+                self.operand_stack.push_i64(v1.wrapping_rem(v2));
+            }
+            Ok(())
         }
 
-        fn i64_remu(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_remu(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u64();
             let v1 = self.operand_stack.pop_u64();
-            self.operand_stack.push_u64(v1 % v2);
+            if v2 == 0 {
+                self.check_div_by_zero()?;
+                self.operand_stack.push_u64(0);
+            } else {
+                self.operand_stack.push_u64(v1 % v2);
+            }
+            Ok(())
         }
 
-        fn i64_and(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_and(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
             self.operand_stack.push_i64(v1 & v2);
+            Ok(())
         }
 
-        fn i64_or(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_or(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
             self.operand_stack.push_i64(v1 | v2);
+            Ok(())
         }
 
-        fn i64_xor(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_xor(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
             self.operand_stack.push_i64(v1 ^ v2);
+            Ok(())
         }
 
-        fn i64_shl(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_shl(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
-            self.operand_stack.push_i64(v1 << (v2 % 64));
+            self.operand_stack.push_i64(v1.wrapping_shl(v2 as u32));
+            Ok(())
         }
 
-        fn i64_shrs(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_shrs(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
-            self.operand_stack.push_i64(v1 >> (v2 % 64));
+            self.operand_stack.push_i64(v1.wrapping_shr(v2 as u32));
+            Ok(())
         }
 
-        fn i64_shru(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_shru(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_u64();
             let v1 = self.operand_stack.pop_u64();
-            self.operand_stack.push_u64(v1 >> (v2 % 64));
+            self.operand_stack.push_u64(v1.wrapping_shr(v2 as u32));
+            Ok(())
         }
 
-        fn i64_rotl(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_rotl(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
             self.operand_stack.push_i64(v1.rotate_left(v2 as u32));
+            Ok(())
         }
 
-        fn i64_rotr(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_rotr(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_i64();
             let v1 = self.operand_stack.pop_i64();
             self.operand_stack.push_i64(v1.rotate_right(v2 as u32));
+            Ok(())
         }
 
         // part2: 浮点算术运算，共14条
-        fn f32_add(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_add(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(v1 + v2);
+            Ok(())
         }
 
-        fn f32_sub(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_sub(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(v1 - v2);
+            Ok(())
         }
 
-        fn f32_mul(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_mul(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(v1 * v2);
+            Ok(())
         }
 
-        fn f32_div(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_div(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(v1 / v2);
+            Ok(())
         }
 
-        fn f32_min(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_min(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
-            self.operand_stack.push_f32(v1.min(v2));
+            self.operand_stack.push_f32(min_f32(v1, v2));
+            Ok(())
         }
 
-        fn f32_max(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_max(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
-            self.operand_stack.push_f32(v1.max(v2));
+            self.operand_stack.push_f32(max_f32(v1, v2));
+            Ok(())
         }
 
-        fn f32_copy_sign(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_copy_sign(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f32();
             let v1 = self.operand_stack.pop_f32();
             self.operand_stack.push_f32(v1.copysign(v2));
+            Ok(())
         }
 
-        fn f64_add(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_add(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(v1 + v2);
+            Ok(())
         }
 
-        fn f64_sub(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_sub(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(v1 - v2);
+            Ok(())
         }
 
-        fn f64_mul(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_mul(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(v1 * v2);
+            Ok(())
         }
 
-        fn f64_div(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_div(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(v1 / v2);
+            Ok(())
         }
 
-        fn f64_min(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_min(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
-            self.operand_stack.push_f64(v1.min(v2));
+            self.operand_stack.push_f64(min_f64(v1, v2));
+            Ok(())
         }
 
-        fn f64_max(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_max(&mut self, _args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
-            self.operand_stack.push_f64(v1.max(v2));
+            self.operand_stack.push_f64(max_f64(v1, v2));
+            Ok(())
         }
 
-        fn f64_copy_sign(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_copy_sign(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v2 = self.operand_stack.pop_f64();
             let v1 = self.operand_stack.pop_f64();
             self.operand_stack.push_f64(v1.copysign(v2));
+            Ok(())
         }
 
         // 类型转换指令
         // part1: 整数截断，共1条指令
-        fn i32_wrap_i64(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_wrap_i64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_u64();
             self.operand_stack.push_u32(v as u32);
+            Ok(())
         }
         // part2: 整数拉升，共7条指令
-        fn i64_extend_i32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_extend_i32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_i32();
             self.operand_stack.push_u64(v as u64);
+            Ok(())
         }
 
-        fn i64_extend_u32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_extend_u32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_u32();
             self.operand_stack.push_u64(v as u64);
+            Ok(())
         }
 
-        fn i32_extend_8(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_extend_8(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_i32() as i8;
             self.operand_stack.push_i32(v as i32);
+            Ok(())
         }
 
-        fn i32_extend_16(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_extend_16(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_i32() as i16;
             self.operand_stack.push_i32(v as i32);
+            Ok(())
         }
 
-        fn i64_extend_8(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_extend_8(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_i64() as i8;
             self.operand_stack.push_i64(v as i64);
+            Ok(())
         }
 
-        fn i64_extend_16(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_extend_16(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_i64() as i16;
             self.operand_stack.push_i64(v as i64);
+            Ok(())
         }
 
-        fn i64_extend_32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_extend_32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_i64() as i32;
             self.operand_stack.push_i64(v as i64);
+            Ok(())
         }
         // part3: 浮点数截断，共9条指令
-        fn i32_trunc_f32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_trunc_f32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_f32();
             self.operand_stack.push_i32(v.trunc() as i32);
+            Ok(())
         }
 
-        fn u32_trunc_f32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn u32_trunc_f32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_f32();
             self.operand_stack.push_u32(v.trunc() as u32);
+            Ok(())
         }
 
-        fn i32_trunc_f64(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i32_trunc_f64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_f64();
             self.operand_stack.push_i32(v.trunc() as i32);
+            Ok(())
         }
 
-        fn u32_trunc_f64(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn u32_trunc_f64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_f64();
             self.operand_stack.push_u32(v.trunc() as u32);
+            Ok(())
         }
 
-        fn i64_trunc_f32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_trunc_f32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_f32();
             self.operand_stack.push_i64(v.trunc() as i64);
+            Ok(())
         }
 
-        fn u64_trunc_f32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn u64_trunc_f32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_f32();
             self.operand_stack.push_u64(v.trunc() as u64);
+            Ok(())
         }
 
-        fn i64_trunc_f64(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn i64_trunc_f64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_f64();
             self.operand_stack.push_i64(v.trunc() as i64);
+            Ok(())
         }
 
-        fn u64_trunc_f64(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn u64_trunc_f64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_f64();
+            if v.is_nan() {
+                return Err(Trap::InvalidConversionToInteger);
+            }
+            // 截断后落在 [0, 2^64) 内才合法，即 -1 < v < 2^64，2^64 本身可以精确表示但已越界
+            if v <= -1.0 || v >= 18446744073709551616.0 {
+                return Err(Trap::IntegerOverflow);
+            }
             self.operand_stack.push_u64(v.trunc() as u64);
+            Ok(())
         }
 
         // part4: 整数转换，共8条指令
-        fn f32_convert_i32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_convert_i32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_i32();
             self.operand_stack.push_f32(v as f32);
+            Ok(())
         }
 
-        fn f32_convert_u32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_convert_u32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_u32();
             self.operand_stack.push_f32(v as f32);
+            Ok(())
         }
 
-        fn f32_convert_i64(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_convert_i64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_i64();
             self.operand_stack.push_f32(v as f32);
+            Ok(())
         }
 
-        fn f32_convert_u64(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_convert_u64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_u64();
             self.operand_stack.push_f32(v as f32);
+            Ok(())
         }
 
-        fn f64_convert_i32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_convert_i32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_i32();
             self.operand_stack.push_f64(v as f64);
+            Ok(())
         }
 
-        fn f64_convert_u32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_convert_u32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_u32();
             self.operand_stack.push_f64(v as f64);
+            Ok(())
         }
 
-        fn f64_convert_i64(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_convert_i64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_i64();
             self.operand_stack.push_f64(v as f64);
+            Ok(())
         }
 
-        fn f64_convert_u64(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_convert_u64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_u64();
             self.operand_stack.push_f64(v as f64);
+            Ok(())
         }
         // part5: 浮点数精度调整，共2条指令
-        fn f32_demote_f64(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f32_demote_f64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_f64();
             self.operand_stack.push_f32(v as f32);
+            Ok(())
         }
 
-        fn f64_promote_f32(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn f64_promote_f32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let v = self.operand_stack.pop_f32();
             self.operand_stack.push_f64(v as f64);
+            Ok(())
         }
         // part6: 比特位重新解释，共4条指令，只需重新解释类型，无需做任何操作
-        fn i32_reinterpret_f32(&mut self, _args: &Option<Rc<dyn Any>>) {}
-        fn i64_reinterpret_f64(&mut self, _args: &Option<Rc<dyn Any>>) {}
-        fn f32_reinterpret_i32(&mut self, _args: &Option<Rc<dyn Any>>) {}
-        fn f64_reinterpret_i64(&mut self, _args: &Option<Rc<dyn Any>>) {}
+        fn i32_reinterpret_f32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            self.operand_stack.set_top_type(ValType::I32);
+            Ok(())
+        }
+        fn i64_reinterpret_f64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            self.operand_stack.set_top_type(ValType::I64);
+            Ok(())
+        }
+        fn f32_reinterpret_i32(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            self.operand_stack.set_top_type(ValType::F32);
+            Ok(())
+        }
+        fn f64_reinterpret_i64(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            self.operand_stack.set_top_type(ValType::F64);
+            Ok(())
+        }
 
         // 内存相关指令
         // helper function
@@ -1622,64 +3184,162 @@ pub mod interpreter {
             self.operand_stack.pop_u32() as usize + arg.offset as usize
         }
 
-        fn read_u8(&mut self, args: &Option<Rc<dyn Any>>) -> u8 {
+        // 对齐只是提示，只有严格对齐模式下才检查自然对齐；
+        // 同时统计对齐提示小于自然对齐的访问次数
+        fn check_alignment(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+            offset: usize,
+            natural_align: usize,
+        ) -> Result<(), Trap> {
+            let arg = args.as_ref().unwrap().downcast_ref::<MemArg>().unwrap();
+            if arg.align_bytes().is_some_and(|b| b < natural_align as u64) {
+                self.under_aligned_count += 1;
+            }
+            if self.config.strict_alignment
+                && !offset.is_multiple_of(natural_align)
+            {
+                return Err(Trap::UnalignedAccess);
+            }
+            Ok(())
+        }
+
+        fn read_memory_buf(
+            &mut self,
+            offset: usize,
+            buf: &mut [u8],
+        ) -> Result<(), Trap> {
+            match self.memory.read(offset, buf) {
+                Err(Trap::MemoryOutOfBounds)
+                    if self.oob_mode == OobMode::Sentinel =>
+                {
+                    buf.fill(0);
+                    Ok(())
+                }
+                Ok(()) if !self.memory.is_written(offset, buf.len()) => {
+                    Err(Trap::UninitializedRead)
+                }
+                ret => ret,
+            }
+        }
+
+        fn write_memory_buf(
+            &mut self,
+            offset: usize,
+            data: &[u8],
+        ) -> Result<(), Trap> {
+            match self.memory.write(offset, data) {
+                Ok(()) => {
+                    self.audit_write(offset, data.len());
+                    Ok(())
+                }
+                Err(Trap::MemoryOutOfBounds)
+                    if self.oob_mode == OobMode::Sentinel =>
+                {
+                    Ok(())
+                }
+                ret => ret,
+            }
+        }
+
+        fn read_u8(&mut self, args: &Option<Rc<dyn Any>>) -> Result<u8, Trap> {
             let offset = self.get_offset(args);
+            self.check_alignment(args, offset, 1)?;
             let mut buf = vec![0u8];
-            self.memory.read(offset, &mut buf[..]);
-            buf[0]
+            self.read_memory_buf(offset, &mut buf[..])?;
+            Ok(buf[0])
         }
 
-        fn read_u16(&mut self, args: &Option<Rc<dyn Any>>) -> u16 {
+        fn read_u16(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<u16, Trap> {
             let offset = self.get_offset(args);
+            self.check_alignment(args, offset, 2)?;
             let mut buf = vec![0u8; 2];
-            self.memory.read(offset, &mut buf[..]);
-            u16::from_le_bytes(buf.try_into().unwrap())
+            self.read_memory_buf(offset, &mut buf[..])?;
+            Ok(u16::from_le_bytes(buf.try_into().unwrap()))
         }
 
-        fn read_u32(&mut self, args: &Option<Rc<dyn Any>>) -> u32 {
+        fn read_u32(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<u32, Trap> {
             let offset = self.get_offset(args);
+            self.check_alignment(args, offset, 4)?;
             let mut buf = vec![0u8; 4];
-            self.memory.read(offset, &mut buf[..]);
-            u32::from_le_bytes(buf.try_into().unwrap())
+            self.read_memory_buf(offset, &mut buf[..])?;
+            Ok(u32::from_le_bytes(buf.try_into().unwrap()))
         }
 
-        fn read_u64(&mut self, args: &Option<Rc<dyn Any>>) -> u64 {
+        fn read_u64(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<u64, Trap> {
             let offset = self.get_offset(args);
+            self.check_alignment(args, offset, 8)?;
             let mut buf = vec![0u8; 8];
-            self.memory.read(offset, &mut buf[..]);
-            u64::from_le_bytes(buf.try_into().unwrap())
+            self.read_memory_buf(offset, &mut buf[..])?;
+            Ok(u64::from_le_bytes(buf.try_into().unwrap()))
         }
 
-        fn write_u8(&mut self, args: &Option<Rc<dyn Any>>, n: u8) {
+        fn write_u8(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+            n: u8,
+        ) -> Result<(), Trap> {
             let offset = self.get_offset(args);
+            self.check_alignment(args, offset, 1)?;
             let buf = vec![n];
-            self.memory.write(offset, &buf[..]);
+            self.write_memory_buf(offset, &buf[..])
         }
 
-        fn write_u16(&mut self, args: &Option<Rc<dyn Any>>, n: u16) {
+        fn write_u16(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+            n: u16,
+        ) -> Result<(), Trap> {
             let offset = self.get_offset(args);
+            self.check_alignment(args, offset, 2)?;
             let buf = n.to_le_bytes();
-            self.memory.write(offset, &buf);
+            self.write_memory_buf(offset, &buf)
         }
 
-        fn write_u32(&mut self, args: &Option<Rc<dyn Any>>, n: u32) {
+        fn write_u32(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+            n: u32,
+        ) -> Result<(), Trap> {
             let offset = self.get_offset(args);
+            self.check_alignment(args, offset, 4)?;
             let buf = n.to_le_bytes();
-            self.memory.write(offset, &buf);
+            self.write_memory_buf(offset, &buf)
         }
 
-        fn write_u64(&mut self, args: &Option<Rc<dyn Any>>, n: u64) {
+        fn write_u64(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+            n: u64,
+        ) -> Result<(), Trap> {
             let offset = self.get_offset(args);
+            self.check_alignment(args, offset, 8)?;
             let buf = n.to_le_bytes();
-            self.memory.write(offset, &buf);
+            self.write_memory_buf(offset, &buf)
         }
 
         // part1: size 和 grow
-        fn memory_size(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn memory_size(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             self.operand_stack.push_u32(self.memory.size() as u32);
+            Ok(())
         }
 
-        fn memory_grow(&mut self, _args: &Option<Rc<dyn Any>>) {
+        fn memory_grow(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let grow_size = self.operand_stack.pop_u32();
             println!("memory grow size = {}", grow_size);
             let old_size = self.memory.grow(grow_size as usize);
@@ -1689,204 +3349,520 @@ pub mod interpreter {
                 self.memory.size()
             );
             self.operand_stack.push_u32(old_size as u32);
+            Ok(())
         }
 
         // part2: load
-        fn i32_load(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u32(args);
+        fn i32_load(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
+            let val = self.read_u32(args)?;
             self.operand_stack.push_u32(val);
+            Ok(())
         }
 
-        fn i64_load(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u64(args);
+        fn i64_load(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
+            let val = self.read_u64(args)?;
             self.operand_stack.push_u64(val);
+            Ok(())
         }
 
-        fn f32_load(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u32(args);
-            self.operand_stack.push_u32(val);
+        fn f32_load(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
+            let val = self.read_u32(args)?;
+            self.operand_stack.push_f32(f32::from_bits(val));
+            Ok(())
         }
 
-        fn f64_load(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u64(args);
-            self.operand_stack.push_u64(val);
+        fn f64_load(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
+            let val = self.read_u64(args)?;
+            self.operand_stack.push_f64(f64::from_bits(val));
+            Ok(())
         }
 
-        fn i32_load_8s(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u8(args);
+        fn i32_load_8s(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let val = self.read_u8(args)?;
             self.operand_stack.push_i32(val as i8 as i32);
+            Ok(())
         }
 
-        fn i32_load_8u(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u8(args);
+        fn i32_load_8u(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let val = self.read_u8(args)?;
             self.operand_stack.push_u32(val as u32);
+            Ok(())
         }
 
-        fn i32_load_16s(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u16(args);
+        fn i32_load_16s(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let val = self.read_u16(args)?;
             self.operand_stack.push_i32(val as i16 as i32);
+            Ok(())
         }
 
-        fn i32_load_16u(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u16(args);
+        fn i32_load_16u(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let val = self.read_u16(args)?;
             self.operand_stack.push_u32(val as u32);
+            Ok(())
         }
 
-        fn i64_load_8s(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u8(args);
+        fn i64_load_8s(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let val = self.read_u8(args)?;
             self.operand_stack.push_i64(val as i8 as i64);
+            Ok(())
         }
 
-        fn i64_load_8u(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u8(args);
+        fn i64_load_8u(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let val = self.read_u8(args)?;
             self.operand_stack.push_u64(val as u64);
+            Ok(())
         }
 
-        fn i64_load_16s(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u16(args);
+        fn i64_load_16s(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let val = self.read_u16(args)?;
             self.operand_stack.push_i64(val as i16 as i64);
+            Ok(())
         }
 
-        fn i64_load_16u(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u16(args);
+        fn i64_load_16u(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let val = self.read_u16(args)?;
             self.operand_stack.push_u64(val as u64);
+            Ok(())
         }
 
-        fn i64_load_32s(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u32(args);
+        fn i64_load_32s(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let val = self.read_u32(args)?;
             self.operand_stack.push_i64(val as i32 as i64);
+            Ok(())
         }
 
-        fn i64_load_32u(&mut self, args: &Option<Rc<dyn Any>>) {
-            let val = self.read_u32(args);
+        fn i64_load_32u(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let val = self.read_u32(args)?;
             self.operand_stack.push_u64(val as u64);
+            Ok(())
         }
 
         // part3: store
-        fn i32_store(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn i32_store(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u32();
-            self.write_u32(args, val);
+            self.write_u32(args, val)
         }
 
-        fn i64_store(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn i64_store(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u64();
-            self.write_u64(args, val);
+            self.write_u64(args, val)
         }
 
-        fn f32_store(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn f32_store(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u32();
-            self.write_u32(args, val);
+            self.write_u32(args, val)
         }
 
-        fn f64_store(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn f64_store(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u64();
-            self.write_u64(args, val);
+            self.write_u64(args, val)
         }
 
-        fn i32_store_8(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn i32_store_8(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u32();
-            self.write_u8(args, val as u8);
+            self.write_u8(args, val as u8)
         }
 
-        fn i32_store_16(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn i32_store_16(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u32();
-            self.write_u16(args, val as u16);
+            self.write_u16(args, val as u16)
         }
 
-        fn i64_store_8(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn i64_store_8(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u64();
-            self.write_u8(args, val as u8);
+            self.write_u8(args, val as u8)
         }
 
-        fn i64_store_16(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn i64_store_16(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u64();
-            self.write_u16(args, val as u16);
+            self.write_u16(args, val as u16)
         }
-        fn i64_store_32(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn i64_store_32(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let val = self.operand_stack.pop_u64();
-            self.write_u32(args, val as u32);
+            self.write_u32(args, val as u32)
+        }
+
+        // part4: 批量内存操作
+        fn memory_fill(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let n = self.operand_stack.pop_u32();
+            // 填充值只取低 8 位
+            let val = self.operand_stack.pop_u32() as u8;
+            let offset = self.operand_stack.pop_u32();
+            match self.memory.fill(offset as usize, n as usize, val) {
+                Ok(()) => {
+                    self.audit_write(offset as usize, n as usize);
+                    Ok(())
+                }
+                Err(Trap::MemoryOutOfBounds)
+                    if self.oob_mode == OobMode::Sentinel =>
+                {
+                    Ok(())
+                }
+                ret => ret,
+            }
+        }
+
+        fn memory_copy(&mut self) -> Result<(), Trap> {
+            let n = self.operand_stack.pop_u32() as usize;
+            let src = self.operand_stack.pop_u32() as usize;
+            let dst = self.operand_stack.pop_u32() as usize;
+            match self.memory.copy(dst, src, n) {
+                Ok(()) => {
+                    self.audit_write(dst, n);
+                    Ok(())
+                }
+                Err(Trap::MemoryOutOfBounds)
+                    if self.oob_mode == OobMode::Sentinel =>
+                {
+                    Ok(())
+                }
+                ret => ret,
+            }
+        }
+
+        // 数据段索引空间包含主动段，实例化后主动段的原始字节仍保留，未被丢弃时同样可以作为来源
+        fn memory_init(&mut self, data_idx: u32) -> Result<(), Trap> {
+            let n = self.operand_stack.pop_u32() as usize;
+            let src = self.operand_stack.pop_u32() as usize;
+            let dst = self.operand_stack.pop_u32() as usize;
+            let init: &[u8] = match self.module.data_sec.get(data_idx as usize)
+            {
+                Some(_) if self.dropped_data.contains(&data_idx) => &[],
+                Some(data) => &data.init,
+                None => return Err(Trap::MemoryOutOfBounds),
+            };
+            let ret = match src.checked_add(n) {
+                Some(end) if end <= init.len() => {
+                    self.memory.write(dst, &init[src..end])
+                }
+                _ => Err(Trap::MemoryOutOfBounds),
+            };
+            match ret {
+                Ok(()) => {
+                    self.audit_write(dst, n);
+                    Ok(())
+                }
+                Err(Trap::MemoryOutOfBounds)
+                    if self.oob_mode == OobMode::Sentinel =>
+                {
+                    Ok(())
+                }
+                ret => ret,
+            }
+        }
+
+        // 将刚写入的 [addr, addr + n) 交给审计回调
+        fn audit_write(&mut self, addr: usize, n: usize) {
+            if let Some(sink) = self.write_audit.as_mut() {
+                let func_idx = match self.control_stack.top_call_frame().0 {
+                    Some(cf) => cf.func_idx,
+                    None => return,
+                };
+                sink(&MemoryWrite {
+                    addr,
+                    data: self.memory.data[addr..addr + n].to_vec(),
+                    func_idx,
+                    pc: self.cur_flat_pc,
+                });
+            }
+        }
+
+        // 先取出源范围的元素再写入，源和目标范围重叠时结果与 memmove 相同
+        fn table_copy(
+            &mut self,
+            dst_idx: u32,
+            src_idx: u32,
+        ) -> Result<(), Trap> {
+            let n = self.operand_stack.pop_u32() as usize;
+            let src = self.operand_stack.pop_u32() as usize;
+            let dst = self.operand_stack.pop_u32() as usize;
+            let elems = match self.tables.get(src_idx as usize) {
+                Some(table) if src + n <= table.size() => {
+                    table.elems[src..src + n].to_vec()
+                }
+                _ => return Err(Trap::TableOutOfBounds),
+            };
+            match self.tables.get_mut(dst_idx as usize) {
+                Some(table) if dst + n <= table.size() => {
+                    for (i, elem) in elems.into_iter().enumerate() {
+                        table.set_elem(dst + i, elem);
+                    }
+                    Ok(())
+                }
+                _ => Err(Trap::TableOutOfBounds),
+            }
+        }
+
+        fn data_drop(&mut self, data_idx: u32) -> Result<(), Trap> {
+            self.dropped_data.insert(data_idx);
+            Ok(())
+        }
+
+        // 0xFC 前缀指令，按子操作码分派
+        fn prefixed_instr(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let prefixed = args
+                .as_ref()
+                .unwrap()
+                .downcast_ref::<PrefixedArgs>()
+                .unwrap();
+            match prefixed.sub_opcode {
+                MEMORY_INIT => self.memory_init(prefixed.idx),
+                DATA_DROP => self.data_drop(prefixed.idx),
+                MEMORY_COPY => self.memory_copy(),
+                MEMORY_FILL => self.memory_fill(args),
+                TABLE_COPY => self.table_copy(prefixed.idx, prefixed.src_idx),
+                _ => Ok(()),
+            }
         }
 
         // 局部变量指令
-        fn local_get(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn local_get(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let idx = args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
-            let val = self
-                .operand_stack
-                .get_operand(self.local_0_idx + *idx as usize);
-            self.operand_stack.push_u64(val);
+            self.operand_stack
+                .dup_operand(self.local_0_idx + *idx as usize);
+            Ok(())
         }
 
-        fn local_set(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn local_set(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let idx = args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
             let val = self.operand_stack.pop_u64();
             self.operand_stack
                 .set_operand(self.local_0_idx + *idx as usize, val);
+            Ok(())
         }
 
-        fn local_tee(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn local_tee(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let idx = args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
-            let val = self.operand_stack.pop_u64();
-            self.operand_stack.push_u64(val);
+            let top = self.operand_stack.length() - 1;
+            let val = self.operand_stack.get_operand(top);
             self.operand_stack
                 .set_operand(self.local_0_idx + *idx as usize, val);
+            Ok(())
         }
 
         // 全局变量指令
-        fn global_get(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn global_get(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let idx = args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
-            let val = self.globals[*idx as usize].get_as_u64();
-            self.operand_stack.push_u64(val);
+            let global = &self.globals[*idx as usize];
+            let val_type = global.global_type.val_type;
+            let val = global.get_as_u64();
+            self.operand_stack.push_typed(val, val_type);
+            Ok(())
         }
 
-        fn global_set(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn global_set(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let idx = args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
             let val = self.operand_stack.pop_u64();
             self.globals[*idx as usize].set_as_u64(val);
+            Ok(())
+        }
+
+        // 引用指令，所有堆类型的空引用表示相同
+        fn ref_null(
+            &mut self,
+            _args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            self.operand_stack.push_typed(NULL_REF, ValType::FuncRef);
+            Ok(())
+        }
+
+        fn ref_func(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
+            let idx = args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
+            self.operand_stack.push_typed(*idx as u64, ValType::FuncRef);
+            Ok(())
+        }
+
+        // 表指令，表中存放的是函数引用
+        fn table_get(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let table_idx =
+                args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
+            let i = self.operand_stack.pop_u32() as usize;
+            let table = match self.tables.get(*table_idx as usize) {
+                Some(table) if i < table.size() => table,
+                _ => return Err(Trap::TableOutOfBounds),
+            };
+            let elem = table.get_elem(i);
+            let val = if elem.code.is_none() && elem.native_func.is_none() {
+                NULL_REF
+            } else {
+                elem.idx as u64
+            };
+            self.operand_stack.push_typed(val, ValType::FuncRef);
+            Ok(())
+        }
+
+        fn table_set(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let table_idx =
+                args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
+            let val = self.operand_stack.pop_u64();
+            let i = self.operand_stack.pop_u32() as usize;
+            let elem = if val == NULL_REF {
+                VMFunc::default()
+            } else {
+                match self.vm_funcs.get(val as usize) {
+                    Some(f) => f.clone(),
+                    None => return Err(Trap::UndefinedElement),
+                }
+            };
+            match self.tables.get_mut(*table_idx as usize) {
+                Some(table) if i < table.size() => {
+                    table.set_elem(i, elem);
+                    Ok(())
+                }
+                _ => Err(Trap::TableOutOfBounds),
+            }
         }
 
         // 控制指令
-        fn br_if(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn br_if(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             if self.operand_stack.pop_bool() {
-                self.br(args);
+                self.br(args)?;
             }
+            Ok(())
         }
 
-        fn block(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn block(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let block_args =
                 args.as_ref().unwrap().downcast_ref::<BlockArgs>().unwrap();
             let block_type = self.module.get_block_type(block_args.block_type);
+            let func_idx = self.control_stack.top_control_frame().func_idx;
             self.enter_block(
                 OpCode::Block,
                 block_type,
                 block_args.instructions.clone(),
+                func_idx,
+                self.cur_flat_pc + 1,
             );
+            Ok(())
         }
 
-        fn loop_instr(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn loop_instr(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let block_args =
                 args.as_ref().unwrap().downcast_ref::<BlockArgs>().unwrap();
             let block_type = self.module.get_block_type(block_args.block_type);
+            let func_idx = self.control_stack.top_control_frame().func_idx;
             self.enter_block(
                 OpCode::Loop,
                 block_type,
                 block_args.instructions.clone(),
+                func_idx,
+                self.cur_flat_pc + 1,
             );
+            Ok(())
         }
 
-        fn if_instr(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn if_instr(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let if_args =
                 args.as_ref().unwrap().downcast_ref::<IfArgs>().unwrap();
             let block_type = self.module.get_block_type(if_args.block_type);
             let instrs;
+            // else 分支展开后位于 then 分支之后
+            let mut flat_base = self.cur_flat_pc + 1;
             if self.operand_stack.pop_bool() {
                 instrs = if_args.instructions_1.clone();
             } else {
                 instrs = if_args.instructions_2.clone();
+                flat_base += flat_len(&if_args.instructions_1);
             }
-            self.enter_block(OpCode::If, block_type, instrs);
+            let func_idx = self.control_stack.top_control_frame().func_idx;
+            self.enter_block(
+                OpCode::If,
+                block_type,
+                instrs,
+                func_idx,
+                flat_base,
+            );
+            Ok(())
         }
 
-        fn br(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn br(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let label_idx =
                 args.as_ref().unwrap().downcast_ref::<BrArgs>().unwrap();
             // 先弹出label_idx 个控制帧
@@ -1900,66 +3876,90 @@ pub mod interpreter {
             } else {
                 // 如果是 loop 块，需要重新进入进入控制帧
                 cf.pc = 0;
+                cf.flat_pc = cf.flat_base;
                 // self.reset_block(cf);
-                let mut results = self
-                    .operand_stack
-                    .pop_u64s(cf.block_type.params_types.len());
-                self.operand_stack
-                    .pop_u64s(self.operand_stack.length() - cf.bp);
-                self.operand_stack.push_u64s(&mut results);
+                let (bp, n) = (cf.bp, cf.block_type.params_types.len());
+                self.operand_stack.drop_below_top(bp, n);
             }
+            Ok(())
         }
 
-        fn br_table(&mut self, args: &Option<Rc<dyn Any>>) {
+        fn br_table(&mut self, args: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             let br_table_args = args
                 .as_ref()
                 .unwrap()
                 .downcast_ref::<BrTableArgs>()
                 .unwrap();
             let idx = self.operand_stack.pop_u32() as usize;
-            if idx < br_table_args.labels.len() {
-                self.br(&Some(Rc::new(br_table_args.labels[idx])));
-            }
+            // 索引越界时跳转到默认标签，br_table 之后的指令永远不会执行
+            let label_idx = if idx < br_table_args.labels.len() {
+                br_table_args.labels[idx]
+            } else {
+                br_table_args.default
+            };
+            self.br(&Some(Rc::new(label_idx)))
         }
 
-        fn return_instr(&mut self, _: &Option<Rc<dyn Any>>) {
+        fn return_instr(
+            &mut self,
+            _: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
             let (_, label_idx) = self.control_stack.top_call_frame();
-            self.br(&Some(Rc::new(label_idx as BrArgs)));
+            self.br(&Some(Rc::new(label_idx as BrArgs)))
         }
 
-        fn call_indrect(&mut self, args: &Option<Rc<dyn Any>>) {
-            let i = self.operand_stack.pop_u32();
-            if self.table.as_ref().is_none() || i > self.table.as_ref().unwrap().size() as u32 {
-                panic!("Undefined element");
+        fn call_indrect(
+            &mut self,
+            args: &Option<Rc<dyn Any>>,
+        ) -> Result<(), Trap> {
+            let args = args
+                .as_ref()
+                .unwrap()
+                .downcast_ref::<CallIndirectArgs>()
+                .unwrap();
+            let i = self.operand_stack.pop_u32() as usize;
+            let table = match self.tables.get(args.table_idx as usize) {
+                Some(table) if i < table.size() => table,
+                _ => return Err(Trap::UndefinedElement),
+            };
+            let func_in_table = &table.get_elem(i);
+            // 空引用
+            if func_in_table.code.is_none()
+                && func_in_table.native_func.is_none()
+            {
+                return Err(Trap::UninitializedElement);
             }
-            let table = self.table.as_ref().unwrap();
-            let func_in_table = &table.get_elem(i as usize);
-            let type_idx = args.as_ref().unwrap().downcast_ref::<u32>().unwrap();
-            let func_type = &self.module.type_sec[*type_idx as usize];
-            if func_in_table.func_type.get_signature() != func_type.get_signature() {
-                panic!("Indirect call type mismatch");
+            if func_in_table.sig_id != self.sig_ids[args.type_idx as usize] {
+                return Err(Trap::IndirectCallTypeMismatch);
+            }
+            if self.call_mocked_func(func_in_table)? {
+                return Ok(());
             }
             if func_in_table.code.is_some() {
                 self.call_internal_func(func_in_table);
-            } else if func_in_table.native_func.is_some() {
-                self.call_external_func(func_in_table);
             } else {
-                panic!("Unexpected function type");
+                self.call_external_func(func_in_table)?;
             }
+            Ok(())
         }
 
-        fn unreachable(&mut self, _: &Option<Rc<dyn Any>>) {
-            panic!("Unreachable");
+        fn unreachable(&mut self, _: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
+            Err(Trap::Unreachable)
         }
 
-        fn nop(&mut self, _: &Option<Rc<dyn Any>>) {
+        fn nop(&mut self, _: &Option<Rc<dyn Any>>) -> Result<(), Trap> {
             // do nothing
+            Ok(())
         }
     }
 
     #[cfg(test)]
     mod tests {
         use super::*;
+        use crate::module::module::module::{
+            Data, Elem, Export, Global, Import, Locals,
+        };
+        use std::sync::atomic::{AtomicI32, Ordering};
 
         #[test]
         fn test_operand_stack() {
@@ -1984,6 +3984,39 @@ pub mod interpreter {
             assert_eq!(stack.length(), 0);
         }
 
+        #[test]
+        fn test_typed_operand_stack() {
+            let mut stack = OperandStack::new();
+            stack.set_typed(true);
+            stack.push_bool(true);
+            stack.push_u64(3);
+            stack.push_f32(5.5);
+            stack.push_f64(6.5);
+            stack.push_typed(NULL_REF, ValType::FuncRef);
+            let expected = [
+                ValType::I32,
+                ValType::I64,
+                ValType::F32,
+                ValType::F64,
+                ValType::FuncRef,
+            ];
+            assert_eq!(stack.types.as_deref(), Some(&expected[..]));
+            // 复制和移动值时类型随值一起移动
+            stack.dup_operand(2);
+            stack.drop_below_top(1, 2);
+            assert_eq!(
+                stack.types.as_deref(),
+                Some(&[ValType::I32, ValType::FuncRef, ValType::F32][..])
+            );
+            stack.pop_u64s(2);
+            stack.pop_bool();
+            assert_eq!(stack.types.as_deref(), Some(&[][..]));
+
+            let mut stack = OperandStack::new();
+            stack.push_f32(5.5);
+            assert!(stack.types.is_none());
+        }
+
         #[test]
         fn test_local_var() {
             let mut operand_stack = OperandStack::new();
@@ -2011,10 +4044,2456 @@ pub mod interpreter {
         #[test]
         fn test_memory() {
             // test memory size and grow
-            let mut mem = Memory::new(Limits { min: 2, max: None });
+            let mut mem = Memory::new(Limits {
+                min: 2,
+                ..Default::default()
+            });
             assert_eq!(mem.size(), 2);
             assert_eq!(mem.grow(3), 2);
             assert_eq!(mem.size(), 5);
         }
+
+        #[test]
+        fn test_memory_written_bits() {
+            let mut mem = Memory::new(Limits {
+                min: 1,
+                ..Default::default()
+            });
+            assert!(mem.is_written(0, 8));
+            mem.set_poison();
+            assert!(!mem.is_written(0, 1));
+            // 跨越两个 u64 的写入
+            mem.mark_written(60, 10);
+            assert!(mem.is_written(60, 10));
+            assert!(mem.is_written(63, 2));
+            assert!(!mem.is_written(59, 2));
+            assert!(!mem.is_written(69, 2));
+            let len = mem.data.len();
+            mem.mark_written(len - 1, 1);
+            assert!(mem.is_written(len - 1, 1));
+            // 增长出的内存没有写过
+            mem.grow(1);
+            assert!(mem.is_written(len - 1, 1));
+            assert!(!mem.is_written(len, 1));
+            assert_eq!(mem.data[len], POISON_BYTE);
+        }
+
+        fn instr(opcode: OpCode, args: Option<Rc<dyn Any>>) -> Instruction {
+            let mut instr = Instruction::new(opcode);
+            instr.args = args;
+            instr
+        }
+
+        fn i32_const(val: i32) -> Instruction {
+            instr(OpCode::I32Const, Some(Rc::new(val)))
+        }
+
+        fn i64_const(val: i64) -> Instruction {
+            instr(OpCode::I64Const, Some(Rc::new(val)))
+        }
+
+        fn idx_instr(opcode: OpCode, idx: u32) -> Instruction {
+            instr(opcode, Some(Rc::new(idx)))
+        }
+
+        fn call_indirect(type_idx: u32, table_idx: u32) -> Instruction {
+            let args = CallIndirectArgs {
+                type_idx,
+                table_idx,
+            };
+            instr(OpCode::CallIndirect, Some(Rc::new(args)))
+        }
+
+        fn mem_instr(opcode: OpCode, offset: u32) -> Instruction {
+            instr(opcode, Some(Rc::new(MemArg { align: 0, offset })))
+        }
+
+        // 构造一个只包含导出函数 main 的模块，main 没有参数和返回值
+        fn main_module(mem_sec: Vec<MemType>, expr: Expr) -> Module {
+            Module {
+                type_sec: vec![FuncType::default()],
+                func_sec: vec![0],
+                mem_sec,
+                export_sec: vec![Export {
+                    name: "main".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr,
+                }],
+                ..Default::default()
+            }
+        }
+
+        fn unaligned_load_module() -> Module {
+            main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![
+                    i32_const(1),
+                    mem_instr(OpCode::I32Load, 0),
+                    instr(OpCode::Drop, None),
+                ],
+            )
+        }
+
+        #[test]
+        fn test_audit_memory_writes() {
+            // i32.store 16 (0x11223344)，i32.store16 offset=2 (地址 20, 0x5566)
+            let module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![
+                    i32_const(16),
+                    i32_const(0x11223344),
+                    mem_instr(OpCode::I32Store, 0),
+                    i32_const(18),
+                    i32_const(0x5566),
+                    mem_instr(OpCode::I32Store16, 2),
+                ],
+            );
+            let log = Rc::new(std::cell::RefCell::new(vec![]));
+            let sink_log = log.clone();
+            let mut vm = VM::new(&module);
+            vm.audit_memory_writes(Box::new(move |write: &MemoryWrite| {
+                sink_log.borrow_mut().push(write.clone());
+            }));
+            vm.run_main().unwrap();
+            let log = log.borrow();
+            assert_eq!(log.len(), 2);
+            assert_eq!(log[0].addr, 16);
+            assert_eq!(log[0].data, vec![0x44, 0x33, 0x22, 0x11]);
+            assert_eq!((log[0].func_idx, log[0].pc), (0, 2));
+            assert_eq!(log[1].addr, 20);
+            assert_eq!(log[1].data, vec![0x66, 0x55]);
+            assert_eq!((log[1].func_idx, log[1].pc), (0, 5));
+        }
+
+        #[test]
+        fn test_failed_instantiation_is_sticky() {
+            // 数据段越过 1 页内存的末尾，实例化陷入
+            let mut module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![],
+            );
+            module.data_sec.push(Data {
+                mem: 0,
+                offset: vec![i32_const(65535)],
+                init: vec![1, 2, 3],
+            });
+            let mut vm = VM::new(&module);
+            for _ in 0..2 {
+                let ret = vm.invoke("main", &[]);
+                assert!(matches!(
+                    ret,
+                    Err(Error::Trap(Trap::MemoryOutOfBounds))
+                ));
+            }
+        }
+
+        #[test]
+        fn test_float_min_max_nan() {
+            assert!(min_f32(f32::NAN, 1.0).is_nan());
+            assert!(max_f32(1.0, f32::NAN).is_nan());
+            assert!(min_f64(2.0, f64::NAN).is_nan());
+            assert!(max_f64(f64::NAN, 2.0).is_nan());
+            let zero = min_f32(0.0, -0.0);
+            assert!(zero == 0.0 && zero.is_sign_negative());
+            let zero = max_f64(-0.0, 0.0);
+            assert!(zero == 0.0 && zero.is_sign_positive());
+            assert_eq!(min_f64(1.0, 2.0), 1.0);
+            assert_eq!(max_f32(1.0, 2.0), 2.0);
+        }
+
+        #[test]
+        fn test_find_exported_memory() {
+            let mut module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![
+                    i32_const(8),
+                    i32_const(42),
+                    mem_instr(OpCode::I32Store8, 0),
+                ],
+            );
+            module.export_sec.push(Export {
+                name: "linearMemory".to_string(),
+                desc: ExportDesc::Mem(0),
+            });
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            assert_eq!(vm.find_exported_memory("linearMemory"), Some(0));
+            assert_eq!(vm.find_exported_memory("memory"), None);
+            let mut buf = [0u8; 1];
+            vm.read_memory(Some("linearMemory"), 8, &mut buf).unwrap();
+            assert_eq!(buf[0], 42);
+            vm.write_memory(Some("linearMemory"), 9, &[7]).unwrap();
+            vm.read_memory(None, 9, &mut buf).unwrap();
+            assert_eq!(buf[0], 7);
+            // 导出了不存在的内存时返回错误而不是 panic
+            module.export_sec.push(Export {
+                name: "other".to_string(),
+                desc: ExportDesc::Mem(1),
+            });
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            assert!(matches!(
+                vm.read_memory(Some("other"), 0, &mut buf),
+                Err(Error::Link(msg)) if msg == "unknown memory index: 1"
+            ));
+            assert!(matches!(
+                vm.write_memory(Some("other"), 0, &[1]),
+                Err(Error::Link(_))
+            ));
+        }
+
+        // 构造一个导入了 env.assert_eq_i32 的模块，导出的 main 函数索引为 1
+        fn assert_module(
+            mut type_sec: Vec<FuncType>,
+            locals: Vec<Locals>,
+            expr: Expr,
+        ) -> Module {
+            type_sec.insert(0, FuncType::default());
+            type_sec.insert(
+                1,
+                FuncType {
+                    params_types: vec![ValType::I32, ValType::I32],
+                    result_types: vec![],
+                },
+            );
+            Module {
+                type_sec,
+                import_sec: vec![Import {
+                    module_name: "env".to_string(),
+                    member_name: "assert_eq_i32".to_string(),
+                    desc: ImportDesc::Func(1),
+                }],
+                func_sec: vec![0],
+                export_sec: vec![Export {
+                    name: "main".to_string(),
+                    desc: ExportDesc::Func(1),
+                }],
+                code_sec: vec![Code { locals, expr }],
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn test_loop_with_params() {
+            // loop 的类型为 [i32 i32] -> []，每次回跳时把 (a, b) 轮换为 (b, a + b)
+            let loop_type = FuncType {
+                params_types: vec![ValType::I32, ValType::I32],
+                result_types: vec![],
+            };
+            let if_args = IfArgs {
+                block_type: BLOCK_TYPE_EMPTY,
+                instructions_1: vec![
+                    idx_instr(OpCode::LocalGet, 1),
+                    idx_instr(OpCode::LocalGet, 0),
+                    idx_instr(OpCode::LocalGet, 1),
+                    instr(OpCode::I32Add, None),
+                    idx_instr(OpCode::Br, 1),
+                ],
+                instructions_2: vec![],
+            };
+            let loop_args = BlockArgs {
+                block_type: 2,
+                instructions: vec![
+                    idx_instr(OpCode::LocalSet, 1),
+                    idx_instr(OpCode::LocalSet, 0),
+                    idx_instr(OpCode::LocalGet, 2),
+                    i32_const(1),
+                    instr(OpCode::I32Add, None),
+                    idx_instr(OpCode::LocalTee, 2),
+                    i32_const(10),
+                    instr(OpCode::I32LtS, None),
+                    instr(OpCode::If, Some(Rc::new(if_args))),
+                ],
+            };
+            let module = assert_module(
+                vec![loop_type],
+                vec![Locals {
+                    n: 3,
+                    val_type: ValType::I32,
+                }],
+                vec![
+                    i32_const(0),
+                    i32_const(1),
+                    instr(OpCode::Loop, Some(Rc::new(loop_args))),
+                    idx_instr(OpCode::LocalGet, 0),
+                    i32_const(34),
+                    idx_instr(OpCode::Call, 0),
+                    idx_instr(OpCode::LocalGet, 1),
+                    i32_const(55),
+                    idx_instr(OpCode::Call, 0),
+                    idx_instr(OpCode::LocalGet, 2),
+                    i32_const(10),
+                    idx_instr(OpCode::Call, 0),
+                ],
+            );
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            assert_eq!(vm.operand_stack.length(), 0);
+        }
+
+        #[test]
+        fn test_block_with_param_and_result() {
+            // block 的类型为 [i32] -> [i32]，对参数加 1；块下方的 100 不受影响
+            let inc_block = |exit: Vec<Instruction>| {
+                let mut instructions =
+                    vec![i32_const(1), instr(OpCode::I32Add, None)];
+                instructions.extend(exit);
+                let block_args = BlockArgs {
+                    block_type: 1,
+                    instructions,
+                };
+                instr(OpCode::Block, Some(Rc::new(block_args)))
+            };
+            let i32_to_i32 = FuncType {
+                params_types: vec![ValType::I32],
+                result_types: vec![ValType::I32],
+            };
+            for exit in [vec![], vec![idx_instr(OpCode::Br, 0)]] {
+                let mut module = main_module(
+                    vec![],
+                    vec![
+                        i32_const(100),
+                        idx_instr(OpCode::LocalGet, 0),
+                        inc_block(exit),
+                    ],
+                );
+                module.type_sec = vec![
+                    FuncType {
+                        params_types: vec![ValType::I32],
+                        result_types: vec![ValType::I32, ValType::I32],
+                    },
+                    i32_to_i32.clone(),
+                ];
+                let ret = VM::new(&module)
+                    .invoke("main", &[WasmValue::I32(41)])
+                    .unwrap();
+                assert_eq!(ret, vec![WasmValue::I32(100), WasmValue::I32(42)]);
+            }
+        }
+
+        fn block(instructions: Vec<Instruction>) -> Instruction {
+            let block_args = BlockArgs {
+                block_type: BLOCK_TYPE_EMPTY,
+                instructions,
+            };
+            instr(OpCode::Block, Some(Rc::new(block_args)))
+        }
+
+        #[test]
+        fn test_dead_code_after_br() {
+            let module = main_module(
+                vec![],
+                vec![block(vec![
+                    idx_instr(OpCode::Br, 0),
+                    instr(OpCode::Unreachable, None),
+                ])],
+            );
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            assert_eq!(vm.operand_stack.length(), 0);
+        }
+
+        #[test]
+        fn test_br_if() {
+            let module = main_module(
+                vec![],
+                vec![block(vec![
+                    i32_const(1),
+                    idx_instr(OpCode::BrIf, 0),
+                    instr(OpCode::Unreachable, None),
+                ])],
+            );
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            assert_eq!(vm.operand_stack.length(), 0);
+        }
+
+        #[test]
+        fn test_br_if_not_taken_in_result_block() {
+            // block (result i32) 中 br_if 0 携带 7，条件为 cond；
+            // rest 为 br_if 之后直到 end 的指令
+            let run = |cond: i32, rest: Vec<Instruction>| {
+                let mut instructions = vec![
+                    i32_const(7),
+                    i32_const(cond),
+                    idx_instr(OpCode::BrIf, 0),
+                ];
+                instructions.extend(rest);
+                let block_args = BlockArgs {
+                    block_type: BLOCK_TYPE_I32,
+                    instructions,
+                };
+                let mut module = main_module(
+                    vec![],
+                    vec![instr(OpCode::Block, Some(Rc::new(block_args)))],
+                );
+                module.type_sec[0].result_types = vec![ValType::I32];
+                let mut vm = VM::new(&module);
+                let ret = vm.invoke("main", &[]).unwrap();
+                assert_eq!(vm.operand_stack.length(), 0);
+                ret
+            };
+            let replace = || vec![instr(OpCode::Drop, None), i32_const(42)];
+            // 未跳转时 br_if 的值留在栈上，由之后的指令提供块的结果
+            assert_eq!(run(0, replace()), vec![WasmValue::I32(42)]);
+            assert_eq!(run(0, vec![]), vec![WasmValue::I32(7)]);
+            // 跳转时块的结果为 br_if 携带的值
+            assert_eq!(run(1, replace()), vec![WasmValue::I32(7)]);
+        }
+
+        #[test]
+        fn test_dead_code_after_br_table() {
+            let br_table_args = BrTableArgs {
+                labels: vec![1],
+                default: 0,
+            };
+            // 索引 5 越界，应跳转到默认标签 0，跳过 unreachable
+            let module = main_module(
+                vec![],
+                vec![
+                    block(vec![
+                        i32_const(5),
+                        instr(OpCode::BrTable, Some(Rc::new(br_table_args))),
+                        instr(OpCode::Unreachable, None),
+                    ]),
+                    i32_const(1),
+                    instr(OpCode::Drop, None),
+                ],
+            );
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            assert_eq!(vm.operand_stack.length(), 0);
+        }
+
+        #[test]
+        fn test_dead_code_after_return() {
+            let module = main_module(
+                vec![],
+                vec![
+                    block(vec![
+                        instr(OpCode::Return, None),
+                        instr(OpCode::Unreachable, None),
+                    ]),
+                    instr(OpCode::Unreachable, None),
+                ],
+            );
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            assert_eq!(vm.operand_stack.length(), 0);
+        }
+
+        #[test]
+        fn test_coverage() {
+            let if_args = IfArgs {
+                block_type: BLOCK_TYPE_EMPTY,
+                instructions_1: vec![i32_const(1), instr(OpCode::Drop, None)],
+                instructions_2: vec![
+                    i32_const(2),
+                    instr(OpCode::Drop, None),
+                    instr(OpCode::Nop, None),
+                ],
+            };
+            let module = main_module(
+                vec![],
+                vec![i32_const(1), instr(OpCode::If, Some(Rc::new(if_args)))],
+            );
+            let mut vm = VM::new(&module).track_coverage(true);
+            vm.run_main().unwrap();
+            let coverage = vm.coverage();
+            assert_eq!(
+                coverage[&0],
+                vec![true, true, true, true, false, false, false]
+            );
+        }
+
+        #[test]
+        fn test_coverage_disabled() {
+            let module = main_module(vec![], vec![instr(OpCode::Nop, None)]);
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            assert!(vm.coverage().is_empty());
+        }
+
+        #[test]
+        fn test_unaligned_load() {
+            let module = unaligned_load_module();
+            VM::new(&module).strict_alignment(false).run_main().unwrap();
+        }
+
+        #[test]
+        fn test_unaligned_access_count() {
+            // 第一次 i32.load 的对齐提示为 1 字节，第二次为自然对齐 4 字节
+            let load = |align| {
+                instr(
+                    OpCode::I32Load,
+                    Some(Rc::new(MemArg { align, offset: 0 })),
+                )
+            };
+            let module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![
+                    i32_const(0),
+                    load(0),
+                    instr(OpCode::Drop, None),
+                    i32_const(0),
+                    load(2),
+                    instr(OpCode::Drop, None),
+                    // 畸形的对齐指数不会导致移位溢出
+                    i32_const(0),
+                    load(64),
+                    instr(OpCode::Drop, None),
+                ],
+            );
+            let mut vm = VM::new(&module);
+            assert_eq!(vm.unaligned_access_count(), 0);
+            vm.run_main().unwrap();
+            assert_eq!(vm.unaligned_access_count(), 1);
+        }
+
+        #[test]
+        fn test_unaligned_load_strict() {
+            let module = unaligned_load_module();
+            let ret = VM::new(&module).strict_alignment(true).run_main();
+            assert!(matches!(ret, Err(Error::Trap(Trap::UnalignedAccess))));
+        }
+
+        #[test]
+        fn test_run_stats() {
+            // (func $sum (param i32) (result i32)
+            //   local.get 0 i32.eqz
+            //   if (result i32) i32.const 0
+            //   else local.get 0 local.get 0 i32.const 1 i32.sub call $sum
+            //     i32.add end)
+            let if_args = IfArgs {
+                block_type: BLOCK_TYPE_I32,
+                instructions_1: vec![i32_const(0)],
+                instructions_2: vec![
+                    idx_instr(OpCode::LocalGet, 0),
+                    idx_instr(OpCode::LocalGet, 0),
+                    i32_const(1),
+                    instr(OpCode::I32Sub, None),
+                    idx_instr(OpCode::Call, 0),
+                    instr(OpCode::I32Add, None),
+                ],
+            };
+            let module = Module {
+                type_sec: vec![
+                    FuncType {
+                        params_types: vec![ValType::I32],
+                        result_types: vec![ValType::I32],
+                    },
+                    FuncType::default(),
+                ],
+                func_sec: vec![0, 1],
+                mem_sec: vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                export_sec: vec![
+                    Export {
+                        name: "sum".to_string(),
+                        desc: ExportDesc::Func(0),
+                    },
+                    Export {
+                        name: "grow".to_string(),
+                        desc: ExportDesc::Func(1),
+                    },
+                ],
+                code_sec: vec![
+                    Code {
+                        locals: vec![],
+                        expr: vec![
+                            idx_instr(OpCode::LocalGet, 0),
+                            instr(OpCode::I32Eqz, None),
+                            instr(OpCode::If, Some(Rc::new(if_args))),
+                        ],
+                    },
+                    Code {
+                        locals: vec![],
+                        expr: vec![
+                            i32_const(3),
+                            instr(OpCode::MemoryGrow, Some(Rc::new(0u8))),
+                            instr(OpCode::Drop, None),
+                        ],
+                    },
+                ],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            vm.instantiate().unwrap();
+            let ret = vm.invoke("sum", &[WasmValue::I32(10)]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(55)]);
+            vm.invoke("grow", &[]).unwrap();
+            let stats = vm.run_stats();
+            // 每层递归至少有一个调用帧和一个 if 帧
+            assert!(stats.peak_control_stack >= 22);
+            assert!(stats.peak_operand_stack >= 11);
+            assert_eq!(stats.peak_memory_pages, 4);
+            assert_eq!(vm.control_stack.control_depth(), 0);
+        }
+
+        #[test]
+        fn test_multi_value_swap() {
+            // (func (export "swap") (param i32 i32) (result i32 i32)
+            //   block (type 0) (param i32 i32) (result i32 i32)
+            //     local.set 0 local.set 1 local.get 0 local.get 1
+            //   end)
+            let swap_type = FuncType {
+                params_types: vec![ValType::I32, ValType::I32],
+                result_types: vec![ValType::I32, ValType::I32],
+            };
+            let block_args = BlockArgs {
+                block_type: 0,
+                instructions: vec![
+                    idx_instr(OpCode::LocalSet, 0),
+                    idx_instr(OpCode::LocalSet, 1),
+                    idx_instr(OpCode::LocalGet, 0),
+                    idx_instr(OpCode::LocalGet, 1),
+                ],
+            };
+            let module = Module {
+                type_sec: vec![swap_type],
+                func_sec: vec![0],
+                export_sec: vec![Export {
+                    name: "swap".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![
+                        idx_instr(OpCode::LocalGet, 0),
+                        idx_instr(OpCode::LocalGet, 1),
+                        instr(OpCode::Block, Some(Rc::new(block_args))),
+                    ],
+                }],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            vm.instantiate().unwrap();
+            let ret = vm
+                .invoke("swap", &[WasmValue::I32(1), WasmValue::I32(2)])
+                .unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(2), WasmValue::I32(1)]);
+            assert_eq!(vm.operand_stack.length(), 0);
+        }
+
+        // 在地址 0 处写入 val，再用 load_op 读出
+        fn store_then_load(
+            val: Instruction,
+            store_op: OpCode,
+            load_op: OpCode,
+            result_type: ValType,
+        ) -> WasmValue {
+            let module = Module {
+                type_sec: vec![FuncType {
+                    params_types: vec![],
+                    result_types: vec![result_type],
+                }],
+                func_sec: vec![0],
+                mem_sec: vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                export_sec: vec![Export {
+                    name: "load".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![
+                        i32_const(0),
+                        val,
+                        mem_instr(store_op, 0),
+                        i32_const(0),
+                        mem_instr(load_op, 0),
+                    ],
+                }],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            vm.instantiate().unwrap();
+            vm.invoke("load", &[]).unwrap()[0]
+        }
+
+        #[test]
+        fn test_load_sign_extension() {
+            let cases = [
+                (OpCode::I32Load8S, WasmValue::I32(-1)),
+                (OpCode::I32Load8U, WasmValue::I32(255)),
+            ];
+            for (load_op, expected) in cases {
+                let val = i32_const(0xFF);
+                let ret = store_then_load(
+                    val,
+                    OpCode::I32Store8,
+                    load_op,
+                    ValType::I32,
+                );
+                assert_eq!(ret, expected);
+            }
+            let cases = [
+                (OpCode::I32Load16S, WasmValue::I32(-32768)),
+                (OpCode::I32Load16U, WasmValue::I32(32768)),
+            ];
+            for (load_op, expected) in cases {
+                let val = i32_const(0x8000);
+                let ret = store_then_load(
+                    val,
+                    OpCode::I32Store16,
+                    load_op,
+                    ValType::I32,
+                );
+                assert_eq!(ret, expected);
+            }
+            let cases = [
+                (OpCode::I64Load8S, 0xFF, WasmValue::I64(-1)),
+                (OpCode::I64Load8U, 0xFF, WasmValue::I64(255)),
+                (OpCode::I64Load16S, 0x8000, WasmValue::I64(-32768)),
+                (OpCode::I64Load16U, 0x8000, WasmValue::I64(32768)),
+                (OpCode::I64Load32S, 0x8000_0000, WasmValue::I64(-2147483648)),
+                (OpCode::I64Load32U, 0x8000_0000, WasmValue::I64(2147483648)),
+            ];
+            for (load_op, val, expected) in cases {
+                let ret = store_then_load(
+                    i64_const(val),
+                    OpCode::I64Store,
+                    load_op,
+                    ValType::I64,
+                );
+                assert_eq!(ret, expected);
+            }
+        }
+
+        #[test]
+        fn test_typed_stack_select() {
+            // select 保留的值类型不变，reinterpret 只改变类型
+            let mut module = main_module(
+                vec![],
+                vec![
+                    instr(OpCode::F32Const, Some(Rc::new(1.5f32))),
+                    instr(OpCode::F32Const, Some(Rc::new(2.5f32))),
+                    i32_const(0),
+                    instr(OpCode::Select, None),
+                    instr(OpCode::I32ReinterpretF32, None),
+                    instr(OpCode::F64Const, Some(Rc::new(1.0f64))),
+                    instr(OpCode::F64Const, Some(Rc::new(2.0f64))),
+                    i32_const(1),
+                    instr(OpCode::Select, None),
+                ],
+            );
+            module.type_sec[0].result_types = vec![ValType::I32, ValType::F64];
+            let mut vm = VM::new(&module).typed_stack(true);
+            vm.instantiate().unwrap();
+            vm.exec_func(0).unwrap();
+            assert_eq!(
+                vm.operand_types(),
+                Some(&[ValType::I32, ValType::F64][..])
+            );
+            assert_eq!(vm.operand_stack.pop_f64(), 1.0);
+            assert_eq!(vm.operand_stack.pop_u32(), 2.5f32.to_bits());
+        }
+
+        #[test]
+        fn test_float_store_round_trip() {
+            for val in [1.5f32, -0.1, f32::MIN_POSITIVE] {
+                let ret = store_then_load(
+                    instr(OpCode::F32Const, Some(Rc::new(val))),
+                    OpCode::F32Store,
+                    OpCode::F32Load,
+                    ValType::F32,
+                );
+                match ret {
+                    WasmValue::F32(v) => assert_eq!(v.to_bits(), val.to_bits()),
+                    _ => panic!("expected f32, got {:?}", ret),
+                }
+            }
+            for val in [1.5f64, -0.1, f64::MIN_POSITIVE] {
+                let ret = store_then_load(
+                    instr(OpCode::F64Const, Some(Rc::new(val))),
+                    OpCode::F64Store,
+                    OpCode::F64Load,
+                    ValType::F64,
+                );
+                match ret {
+                    WasmValue::F64(v) => assert_eq!(v.to_bits(), val.to_bits()),
+                    _ => panic!("expected f64, got {:?}", ret),
+                }
+            }
+        }
+
+        // 从地址 65534 处写入 4 字节后读回，超出了 1 页内存
+        fn oob_store_load_module() -> Module {
+            let mut module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![
+                    i32_const(65534),
+                    i32_const(-1),
+                    mem_instr(OpCode::I32Store, 0),
+                    i32_const(65534),
+                    mem_instr(OpCode::I32Load, 0),
+                ],
+            );
+            module.type_sec[0].result_types.push(ValType::I32);
+            module
+        }
+
+        #[test]
+        fn test_oob_sentinel() {
+            let module = oob_store_load_module();
+            let mut vm = VM::new(&module).oob_memory_mode(OobMode::Sentinel);
+            vm.instantiate().unwrap();
+            let ret = vm.invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(0)]);
+            // 越界写被忽略，未越界的部分也不会被写入
+            let mut buf = [0u8; 2];
+            vm.read_memory(None, 65534, &mut buf).unwrap();
+            assert_eq!(buf, [0, 0]);
+        }
+
+        #[test]
+        fn test_oob_trap() {
+            let module = oob_store_load_module();
+            let mut vm = VM::new(&module).oob_memory_mode(OobMode::Trap);
+            vm.instantiate().unwrap();
+            let ret = vm.invoke("main", &[]);
+            assert!(matches!(ret, Err(Error::Trap(Trap::MemoryOutOfBounds))));
+        }
+
+        #[test]
+        fn test_start_func_calls_helper() {
+            // func 0 为起始函数，调用 func 1 将全局变量设为 42，导出的 main 读取全局变量
+            let module = Module {
+                type_sec: vec![
+                    FuncType::default(),
+                    FuncType {
+                        params_types: vec![],
+                        result_types: vec![ValType::I32],
+                    },
+                ],
+                func_sec: vec![0, 0, 1],
+                global_sec: vec![Global {
+                    global_type: GlobalType {
+                        val_type: ValType::I32,
+                        mutable: true,
+                    },
+                    init_expr: vec![i32_const(0)],
+                }],
+                export_sec: vec![Export {
+                    name: "main".to_string(),
+                    desc: ExportDesc::Func(2),
+                }],
+                start_sec: Some(0),
+                code_sec: vec![
+                    Code {
+                        locals: vec![],
+                        expr: vec![idx_instr(OpCode::Call, 1)],
+                    },
+                    Code {
+                        locals: vec![],
+                        expr: vec![
+                            i32_const(42),
+                            idx_instr(OpCode::GlobalSet, 0),
+                        ],
+                    },
+                    Code {
+                        locals: vec![],
+                        expr: vec![idx_instr(OpCode::GlobalGet, 0)],
+                    },
+                ],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            vm.instantiate().unwrap();
+            assert_eq!(vm.control_stack.control_depth(), 0);
+            let ret = vm.invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(42)]);
+        }
+
+        #[test]
+        fn test_invoke_reuses_instance() {
+            // inc 将全局计数器加 1，get 返回计数器的值
+            let module = Module {
+                type_sec: vec![
+                    FuncType::default(),
+                    FuncType {
+                        params_types: vec![],
+                        result_types: vec![ValType::I32],
+                    },
+                ],
+                func_sec: vec![0, 1],
+                global_sec: vec![Global {
+                    global_type: GlobalType {
+                        val_type: ValType::I32,
+                        mutable: true,
+                    },
+                    init_expr: vec![i32_const(0)],
+                }],
+                export_sec: vec![
+                    Export {
+                        name: "inc".to_string(),
+                        desc: ExportDesc::Func(0),
+                    },
+                    Export {
+                        name: "get".to_string(),
+                        desc: ExportDesc::Func(1),
+                    },
+                ],
+                code_sec: vec![
+                    Code {
+                        locals: vec![],
+                        expr: vec![
+                            idx_instr(OpCode::GlobalGet, 0),
+                            i32_const(1),
+                            instr(OpCode::I32Add, None),
+                            idx_instr(OpCode::GlobalSet, 0),
+                        ],
+                    },
+                    Code {
+                        locals: vec![],
+                        expr: vec![idx_instr(OpCode::GlobalGet, 0)],
+                    },
+                ],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            vm.instantiate().unwrap();
+            vm.invoke("inc", &[]).unwrap();
+            vm.instantiate().unwrap();
+            vm.invoke("inc", &[]).unwrap();
+            let ret = vm.invoke("get", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(2)]);
+            assert_eq!(vm.globals.len(), 1);
+            assert_eq!(vm.vm_funcs.len(), 2);
+        }
+
+        #[test]
+        fn test_empty_body() {
+            let module = main_module(vec![], vec![]);
+            let mut vm = VM::new(&module);
+            assert_eq!(vm.invoke("main", &[]).unwrap(), vec![]);
+            assert_eq!(vm.control_stack.control_depth(), 0);
+            assert_eq!(vm.operand_stack.length(), 0);
+        }
+
+        #[test]
+        fn test_nop_body() {
+            let module = main_module(
+                vec![],
+                vec![
+                    instr(OpCode::Nop, None),
+                    instr(OpCode::Nop, None),
+                    instr(OpCode::Nop, None),
+                    block(vec![]),
+                ],
+            );
+            let mut vm = VM::new(&module).track_coverage(true);
+            assert_eq!(vm.invoke("main", &[]).unwrap(), vec![]);
+            assert_eq!(vm.control_stack.control_depth(), 0);
+            assert_eq!(vm.operand_stack.length(), 0);
+            assert_eq!(vm.coverage()[&0], vec![true; 4]);
+        }
+
+        fn memory_fill_module(offset: i32, val: i32, n: i32) -> Module {
+            main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![
+                    i32_const(offset),
+                    i32_const(val),
+                    i32_const(n),
+                    prefixed_instr(MEMORY_FILL, 0),
+                ],
+            )
+        }
+
+        fn prefixed_instr(sub_opcode: u8, idx: u32) -> Instruction {
+            let args = PrefixedArgs {
+                sub_opcode,
+                idx,
+                src_idx: 0,
+            };
+            instr(OpCode::TruncSat, Some(Rc::new(args)))
+        }
+
+        // 主动段在实例化时写入地址 0，main 再用 memory.init 从同一个段复制到地址 100
+        fn memory_init_module(drop_first: bool) -> Module {
+            let mut expr = vec![];
+            if drop_first {
+                expr.push(prefixed_instr(DATA_DROP, 0));
+            }
+            expr.extend([
+                i32_const(100),
+                i32_const(1),
+                i32_const(4),
+                prefixed_instr(MEMORY_INIT, 0),
+            ]);
+            let mut module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                expr,
+            );
+            module.data_sec = vec![Data {
+                mem: 0,
+                offset: vec![i32_const(0)],
+                init: b"hello".to_vec(),
+            }];
+            module
+        }
+
+        #[test]
+        fn test_memory_init_from_active_segment() {
+            let module = memory_init_module(false);
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            let mut buf = [0u8; 5];
+            vm.read_memory(None, 0, &mut buf).unwrap();
+            assert_eq!(&buf, b"hello");
+            let mut buf = [0u8; 4];
+            vm.read_memory(None, 100, &mut buf).unwrap();
+            assert_eq!(&buf, b"ello");
+        }
+
+        #[test]
+        fn test_memory_init_dropped_segment() {
+            let module = memory_init_module(true);
+            let ret = VM::new(&module).run_main();
+            assert!(matches!(ret, Err(Error::Trap(Trap::MemoryOutOfBounds))));
+        }
+
+        #[test]
+        fn test_memory_fill() {
+            let module = memory_fill_module(16, 0x1FF, 4);
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            let mut buf = [0u8; 6];
+            vm.read_memory(None, 15, &mut buf).unwrap();
+            assert_eq!(buf, [0, 0xFF, 0xFF, 0xFF, 0xFF, 0]);
+        }
+
+        #[test]
+        fn test_memory_copy_overlap() {
+            // 把 [0, 10) 复制到 [3, 13)，两个区域重叠
+            let module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![
+                    i32_const(3),
+                    i32_const(0),
+                    i32_const(10),
+                    prefixed_instr(MEMORY_COPY, 0),
+                ],
+            );
+            let mut vm = VM::new(&module);
+            vm.instantiate().unwrap();
+            let src: Vec<u8> = (1..=10).collect();
+            vm.write_memory(None, 0, &src).unwrap();
+            vm.invoke("main", &[]).unwrap();
+            let mut buf = [0u8; 13];
+            vm.read_memory(None, 0, &mut buf).unwrap();
+            assert_eq!(buf, [1, 2, 3, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        }
+
+        #[test]
+        fn test_poison_memory() {
+            // main 先向地址 8 写入 7，再读取地址 addr
+            let module = |addr| {
+                main_module(
+                    vec![Limits {
+                        min: 1,
+                        ..Default::default()
+                    }],
+                    vec![
+                        i32_const(8),
+                        i32_const(7),
+                        mem_instr(OpCode::I32Store, 0),
+                        i32_const(addr),
+                        mem_instr(OpCode::I32Load, 0),
+                        instr(OpCode::Drop, None),
+                    ],
+                )
+            };
+            let written = module(8);
+            assert!(VM::new(&written).poison_memory(true).run_main().is_ok());
+            let unwritten = module(16);
+            let ret = VM::new(&unwritten).poison_memory(true).run_main();
+            assert!(matches!(ret, Err(Error::Trap(Trap::UninitializedRead))));
+            // 部分字节未写过同样陷入
+            let partial = module(10);
+            let ret = VM::new(&partial).poison_memory(true).run_main();
+            assert!(matches!(ret, Err(Error::Trap(Trap::UninitializedRead))));
+            assert!(VM::new(&unwritten).run_main().is_ok());
+        }
+
+        #[test]
+        fn test_memory_fill_zero_len() {
+            let module = memory_fill_module(65536, 0xFF, 0);
+            let mut vm = VM::new(&module);
+            vm.run_main().unwrap();
+            assert!(vm.memory.data.iter().all(|b| *b == 0));
+        }
+
+        #[test]
+        fn test_memory_fill_out_of_bounds() {
+            let module = memory_fill_module(65534, 0xFF, 4);
+            let ret = VM::new(&module).run_main();
+            assert!(matches!(ret, Err(Error::Trap(Trap::MemoryOutOfBounds))));
+        }
+
+        #[test]
+        fn test_mock_function() {
+            // main 调用 func 1 并将结果加 1，func 1 被 mock 为总是返回 99
+            let i32_result = FuncType {
+                params_types: vec![],
+                result_types: vec![ValType::I32],
+            };
+            let module = Module {
+                type_sec: vec![
+                    i32_result,
+                    FuncType {
+                        params_types: vec![ValType::I32],
+                        result_types: vec![ValType::I32],
+                    },
+                ],
+                func_sec: vec![0, 1],
+                export_sec: vec![Export {
+                    name: "main".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                code_sec: vec![
+                    Code {
+                        locals: vec![],
+                        expr: vec![
+                            i32_const(7),
+                            idx_instr(OpCode::Call, 1),
+                            i32_const(1),
+                            instr(OpCode::I32Add, None),
+                        ],
+                    },
+                    Code {
+                        locals: vec![],
+                        expr: vec![instr(OpCode::Unreachable, None)],
+                    },
+                ],
+                ..Default::default()
+            };
+            let calls = Rc::new(std::cell::RefCell::new(vec![]));
+            let recorded = calls.clone();
+            let mut vm = VM::new(&module);
+            vm.mock_function(
+                1,
+                Box::new(move |args| {
+                    recorded.borrow_mut().push(args);
+                    vec![WasmValue::I32(99)]
+                }),
+            );
+            let ret = vm.invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(100)]);
+            assert_eq!(*calls.borrow(), vec![vec![WasmValue::I32(7)]]);
+            // mock 返回值的个数或类型与签名不符时陷入
+            for results in [vec![], vec![WasmValue::I64(99)]] {
+                let mut vm = VM::new(&module);
+                vm.mock_function(1, Box::new(move |_| results.clone()));
+                let ret = vm.invoke("main", &[]);
+                assert!(matches!(
+                    ret,
+                    Err(Error::Trap(Trap::HostResultMismatch))
+                ));
+            }
+        }
+
+        #[test]
+        fn test_select_f64() {
+            let f64_const = |v: f64| instr(OpCode::F64Const, Some(Rc::new(v)));
+            for (cond, expected) in [(0, 2.5), (1, 1.5)] {
+                let mut module = main_module(
+                    vec![],
+                    vec![
+                        f64_const(1.5),
+                        f64_const(2.5),
+                        i32_const(cond),
+                        instr(OpCode::Select, None),
+                    ],
+                );
+                module.type_sec[0].result_types.push(ValType::F64);
+                let ret = VM::new(&module).invoke("main", &[]).unwrap();
+                assert_eq!(ret, vec![WasmValue::F64(expected)]);
+            }
+        }
+
+        #[test]
+        fn test_funcref_param() {
+            // apply 把传入的函数引用放到表中再间接调用，callee 返回 42
+            let module = Module {
+                type_sec: vec![
+                    FuncType {
+                        params_types: vec![ValType::FuncRef],
+                        result_types: vec![ValType::I32],
+                    },
+                    FuncType {
+                        params_types: vec![],
+                        result_types: vec![ValType::I32],
+                    },
+                ],
+                func_sec: vec![0, 1],
+                table_sec: vec![TableType {
+                    elem_type: ValType::FuncRef,
+                    limits: Limits {
+                        min: 1,
+                        ..Default::default()
+                    },
+                }],
+                export_sec: vec![Export {
+                    name: "apply".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                code_sec: vec![
+                    Code {
+                        locals: vec![],
+                        expr: vec![
+                            i32_const(0),
+                            idx_instr(OpCode::LocalGet, 0),
+                            idx_instr(OpCode::TableSet, 0),
+                            i32_const(0),
+                            call_indirect(1, 0),
+                        ],
+                    },
+                    Code {
+                        locals: vec![],
+                        expr: vec![i32_const(42)],
+                    },
+                ],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            let ret = vm.invoke("apply", &[WasmValue::FuncRef(Some(1))]);
+            assert_eq!(ret.unwrap(), vec![WasmValue::I32(42)]);
+            let ret = vm.invoke("apply", &[WasmValue::FuncRef(None)]);
+            assert!(matches!(
+                ret,
+                Err(Error::Trap(Trap::UninitializedElement))
+            ));
+            // 函数索引越界的引用在调用前被拒绝
+            let ret = vm.invoke("apply", &[WasmValue::FuncRef(Some(999))]);
+            assert!(matches!(ret, Err(Error::ArgumentMismatch(_))));
+        }
+
+        #[test]
+        fn test_table_copy_overlapping() {
+            // 表中 0..3 为 func 2, 3, 4，copy(dst, src, n) 执行 table.copy 0 0，
+            // get(i) 间接调用表中第 i 个函数
+            let i32_result = FuncType {
+                params_types: vec![],
+                result_types: vec![ValType::I32],
+            };
+            let get_type = FuncType {
+                params_types: vec![ValType::I32],
+                result_types: vec![ValType::I32],
+            };
+            let copy_type = FuncType {
+                params_types: vec![ValType::I32; 3],
+                result_types: vec![],
+            };
+            let table_copy = PrefixedArgs {
+                sub_opcode: TABLE_COPY,
+                idx: 0,
+                src_idx: 0,
+            };
+            let code = |expr| Code {
+                locals: vec![],
+                expr,
+            };
+            let export = |name: &str, idx| Export {
+                name: name.to_string(),
+                desc: ExportDesc::Func(idx),
+            };
+            let module = Module {
+                type_sec: vec![i32_result, get_type, copy_type],
+                func_sec: vec![2, 1, 0, 0, 0],
+                table_sec: vec![TableType {
+                    elem_type: ValType::FuncRef,
+                    limits: Limits {
+                        min: 5,
+                        ..Default::default()
+                    },
+                }],
+                export_sec: vec![export("copy", 0), export("get", 1)],
+                elem_sec: vec![Elem {
+                    mode: ElemMode::Active,
+                    table: 0,
+                    offset: vec![i32_const(0)],
+                    init: vec![2, 3, 4],
+                }],
+                code_sec: vec![
+                    code(vec![
+                        idx_instr(OpCode::LocalGet, 0),
+                        idx_instr(OpCode::LocalGet, 1),
+                        idx_instr(OpCode::LocalGet, 2),
+                        instr(OpCode::TruncSat, Some(Rc::new(table_copy))),
+                    ]),
+                    code(vec![
+                        idx_instr(OpCode::LocalGet, 0),
+                        call_indirect(0, 0),
+                    ]),
+                    code(vec![i32_const(1)]),
+                    code(vec![i32_const(2)]),
+                    code(vec![i32_const(3)]),
+                ],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            let args = [1, 0, 3].map(WasmValue::I32);
+            assert!(vm.invoke("copy", &args).is_ok());
+            for (i, expected) in [1, 1, 2, 3].into_iter().enumerate() {
+                let ret = vm.invoke("get", &[WasmValue::I32(i as i32)]);
+                assert_eq!(ret.unwrap(), vec![WasmValue::I32(expected)]);
+            }
+            // 向低地址复制重叠范围
+            let args = [0, 1, 3].map(WasmValue::I32);
+            assert!(vm.invoke("copy", &args).is_ok());
+            for (i, expected) in [1, 2, 3, 3].into_iter().enumerate() {
+                let ret = vm.invoke("get", &[WasmValue::I32(i as i32)]);
+                assert_eq!(ret.unwrap(), vec![WasmValue::I32(expected)]);
+            }
+            // 越界时陷入，表保持不变
+            let args = [3, 0, 3].map(WasmValue::I32);
+            let ret = vm.invoke("copy", &args);
+            assert!(matches!(ret, Err(Error::Trap(Trap::TableOutOfBounds))));
+            let ret = vm.invoke("get", &[WasmValue::I32(3)]);
+            assert_eq!(ret.unwrap(), vec![WasmValue::I32(3)]);
+        }
+
+        #[test]
+        fn test_init_table_out_of_bounds() {
+            let module_with = |offset, init| Module {
+                type_sec: vec![FuncType::default()],
+                func_sec: vec![0],
+                table_sec: vec![TableType {
+                    elem_type: ValType::FuncRef,
+                    limits: Limits {
+                        min: 2,
+                        ..Default::default()
+                    },
+                }],
+                elem_sec: vec![Elem {
+                    mode: ElemMode::Active,
+                    table: 0,
+                    offset: vec![i32_const(offset)],
+                    init,
+                }],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![],
+                }],
+                ..Default::default()
+            };
+            let module = module_with(1, vec![0]);
+            assert!(VM::new(&module).instantiate().is_ok());
+            // 段超出表的大小
+            let module = module_with(1, vec![0, 0]);
+            let ret = VM::new(&module).instantiate();
+            assert!(matches!(ret, Err(Error::Trap(Trap::TableOutOfBounds))));
+            // 段中的函数索引不存在
+            let module = module_with(0, vec![5]);
+            let ret = VM::new(&module).instantiate();
+            assert!(matches!(ret, Err(Error::Trap(Trap::UndefinedElement))));
+        }
+
+        #[test]
+        fn test_call_indirect_second_table() {
+            // 表 0 的元素为 func 1，表 1 的元素为 func 2，main 通过表 1 间接调用
+            let i32_result = FuncType {
+                params_types: vec![],
+                result_types: vec![ValType::I32],
+            };
+            let func_table = TableType {
+                elem_type: ValType::FuncRef,
+                limits: Limits {
+                    min: 1,
+                    ..Default::default()
+                },
+            };
+            let elem = |table, func_idx| Elem {
+                mode: ElemMode::Active,
+                table,
+                offset: vec![i32_const(0)],
+                init: vec![func_idx],
+            };
+            let code = |expr| Code {
+                locals: vec![],
+                expr,
+            };
+            let module = Module {
+                type_sec: vec![i32_result],
+                func_sec: vec![0, 0, 0],
+                table_sec: vec![func_table, func_table],
+                export_sec: vec![Export {
+                    name: "main".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                elem_sec: vec![elem(0, 1), elem(1, 2)],
+                code_sec: vec![
+                    code(vec![i32_const(0), call_indirect(0, 1)]),
+                    code(vec![i32_const(1)]),
+                    code(vec![i32_const(2)]),
+                ],
+                ..Default::default()
+            };
+            let ret = VM::new(&module).invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(2)]);
+        }
+
+        #[test]
+        fn test_call_indirect_sig_id() {
+            // 类型 0 和类型 1 签名相同，类型 2 不同；表中为 func 1 (类型 1)
+            let i32_result = FuncType {
+                params_types: vec![],
+                result_types: vec![ValType::I32],
+            };
+            let i64_result = FuncType {
+                params_types: vec![],
+                result_types: vec![ValType::I64],
+            };
+            let mut module = Module {
+                type_sec: vec![i32_result.clone(), i32_result, i64_result],
+                func_sec: vec![0, 1],
+                table_sec: vec![TableType {
+                    elem_type: ValType::FuncRef,
+                    limits: Limits {
+                        min: 1,
+                        ..Default::default()
+                    },
+                }],
+                export_sec: vec![Export {
+                    name: "main".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                elem_sec: vec![Elem {
+                    mode: ElemMode::Active,
+                    table: 0,
+                    offset: vec![i32_const(0)],
+                    init: vec![1],
+                }],
+                code_sec: vec![
+                    Code {
+                        locals: vec![],
+                        expr: vec![i32_const(0)],
+                    },
+                    Code {
+                        locals: vec![],
+                        expr: vec![i32_const(5)],
+                    },
+                ],
+                ..Default::default()
+            };
+            // 循环中多次间接调用，签名匹配
+            let mut expr = vec![];
+            for _ in 0..100 {
+                expr.push(i32_const(0));
+                expr.push(call_indirect(0, 0));
+                expr.push(instr(OpCode::I32Add, None));
+            }
+            module.code_sec[0].expr.extend(expr);
+            let ret = VM::new(&module).invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(500)]);
+
+            module.code_sec[0].expr = vec![
+                i32_const(0),
+                call_indirect(2, 0),
+                instr(OpCode::Drop, None),
+                i32_const(0),
+            ];
+            let ret = VM::new(&module).invoke("main", &[]);
+            assert!(matches!(
+                ret,
+                Err(Error::Trap(Trap::IndirectCallTypeMismatch))
+            ));
+        }
+
+        // 表的元素段为 [ref.func 2, ref.func 1]，main 间接调用表中下标 1 的函数，
+        // func 1 返回 11，func 2 返回 22
+        const ELEM_EXPRS_WASM: &[u8] = &[
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00,
+            0x00, // magic & version
+            0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7f, // type sec
+            0x03, 0x04, 0x03, 0x00, 0x00, 0x00, // func sec
+            0x04, 0x04, 0x01, 0x70, 0x00, 0x02, // table sec
+            0x07, 0x08, 0x01, 0x04, 0x6d, 0x61, 0x69, 0x6e, 0x00,
+            0x00, // export
+            0x09, 0x0c, 0x01, 0x04, 0x41, 0x00, 0x0b, 0x02, 0xd2, 0x02, 0x0b,
+            0xd2, 0x01, 0x0b, // elem sec
+            0x0a, 0x13, 0x03, 0x07, 0x00, 0x41, 0x01, 0x11, 0x00, 0x00, 0x0b,
+            0x04, 0x00, 0x41, 0x0b, 0x0b, 0x04, 0x00, 0x41, 0x16,
+            0x0b, // code
+        ];
+
+        #[test]
+        fn test_elem_exprs_call_indirect() {
+            let module = WasmReader::decode_bytes(ELEM_EXPRS_WASM).unwrap();
+            assert_eq!(module.elem_sec[0].init, vec![2, 1]);
+            let ret = VM::new(&module).invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(11)]);
+        }
+
+        // 内存页大小为 1 字节，main 先增长 4 页，返回增长前后的页数
+        const ONE_BYTE_PAGE_WASM: &[u8] = &[
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00,
+            0x00, // magic & version
+            0x01, 0x06, 0x01, 0x60, 0x00, 0x02, 0x7f, 0x7f, // type sec
+            0x03, 0x02, 0x01, 0x00, // func sec
+            0x05, 0x04, 0x01, 0x08, 0x10,
+            0x00, // mem sec: min 16, page 2^0
+            0x07, 0x08, 0x01, 0x04, 0x6d, 0x61, 0x69, 0x6e, 0x00,
+            0x00, // export sec
+            0x0a, 0x0a, 0x01, 0x08, 0x00, 0x41, 0x04, 0x40, 0x00, 0x3f, 0x00,
+            0x0b, // code sec
+        ];
+
+        #[test]
+        fn test_custom_page_size() {
+            let module = WasmReader::decode_bytes(ONE_BYTE_PAGE_WASM).unwrap();
+            assert_eq!(module.mem_sec[0].page_size_log2, Some(0));
+            assert_eq!(module.mem_sec[0].page_size(), 1);
+            let mut vm = VM::new(&module);
+            let ret = vm.invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(16), WasmValue::I32(20)]);
+            assert_eq!(vm.memory.data.len(), 20);
+            // 默认页大小不变
+            assert_eq!(MemType::default().page_size(), PAGE_SIZE);
+        }
+
+        #[test]
+        fn test_reserve_max_memory() {
+            let mut module = main_module(
+                vec![Limits {
+                    min: 1,
+                    max: Some(4),
+                    ..Default::default()
+                }],
+                vec![
+                    instr(OpCode::MemorySize, Some(Rc::new(0u8))),
+                    i32_const(2),
+                    instr(OpCode::MemoryGrow, Some(Rc::new(0u8))),
+                    instr(OpCode::Drop, None),
+                    instr(OpCode::MemorySize, Some(Rc::new(0u8))),
+                ],
+            );
+            module.type_sec[0].result_types = vec![ValType::I32, ValType::I32];
+            let mut vm = VM::new(&module).reserve_max_memory(true);
+            vm.instantiate().unwrap();
+            let capacity = vm.memory.data.capacity();
+            let ptr = vm.memory.data.as_ptr();
+            assert!(capacity >= 4 * PAGE_SIZE);
+            let ret = vm.invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(1), WasmValue::I32(3)]);
+            assert_eq!(vm.memory.data.capacity(), capacity);
+            assert_eq!(vm.memory.data.as_ptr(), ptr);
+        }
+
+        fn host_call_module() -> Module {
+            // main 调用导入的 host.get 并返回其结果
+            let i32_result = FuncType {
+                params_types: vec![],
+                result_types: vec![ValType::I32],
+            };
+            Module {
+                type_sec: vec![i32_result],
+                import_sec: vec![Import {
+                    module_name: "host".to_string(),
+                    member_name: "get".to_string(),
+                    desc: ImportDesc::Func(0),
+                }],
+                func_sec: vec![0],
+                export_sec: vec![Export {
+                    name: "main".to_string(),
+                    desc: ExportDesc::Func(1),
+                }],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![idx_instr(OpCode::Call, 0)],
+                }],
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn test_host_func() {
+            let module = host_call_module();
+            let mut vm = VM::new(&module);
+            vm.register_host_func("host", "get", |_| vec![Box::new(7i32)]);
+            let ret = vm.invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(7)]);
+        }
+
+        #[test]
+        fn test_host_func_multi_results() {
+            // 宿主函数返回 (i32, f32)，每个结果按自己的类型入栈
+            let mut module = host_call_module();
+            module.type_sec[0].result_types = vec![ValType::I32, ValType::F32];
+            let mut vm = VM::new(&module);
+            vm.register_host_func("host", "get", |_| {
+                vec![Box::new(3i32), Box::new(1.5f32)]
+            });
+            let ret = vm.invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(3), WasmValue::F32(1.5)]);
+        }
+
+        #[test]
+        fn test_replay_host_calls() {
+            // 每次调用返回不同值的宿主函数
+            fn rand(_: Vec<WasmVal>) -> Vec<WasmVal> {
+                static SEED: AtomicI32 = AtomicI32::new(1);
+                vec![Box::new(SEED.fetch_add(1, Ordering::SeqCst))]
+            }
+            let module = host_call_module();
+            let mut vm = VM::new(&module);
+            vm.register_host_func("host", "get", rand);
+            vm.record_host_calls();
+            let recorded = vm.invoke("main", &[]).unwrap();
+            let calls = vm.host_calls().to_vec();
+            assert_eq!(calls.len(), 1);
+            assert_eq!(calls[0].member_name, "get");
+            assert_eq!(calls[0].results, recorded);
+
+            let mut vm = VM::new(&module);
+            vm.register_host_func("host", "get", rand);
+            vm.replay_host_calls(calls.clone());
+            assert_eq!(vm.invoke("main", &[]).unwrap(), recorded);
+            // 记录已用完，再次调用与记录不一致
+            let ret = vm.invoke("main", &[]);
+            assert!(matches!(ret, Err(Error::Trap(Trap::ReplayDivergence))));
+            // 记录的结果个数或类型与签名不符
+            for results in [vec![], vec![WasmValue::F32(1.0)]] {
+                let mut call = calls[0].clone();
+                call.results = results;
+                let mut vm = VM::new(&module);
+                vm.register_host_func("host", "get", rand);
+                vm.replay_host_calls(vec![call]);
+                let ret = vm.invoke("main", &[]);
+                assert!(matches!(
+                    ret,
+                    Err(Error::Trap(Trap::ReplayDivergence))
+                ));
+            }
+        }
+
+        #[test]
+        fn test_register_host_module() {
+            fn add(args: Vec<WasmVal>) -> Vec<WasmVal> {
+                let a = args[0].downcast_ref::<i32>().unwrap();
+                let b = args[1].downcast_ref::<i32>().unwrap();
+                vec![Box::new(a + b)]
+            }
+            fn neg(args: Vec<WasmVal>) -> Vec<WasmVal> {
+                vec![Box::new(-args[0].downcast_ref::<i32>().unwrap())]
+            }
+            fn square(args: Vec<WasmVal>) -> Vec<WasmVal> {
+                let a = args[0].downcast_ref::<i32>().unwrap();
+                vec![Box::new(a * a)]
+            }
+            // 导入 math 的三个函数，导出函数 i 调用导入函数 i
+            let binary = FuncType {
+                params_types: vec![ValType::I32, ValType::I32],
+                result_types: vec![ValType::I32],
+            };
+            let unary = FuncType {
+                params_types: vec![ValType::I32],
+                result_types: vec![ValType::I32],
+            };
+            let names = ["add", "neg", "square"];
+            let import = |name: &str, type_idx| Import {
+                module_name: "math".to_string(),
+                member_name: name.to_string(),
+                desc: ImportDesc::Func(type_idx),
+            };
+            let call = |args: Vec<i32>, func_idx| {
+                let mut expr: Vec<_> =
+                    args.into_iter().map(i32_const).collect();
+                expr.push(idx_instr(OpCode::Call, func_idx));
+                Code {
+                    locals: vec![],
+                    expr,
+                }
+            };
+            let module = Module {
+                type_sec: vec![
+                    binary,
+                    unary,
+                    FuncType {
+                        params_types: vec![],
+                        result_types: vec![ValType::I32],
+                    },
+                ],
+                import_sec: vec![
+                    import(names[0], 0),
+                    import(names[1], 1),
+                    import(names[2], 1),
+                ],
+                func_sec: vec![2, 2, 2],
+                export_sec: names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, name)| Export {
+                        name: name.to_string(),
+                        desc: ExportDesc::Func(3 + i as u32),
+                    })
+                    .collect(),
+                code_sec: vec![
+                    call(vec![2, 3], 0),
+                    call(vec![4], 1),
+                    call(vec![5], 2),
+                ],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            vm.register_host_module(
+                "math",
+                [("add", add as NativeFunc), ("neg", neg), ("square", square)],
+            );
+            for (name, expected) in names.iter().zip([5, -4, 25]) {
+                let ret = vm.invoke(name, &[]).unwrap();
+                assert_eq!(ret, vec![WasmValue::I32(expected)]);
+            }
+        }
+
+        #[test]
+        fn test_import_type_idx_out_of_range() {
+            let mut module = host_call_module();
+            module.type_sec = vec![FuncType::default(); 3];
+            module.import_sec[0].desc = ImportDesc::Func(50);
+            let mut vm = VM::new(&module);
+            vm.register_host_func("host", "get", |_| vec![]);
+            match vm.instantiate() {
+                Err(Error::Link(msg)) => {
+                    assert!(msg.contains("host.get"));
+                    assert!(msg.contains("type index 50"));
+                }
+                _ => panic!("expected link error"),
+            }
+        }
+
+        #[test]
+        fn test_unresolved_import() {
+            // 既没有注册也不是 env 内置函数的导入
+            let module = host_call_module();
+            match VM::new(&module).instantiate() {
+                Err(Error::Link(msg)) => {
+                    assert_eq!(msg, "unresolved import host.get")
+                }
+                _ => panic!("expected link error"),
+            }
+            // env 中不存在的内置函数
+            let mut module = host_call_module();
+            module.import_sec[0].module_name = "env".to_string();
+            match VM::new(&module).instantiate() {
+                Err(Error::Link(msg)) => {
+                    assert_eq!(msg, "unresolved import env.get")
+                }
+                _ => panic!("expected link error"),
+            }
+        }
+
+        #[test]
+        fn test_host_func_signature_mismatch() {
+            let module = host_call_module();
+            let i64_result = FuncType {
+                params_types: vec![],
+                result_types: vec![ValType::I64],
+            };
+            let mut vm = VM::new(&module);
+            vm.register_host_func_with_type("host", "get", i64_result, |_| {
+                vec![Box::new(7i64)]
+            });
+            match vm.instantiate() {
+                Err(Error::Link(msg)) => {
+                    assert!(msg.contains("host.get"), "{}", msg);
+                    assert!(msg.contains("i64"), "{}", msg);
+                }
+                _ => panic!("expected link error"),
+            }
+
+            // 签名一致时正常链接
+            let i32_result = module.type_sec[0].clone();
+            let mut vm = VM::new(&module);
+            vm.register_host_func_with_type("host", "get", i32_result, |_| {
+                vec![Box::new(7i32)]
+            });
+            assert_eq!(
+                vm.invoke("main", &[]).unwrap(),
+                vec![WasmValue::I32(7)]
+            );
+        }
+
+        #[test]
+        fn test_reexport_imports() {
+            static LOGGED: AtomicI32 = AtomicI32::new(0);
+            fn log(args: Vec<WasmVal>) -> Vec<WasmVal> {
+                let val = args[0].downcast_ref::<i32>().unwrap();
+                LOGGED.store(*val, Ordering::SeqCst);
+                vec![]
+            }
+            // 导入 env.log 和 env.memory，原样导出，模块本身没有定义任何函数
+            let module = Module {
+                type_sec: vec![FuncType {
+                    params_types: vec![ValType::I32],
+                    result_types: vec![],
+                }],
+                import_sec: vec![
+                    Import {
+                        module_name: "env".to_string(),
+                        member_name: "log".to_string(),
+                        desc: ImportDesc::Func(0),
+                    },
+                    Import {
+                        module_name: "env".to_string(),
+                        member_name: "memory".to_string(),
+                        desc: ImportDesc::Mem(Limits {
+                            min: 1,
+                            ..Default::default()
+                        }),
+                    },
+                ],
+                export_sec: vec![
+                    Export {
+                        name: "log".to_string(),
+                        desc: ExportDesc::Func(0),
+                    },
+                    Export {
+                        name: "memory".to_string(),
+                        desc: ExportDesc::Mem(0),
+                    },
+                ],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            vm.register_host_func("env", "log", log);
+            let ret = vm.invoke("log", &[WasmValue::I32(42)]).unwrap();
+            assert!(ret.is_empty());
+            assert_eq!(LOGGED.load(Ordering::SeqCst), 42);
+            vm.write_memory(Some("memory"), 100, &[1, 2]).unwrap();
+            let mut buf = [0u8; 2];
+            vm.read_memory(Some("memory"), 100, &mut buf).unwrap();
+            assert_eq!(buf, [1, 2]);
+        }
+
+        #[test]
+        fn test_host_func_result_mismatch() {
+            let module = host_call_module();
+            let mut vm = VM::new(&module);
+            vm.register_host_func("host", "get", |_| vec![]);
+            let ret = vm.invoke("main", &[]);
+            assert!(matches!(ret, Err(Error::Trap(Trap::HostResultMismatch))));
+            assert_eq!(vm.operand_stack.length(), 0);
+        }
+
+        #[test]
+        fn test_ref_null() {
+            let mut module = main_module(
+                vec![],
+                vec![instr(OpCode::RefNull, Some(Rc::new(HeapType::Func)))],
+            );
+            module.type_sec[0].result_types.push(ValType::FuncRef);
+            let ret = VM::new(&module).invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::FuncRef(None)]);
+        }
+
+        #[test]
+        fn test_gc_unsupported() {
+            let gc_args = GcArgs {
+                sub_opcode: STRUCT_NEW_DEFAULT,
+                type_idx: 0,
+            };
+            let module = main_module(
+                vec![],
+                vec![
+                    instr(OpCode::Gc, Some(Rc::new(gc_args))),
+                    instr(OpCode::Drop, None),
+                ],
+            );
+            let ret = VM::new(&module).invoke("main", &[]);
+            assert!(matches!(
+                ret,
+                Err(Error::Trap(Trap::UnsupportedInstruction))
+            ));
+        }
+
+        #[test]
+        fn test_total_instruction_budget() {
+            // (func (export "f") (result i32) i32.const 1 i32.const 2 i32.add)
+            let module = Module {
+                type_sec: vec![FuncType {
+                    params_types: vec![],
+                    result_types: vec![ValType::I32],
+                }],
+                func_sec: vec![0],
+                export_sec: vec![Export {
+                    name: "f".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![
+                        i32_const(1),
+                        i32_const(2),
+                        instr(OpCode::I32Add, None),
+                    ],
+                }],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            // 每次调用执行 3 条指令，预算只够两次完整调用
+            vm.set_total_instruction_budget(7);
+            for _ in 0..2 {
+                assert_eq!(
+                    vm.invoke("f", &[]).unwrap(),
+                    vec![WasmValue::I32(3)]
+                );
+            }
+            for _ in 0..2 {
+                let ret = vm.invoke("f", &[]);
+                assert!(matches!(ret, Err(Error::Trap(Trap::OutOfFuel))));
+            }
+        }
+
+        #[test]
+        fn test_extended_const_global_init() {
+            // (global i32 (i32.add (i32.const 10) (i32.const 5)))
+            let module = Module {
+                type_sec: vec![FuncType {
+                    params_types: vec![],
+                    result_types: vec![ValType::I32],
+                }],
+                func_sec: vec![0],
+                global_sec: vec![Global {
+                    global_type: GlobalType {
+                        val_type: ValType::I32,
+                        mutable: false,
+                    },
+                    init_expr: vec![
+                        i32_const(10),
+                        i32_const(5),
+                        instr(OpCode::I32Add, None),
+                    ],
+                }],
+                export_sec: vec![Export {
+                    name: "g".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![idx_instr(OpCode::GlobalGet, 0)],
+                }],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            assert_eq!(vm.invoke("g", &[]).unwrap(), vec![WasmValue::I32(15)]);
+        }
+
+        #[test]
+        fn test_integer_divide_by_zero() {
+            let cases = [
+                (i32_const(1), i32_const(0), OpCode::I32DivS),
+                (i32_const(1), i32_const(0), OpCode::I32RemU),
+                (i64_const(1), i64_const(0), OpCode::I64DivU),
+                (i64_const(1), i64_const(0), OpCode::I64RemS),
+            ];
+            for (v1, v2, opcode) in cases {
+                let module = main_module(
+                    vec![],
+                    vec![
+                        v1,
+                        v2,
+                        instr(opcode, None),
+                        instr(OpCode::Drop, None),
+                    ],
+                );
+                let ret = VM::new(&module).run_main();
+                assert!(matches!(
+                    ret,
+                    Err(Error::Trap(Trap::IntegerDivideByZero))
+                ));
+            }
+        }
+
+        #[test]
+        fn test_post_mortem() {
+            // main 调用 func 1，func 1 在写入 1 页内存之外时陷入
+            let mut module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![
+                    i32_const(8),
+                    i32_const(0x55),
+                    mem_instr(OpCode::I32Store8, 0),
+                    idx_instr(OpCode::Call, 1),
+                ],
+            );
+            module.func_sec.push(0);
+            module.code_sec.push(Code {
+                locals: vec![],
+                expr: vec![
+                    i32_const(7),
+                    i32_const(65536),
+                    i32_const(1),
+                    mem_instr(OpCode::I32Store, 0),
+                ],
+            });
+            let mut vm = VM::new(&module);
+            let ret = vm.invoke("main", &[]);
+            assert!(matches!(ret, Err(Error::Trap(Trap::MemoryOutOfBounds))));
+            let post_mortem = vm.post_mortem();
+            assert_eq!(post_mortem.trap, Some(Trap::MemoryOutOfBounds));
+            assert_eq!(post_mortem.faulting_func(), Some(1));
+            assert_eq!(post_mortem.frames[0].func_idx, 0);
+            assert_eq!(post_mortem.frames[1].pc, 4);
+            assert_eq!(post_mortem.operand_stack, vec![7]);
+            assert!(post_mortem.memory_dump().starts_with("00000000: 00"));
+            assert!(post_mortem.memory_dump().contains(" 55"));
+        }
+
+        #[test]
+        fn test_display_trap_with_module() {
+            // main 调用 func 1，func 1 越界读取；name 段将 func 1 命名为 load_at
+            let mut module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![idx_instr(OpCode::Call, 1), instr(OpCode::Drop, None)],
+            );
+            module.type_sec.push(FuncType {
+                params_types: vec![],
+                result_types: vec![ValType::I32],
+            });
+            module.func_sec.push(1);
+            module.code_sec.push(Code {
+                locals: vec![],
+                expr: vec![i32_const(65536), mem_instr(OpCode::I32Load, 4)],
+            });
+            let mut names = vec![0x01, 0x01];
+            names.push(b"load_at".len() as u8);
+            names.extend(b"load_at");
+            let mut bytes = vec![0x01, names.len() as u8];
+            bytes.extend(names);
+            module.custom_sec.push(CustomSec {
+                name: "name".to_string(),
+                bytes,
+            });
+            let mut vm = VM::new(&module);
+            assert!(vm.invoke("main", &[]).is_err());
+            let post_mortem = vm.post_mortem();
+            let msg = post_mortem.display_with_module(&module);
+            let lines: Vec<&str> = msg.lines().collect();
+            assert_eq!(lines[0], "trap: out of bounds memory access");
+            assert_eq!(lines[1], "  at func[1] <load_at>");
+            // 陷入的指令及其之前的指令，与 --dump 的反汇编一致
+            assert_eq!(lines[2], "       I32Const 65536");
+            assert_eq!(lines[3], "    -> I32Load offset=4 align=1");
+            assert_eq!(lines[4], "backtrace:");
+            assert_eq!(lines[5], "  0: func[1] <load_at>");
+            assert_eq!(lines[6], "  1: func[0] <main>");
+            let trap = post_mortem.trap.unwrap();
+            assert_eq!(trap.display_with_module(&module, &post_mortem), msg);
+        }
+
+        #[test]
+        fn test_shift_sign() {
+            let cases = [
+                (i32_const(-8), i32_const(1), OpCode::I32ShrS, -4i64),
+                (i32_const(-8), i32_const(1), OpCode::I32ShrU, 0x7fff_fffc),
+                (i32_const(-8), i32_const(33), OpCode::I32ShrS, -4),
+                (i64_const(-8), i64_const(1), OpCode::I64ShrS, -4),
+                (
+                    i64_const(-8),
+                    i64_const(1),
+                    OpCode::I64ShrU,
+                    0x7fff_ffff_ffff_fffc,
+                ),
+            ];
+            for (v1, v2, opcode, expected) in cases {
+                let is_i64 = opcode >= OpCode::I64Clz;
+                let mut module =
+                    main_module(vec![], vec![v1, v2, instr(opcode, None)]);
+                module.type_sec[0].result_types =
+                    vec![if is_i64 { ValType::I64 } else { ValType::I32 }];
+                let ret = VM::new(&module).invoke("main", &[]).unwrap();
+                let expected = if is_i64 {
+                    WasmValue::I64(expected)
+                } else {
+                    WasmValue::I32(expected as i32)
+                };
+                assert_eq!(ret, vec![expected]);
+            }
+        }
+
+        #[test]
+        fn test_build_instructions() {
+            let mut module = main_module(
+                vec![],
+                vec![
+                    Instruction::with_i32_const(2),
+                    Instruction::with_i32_const(3),
+                    Instruction::new(OpCode::I32Add),
+                ],
+            );
+            module.type_sec[0].result_types = vec![ValType::I32];
+            let ret = VM::new(&module).invoke("main", &[]).unwrap();
+            assert_eq!(ret, vec![WasmValue::I32(5)]);
+        }
+
+        #[test]
+        fn test_freeze_memory() {
+            // main 读取地址 0，store 向地址 0 写入 1
+            let mut module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![
+                    i32_const(0),
+                    mem_instr(OpCode::I32Load, 0),
+                    instr(OpCode::Drop, None),
+                ],
+            );
+            module.func_sec.push(0);
+            module.code_sec.push(Code {
+                locals: vec![],
+                expr: vec![
+                    i32_const(0),
+                    i32_const(1),
+                    mem_instr(OpCode::I32Store, 0),
+                ],
+            });
+            module.export_sec.push(Export {
+                name: "store".to_string(),
+                desc: ExportDesc::Func(1),
+            });
+            let mut vm = VM::new(&module);
+            vm.freeze_memory();
+            assert!(vm.invoke("main", &[]).is_ok());
+            let ret = vm.invoke("store", &[]);
+            assert!(matches!(ret, Err(Error::Trap(Trap::MemoryReadOnly))));
+            assert_eq!(vm.memory.grow(1), 0xFFFFFFFF);
+
+            vm.unfreeze_memory();
+            assert!(vm.invoke("store", &[]).is_ok());
+            assert_eq!(vm.memory.data[0], 1);
+        }
+
+        #[test]
+        fn test_float_locals() {
+            let cases = [
+                (
+                    ValType::F32,
+                    instr(OpCode::F32Const, Some(Rc::new(1.1f32))),
+                    WasmValue::F32(1.1),
+                ),
+                (
+                    ValType::F64,
+                    instr(OpCode::F64Const, Some(Rc::new(1.1f64))),
+                    WasmValue::F64(1.1),
+                ),
+            ];
+            for (val_type, constant, expected) in cases {
+                let mut module = main_module(
+                    vec![],
+                    vec![
+                        constant,
+                        idx_instr(OpCode::LocalSet, 0),
+                        idx_instr(OpCode::LocalGet, 0),
+                    ],
+                );
+                module.type_sec[0].result_types = vec![val_type];
+                module.code_sec[0].locals = vec![Locals { n: 1, val_type }];
+                let ret = VM::new(&module).invoke("main", &[]).unwrap();
+                assert_eq!(ret, vec![expected]);
+            }
+        }
+
+        #[test]
+        fn test_wrap_extend_round_trip() {
+            // (i64 常量, wrap 的结果, extend_s 的结果)
+            let cases = [
+                (0xFFFF_FFFF_0000_0001u64 as i64, 1, 1),
+                (-1, -1, -1),
+                (0x8000_0000, i32::MIN, i32::MIN as i64),
+            ];
+            for (val, wrapped, extended) in cases {
+                let mut module = main_module(
+                    vec![],
+                    vec![
+                        i64_const(val),
+                        instr(OpCode::I32WrapI64, None),
+                        idx_instr(OpCode::LocalTee, 0),
+                        idx_instr(OpCode::LocalGet, 0),
+                        instr(OpCode::I64ExtendI32S, None),
+                    ],
+                );
+                module.type_sec[0].result_types =
+                    vec![ValType::I32, ValType::I64];
+                module.code_sec[0].locals = vec![Locals {
+                    n: 1,
+                    val_type: ValType::I32,
+                }];
+                let ret = VM::new(&module).invoke("main", &[]).unwrap();
+                assert_eq!(
+                    ret,
+                    vec![WasmValue::I32(wrapped), WasmValue::I64(extended)]
+                );
+            }
+        }
+
+        #[test]
+        fn test_vm_config() {
+            // 返回 0.0/0.0 的位模式和 7/0
+            let mut module = main_module(
+                vec![],
+                vec![
+                    instr(OpCode::F32Const, Some(Rc::new(0.0f32))),
+                    instr(OpCode::F32Const, Some(Rc::new(0.0f32))),
+                    instr(OpCode::F32Div, None),
+                    instr(OpCode::I32ReinterpretF32, None),
+                    i32_const(7),
+                    i32_const(0),
+                    instr(OpCode::I32DivS, None),
+                ],
+            );
+            module.type_sec[0].result_types = vec![ValType::I32, ValType::I32];
+            let config = VmConfig {
+                canonicalize_nans: true,
+                saturating_arithmetic: true,
+                ..Default::default()
+            };
+            let mut vm = VM::with_config(&module, config);
+            assert_eq!(vm.config(), config);
+            let ret = vm.invoke("main", &[]).unwrap();
+            assert_eq!(
+                ret,
+                vec![WasmValue::I32(0x7fc0_0000), WasmValue::I32(i32::MAX)]
+            );
+
+            let mut vm = VM::new(&module);
+            assert_eq!(vm.config(), VmConfig::default());
+            let ret = vm.invoke("main", &[]);
+            assert!(matches!(ret, Err(Error::Trap(Trap::IntegerDivideByZero))));
+        }
+
+        #[test]
+        fn test_trap_restores_stack() {
+            let module = main_module(
+                vec![Limits {
+                    min: 1,
+                    ..Default::default()
+                }],
+                vec![
+                    i32_const(65536),
+                    mem_instr(OpCode::I32Load, 0),
+                    instr(OpCode::Drop, None),
+                ],
+            );
+            let mut vm = VM::new(&module);
+            vm.instantiate().unwrap();
+            for _ in 0..2 {
+                let ret = vm.invoke("main", &[]);
+                assert!(matches!(
+                    ret,
+                    Err(Error::Trap(Trap::MemoryOutOfBounds))
+                ));
+                assert_eq!(vm.control_stack.control_depth(), 0);
+                assert_eq!(vm.operand_stack.length(), 0);
+            }
+        }
+
+        // (func $fact (param i64 i64) (result i64)
+        //   (if (i64.eqz (local.get 0)) (then (return (local.get 1))))
+        //   (call $fact (i64.sub (local.get 0) (i64.const 1))
+        //               (i64.mul (local.get 0) (local.get 1))))
+        fn fact_acc_module() -> Module {
+            let if_args = IfArgs {
+                block_type: BLOCK_TYPE_EMPTY,
+                instructions_1: vec![
+                    idx_instr(OpCode::LocalGet, 1),
+                    instr(OpCode::Return, None),
+                ],
+                instructions_2: vec![],
+            };
+            Module {
+                type_sec: vec![FuncType {
+                    params_types: vec![ValType::I64, ValType::I64],
+                    result_types: vec![ValType::I64],
+                }],
+                func_sec: vec![0],
+                export_sec: vec![Export {
+                    name: "fact".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![
+                        idx_instr(OpCode::LocalGet, 0),
+                        instr(OpCode::I64Eqz, None),
+                        instr(OpCode::If, Some(Rc::new(if_args))),
+                        idx_instr(OpCode::LocalGet, 0),
+                        i64_const(1),
+                        instr(OpCode::I64Sub, None),
+                        idx_instr(OpCode::LocalGet, 0),
+                        idx_instr(OpCode::LocalGet, 1),
+                        instr(OpCode::I64Mul, None),
+                        idx_instr(OpCode::Call, 0),
+                    ],
+                }],
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn test_optimize_tail_calls() {
+            let module = fact_acc_module();
+            let args = [WasmValue::I64(20), WasmValue::I64(1)];
+            let fact_20 = WasmValue::I64(2_432_902_008_176_640_000);
+            // 普通调用每层递归占用一个控制帧
+            let mut vm = VM::new(&module);
+            assert_eq!(vm.invoke("fact", &args).unwrap(), vec![fact_20]);
+            assert!(vm.run_stats().peak_control_stack > 20);
+
+            let mut vm = VM::new(&module).optimize_tail_calls(true);
+            assert_eq!(vm.invoke("fact", &args).unwrap(), vec![fact_20]);
+            assert!(vm.run_stats().peak_control_stack <= 2);
+            // 深度递归时控制栈和操作数栈都不增长
+            let args = [WasmValue::I64(100_000), WasmValue::I64(1)];
+            assert_eq!(
+                vm.invoke("fact", &args).unwrap(),
+                vec![WasmValue::I64(0)]
+            );
+            assert!(vm.run_stats().peak_control_stack <= 2);
+            assert!(vm.run_stats().peak_operand_stack <= 8);
+            assert_eq!(vm.control_stack.control_depth(), 0);
+            assert_eq!(vm.operand_stack.length(), 0);
+        }
+
+        #[test]
+        fn test_tail_call_with_extra_operands() {
+            // func 0 (result i32 i32) = i32.const 1; call 1，
+            // call 1 之下还有其他操作数，不能按尾调用处理
+            let mut module = main_module(
+                vec![],
+                vec![i32_const(1), idx_instr(OpCode::Call, 1)],
+            );
+            module.type_sec[0].result_types = vec![ValType::I32, ValType::I32];
+            module.type_sec.push(FuncType {
+                params_types: vec![],
+                result_types: vec![ValType::I32],
+            });
+            module.func_sec.push(1);
+            module.code_sec.push(Code {
+                locals: vec![],
+                expr: vec![i32_const(2)],
+            });
+            let expected = vec![WasmValue::I32(1), WasmValue::I32(2)];
+            let mut vm = VM::new(&module).optimize_tail_calls(true);
+            assert_eq!(vm.invoke("main", &[]).unwrap(), expected);
+            assert_eq!(vm.operand_stack.length(), 0);
+        }
+
+        #[test]
+        fn test_breakpoint_in_loop() {
+            // (func (param $n i32) (result i32) (local $sum i32)
+            //   (loop
+            //     (local.set $sum (i32.add (local.get $sum) (local.get $n)))
+            //     (br_if 0 (local.tee $n (i32.sub (local.get $n) (i32.const 1)))))
+            //   (local.get $sum))
+            let loop_args = BlockArgs {
+                block_type: BLOCK_TYPE_EMPTY,
+                instructions: vec![
+                    idx_instr(OpCode::LocalGet, 1),
+                    idx_instr(OpCode::LocalGet, 0),
+                    instr(OpCode::I32Add, None),
+                    idx_instr(OpCode::LocalSet, 1),
+                    idx_instr(OpCode::LocalGet, 0),
+                    i32_const(1),
+                    instr(OpCode::I32Sub, None),
+                    idx_instr(OpCode::LocalTee, 0),
+                    idx_instr(OpCode::BrIf, 0),
+                ],
+            };
+            let module = Module {
+                type_sec: vec![FuncType {
+                    params_types: vec![ValType::I32],
+                    result_types: vec![ValType::I32],
+                }],
+                func_sec: vec![0],
+                export_sec: vec![Export {
+                    name: "sum".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                code_sec: vec![Code {
+                    locals: vec![Locals {
+                        n: 1,
+                        val_type: ValType::I32,
+                    }],
+                    expr: vec![
+                        instr(OpCode::Loop, Some(Rc::new(loop_args))),
+                        idx_instr(OpCode::LocalGet, 1),
+                    ],
+                }],
+                ..Default::default()
+            };
+            let mut vm = VM::new(&module);
+            // 展开后 0 为 loop，5 为累加之后的 local.get $n
+            vm.set_breakpoint(0, 5);
+            let hit = StepResult::BreakpointHit { func_idx: 0, pc: 5 };
+            let ret = vm.invoke_debug("sum", &[WasmValue::I32(3)]).unwrap();
+            assert_eq!(ret, hit);
+            assert_eq!(vm.local(0), Some(WasmValue::I32(3)));
+            assert_eq!(vm.local(1), Some(WasmValue::I32(3)));
+            // 暂停期间不能开始新的调试调用，暂停的调用不受影响
+            let ret = vm.invoke_debug("sum", &[WasmValue::I32(1)]);
+            assert!(matches!(ret, Err(Error::Debugger(_))));
+            assert_eq!(vm.resume().unwrap(), hit);
+            assert_eq!(vm.local(0), Some(WasmValue::I32(2)));
+            assert_eq!(vm.local(1), Some(WasmValue::I32(5)));
+            assert_eq!(vm.resume().unwrap(), hit);
+            assert_eq!(vm.local(1), Some(WasmValue::I32(6)));
+            assert_eq!(
+                vm.resume().unwrap(),
+                StepResult::Done(vec![WasmValue::I32(6)])
+            );
+            assert_eq!(vm.control_stack.control_depth(), 0);
+            assert_eq!(vm.operand_stack.length(), 0);
+            assert!(matches!(vm.resume(), Err(Error::Debugger(_))));
+            // 普通调用不检查断点
+            assert_eq!(
+                vm.invoke("sum", &[WasmValue::I32(4)]).unwrap(),
+                vec![WasmValue::I32(10)]
+            );
+        }
     }
 }
@@ -1,2 +1,18 @@
 pub mod interpreter;
+pub mod post_mortem;
+pub mod trap;
+pub mod value;
+pub use interpreter::interpreter::HostCall;
+pub use interpreter::interpreter::MemoryWrite;
+pub use interpreter::interpreter::MockFunc;
+pub use interpreter::interpreter::NativeFunc;
+pub use interpreter::interpreter::OobMode;
+pub use interpreter::interpreter::RunStats;
+pub use interpreter::interpreter::StepResult;
 pub use interpreter::interpreter::VM;
+pub use interpreter::interpreter::VmConfig;
+pub use interpreter::interpreter::WriteSink;
+pub use post_mortem::post_mortem::{FrameInfo, PostMortem};
+pub use trap::trap::Trap;
+pub use value::value::WasmValue;
+pub use value::value::NULL_REF;
@@ -0,0 +1,160 @@
+pub mod post_mortem {
+    use crate::dumper::instr_text;
+    use crate::interpreter::Trap;
+    use crate::module::{FuncIdx, Instruction, Module};
+    use std::fmt;
+    use std::fmt::Write;
+
+    /// 控制栈中的一帧，pc 为下一条指令在所属块中的位置
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct FrameInfo {
+        pub func_idx: FuncIdx,
+        pub pc: usize,
+        pub call: bool, // 是否为函数调用帧，其余为 block、loop、if 的帧
+    }
+
+    /// 解释器状态的快照，陷入时自动保存，用于离线分析
+    #[derive(Debug, Clone, Default)]
+    pub struct PostMortem {
+        pub trap: Option<Trap>,
+        pub operand_stack: Vec<u64>,
+        pub frames: Vec<FrameInfo>, // 从最外层到最内层
+        pub block: Vec<Instruction>, // 陷入时所在块的指令，最内层帧的 pc - 1 为陷入的指令
+        pub globals: Vec<u64>,
+        pub memory: Vec<u8>, // 线性内存的原始字节，需要时再格式化
+    }
+
+    impl PostMortem {
+        /// 线性内存的十六进制转储
+        pub fn memory_dump(&self) -> String {
+            hexdump(&self.memory)
+        }
+
+        /// 发生陷入时所在的函数
+        pub fn faulting_func(&self) -> Option<FuncIdx> {
+            self.frames.last().map(|frame| frame.func_idx)
+        }
+
+        /// 陷入的指令在 block 中的位置
+        pub fn faulting_pc(&self) -> Option<usize> {
+            let pc = self.frames.last()?.pc.checked_sub(1)?;
+            (pc < self.block.len()).then_some(pc)
+        }
+
+        /// 类似 Rust 的 panic 信息：陷入类型、出错的函数、陷入的指令及其前后几条指令，
+        /// 以及函数调用栈。函数名取自 name 段或导出名，指令与 --dump 的反汇编一致
+        pub fn display_with_module(&self, module: &Module) -> String {
+            self.render(self.trap, module)
+        }
+
+        pub(crate) fn render(
+            &self,
+            trap: Option<Trap>,
+            module: &Module,
+        ) -> String {
+            let func_label = |idx: FuncIdx| match module.func_name(idx) {
+                Some(name) => format!("func[{}] <{}>", idx, name),
+                None => format!("func[{}]", idx),
+            };
+            let mut out = String::new();
+            match trap {
+                Some(trap) => writeln!(out, "trap: {}", trap).unwrap(),
+                None => writeln!(out, "trap: none").unwrap(),
+            }
+            if let Some(func_idx) = self.faulting_func() {
+                writeln!(out, "  at {}", func_label(func_idx)).unwrap();
+            }
+            if let Some(pc) = self.faulting_pc() {
+                let start = pc.saturating_sub(CONTEXT_INSTRS);
+                let end = (pc + CONTEXT_INSTRS + 1).min(self.block.len());
+                for (i, instr) in self.block[start..end].iter().enumerate() {
+                    let marker = if start + i == pc { "->" } else { "  " };
+                    writeln!(
+                        out,
+                        "    {} {}",
+                        marker,
+                        instr_text(module, instr)
+                    )
+                    .unwrap();
+                }
+            }
+            writeln!(out, "backtrace:").unwrap();
+            let calls = self.frames.iter().rev().filter(|frame| frame.call);
+            for (i, frame) in calls.enumerate() {
+                writeln!(out, "  {}: {}", i, func_label(frame.func_idx))
+                    .unwrap();
+            }
+            out
+        }
+    }
+
+    // 陷入的指令前后各显示的指令条数
+    const CONTEXT_INSTRS: usize = 2;
+
+    impl fmt::Display for PostMortem {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self.trap {
+                Some(trap) => writeln!(f, "trap: {}", trap)?,
+                None => writeln!(f, "trap: none")?,
+            }
+            writeln!(f, "frames:")?;
+            for frame in self.frames.iter().rev() {
+                writeln!(f, "  func[{}] pc={}", frame.func_idx, frame.pc)?;
+            }
+            writeln!(f, "operand stack: {:?}", self.operand_stack)?;
+            writeln!(f, "globals: {:?}", self.globals)?;
+            writeln!(f, "memory:")?;
+            write!(f, "{}", self.memory_dump())
+        }
+    }
+
+    /// 每行 16 字节，与上一行相同的连续行折叠为一个 "*"，和 hexdump -C 一致
+    pub fn hexdump(data: &[u8]) -> String {
+        let mut out = String::new();
+        let mut prev: Option<&[u8]> = None;
+        let mut folded = false;
+        for (i, line) in data.chunks(16).enumerate() {
+            if prev == Some(line) {
+                if !folded {
+                    out.push_str("*\n");
+                    folded = true;
+                }
+                continue;
+            }
+            prev = Some(line);
+            folded = false;
+            write!(out, "{:08x}:", i * 16).unwrap();
+            for b in line {
+                write!(out, " {:02x}", b).unwrap();
+            }
+            out.push('\n');
+        }
+        if !data.is_empty() {
+            writeln!(out, "{:08x}", data.len()).unwrap();
+        }
+        out
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_hexdump() {
+            let mut data = vec![0u8; 64];
+            data[0] = 0xab;
+            data[63] = 0x01;
+            let dump = hexdump(&data);
+            let lines: Vec<&str> = dump.lines().collect();
+            assert_eq!(lines.len(), 5);
+            assert!(lines[0].starts_with("00000000: ab 00"));
+            assert!(lines[1].starts_with("00000010: 00 00"));
+            assert_eq!(lines[2], "*");
+            assert!(
+                lines[3].starts_with("00000030: 00")
+                    && lines[3].ends_with(" 01")
+            );
+            assert_eq!(lines[4], "00000040");
+        }
+    }
+}
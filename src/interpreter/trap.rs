@@ -0,0 +1,102 @@
+pub mod trap {
+    use crate::interpreter::PostMortem;
+    use crate::module::Module;
+    use std::fmt;
+
+    /// 执行过程中发生的陷入
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Trap {
+        Unreachable,
+        MemoryOutOfBounds,
+        UnalignedAccess,
+        UndefinedElement,
+        IndirectCallTypeMismatch,
+        UninitializedElement,
+        TableOutOfBounds,
+        HostResultMismatch,
+        UnsupportedInstruction,
+        OutOfFuel,
+        IntegerDivideByZero,
+        IntegerOverflow,
+        InvalidConversionToInteger,
+        ReplayDivergence,
+        MemoryReadOnly,
+        UninitializedRead,
+    }
+
+    impl Trap {
+        /// 按陷入时保存的 post_mortem 渲染类似 Rust panic 的信息，
+        /// 见 PostMortem::display_with_module
+        pub fn display_with_module(
+            &self,
+            module: &Module,
+            post_mortem: &PostMortem,
+        ) -> String {
+            post_mortem.render(Some(*self), module)
+        }
+
+        /// 将陷入映射为类似 POSIX 信号终止的退出码（128 + 信号值），便于脚本区分失败原因
+        pub fn exit_code(&self) -> i32 {
+            match self {
+                Trap::UnsupportedInstruction => 132, // SIGILL
+                Trap::UnalignedAccess => 135,        // SIGBUS
+                Trap::IntegerDivideByZero
+                | Trap::IntegerOverflow
+                | Trap::InvalidConversionToInteger => 136, // SIGFPE
+                Trap::MemoryOutOfBounds
+                | Trap::TableOutOfBounds
+                | Trap::MemoryReadOnly => 139, // SIGSEGV
+                Trap::OutOfFuel => 152,              // SIGXCPU
+                Trap::Unreachable
+                | Trap::UndefinedElement
+                | Trap::IndirectCallTypeMismatch
+                | Trap::UninitializedElement
+                | Trap::HostResultMismatch
+                | Trap::ReplayDivergence
+                | Trap::UninitializedRead => 134, // SIGABRT
+            }
+        }
+    }
+
+    impl fmt::Display for Trap {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let msg = match self {
+                Trap::Unreachable => "unreachable",
+                Trap::MemoryOutOfBounds => "out of bounds memory access",
+                Trap::UnalignedAccess => "unaligned memory access",
+                Trap::UndefinedElement => "undefined element",
+                Trap::IndirectCallTypeMismatch => "indirect call type mismatch",
+                Trap::UninitializedElement => "uninitialized element",
+                Trap::TableOutOfBounds => "out of bounds table access",
+                Trap::UnsupportedInstruction => "unsupported instruction",
+                Trap::OutOfFuel => "all fuel consumed",
+                Trap::IntegerDivideByZero => "integer divide by zero",
+                Trap::IntegerOverflow => "integer overflow",
+                Trap::InvalidConversionToInteger => {
+                    "invalid conversion to integer"
+                }
+                Trap::MemoryReadOnly => "write to read-only memory",
+                Trap::UninitializedRead => "read of uninitialized memory",
+                Trap::ReplayDivergence => "host call diverged from replay log",
+                Trap::HostResultMismatch => {
+                    "host function results do not match its signature"
+                }
+            };
+            write!(f, "{}", msg)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_exit_code() {
+            assert_eq!(Trap::Unreachable.exit_code(), 134);
+            assert_eq!(Trap::MemoryOutOfBounds.exit_code(), 139);
+            assert_eq!(Trap::IntegerDivideByZero.exit_code(), 136);
+            assert_eq!(Trap::UnalignedAccess.exit_code(), 135);
+            assert_eq!(Trap::OutOfFuel.exit_code(), 152);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+pub mod value {
+    use crate::module::{FuncIdx, ValType};
+
+    /// 空引用在操作数栈上的表示，函数索引只有 32 位，不会与之冲突
+    pub const NULL_REF: u64 = u64::MAX;
+
+    /// 调用导出函数时传入和返回的值
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum WasmValue {
+        I32(i32),
+        I64(i64),
+        F32(f32),
+        F64(f64),
+        FuncRef(Option<FuncIdx>), // None 表示空引用
+    }
+
+    impl WasmValue {
+        pub fn val_type(&self) -> ValType {
+            match self {
+                WasmValue::I32(_) => ValType::I32,
+                WasmValue::I64(_) => ValType::I64,
+                WasmValue::F32(_) => ValType::F32,
+                WasmValue::F64(_) => ValType::F64,
+                WasmValue::FuncRef(_) => ValType::FuncRef,
+            }
+        }
+
+        /// 转换为操作数栈上的表示
+        pub fn to_u64(&self) -> u64 {
+            match *self {
+                WasmValue::I32(v) => v as u32 as u64,
+                WasmValue::I64(v) => v as u64,
+                WasmValue::F32(v) => v.to_bits() as u64,
+                WasmValue::F64(v) => v.to_bits(),
+                WasmValue::FuncRef(Some(idx)) => idx as u64,
+                WasmValue::FuncRef(None) => NULL_REF,
+            }
+        }
+
+        /// 从操作数栈上的表示转换
+        pub fn from_u64(val_type: ValType, val: u64) -> WasmValue {
+            match val_type {
+                ValType::I32 => WasmValue::I32(val as i32),
+                ValType::I64 => WasmValue::I64(val as i64),
+                ValType::F32 => WasmValue::F32(f32::from_bits(val as u32)),
+                ValType::F64 => WasmValue::F64(f64::from_bits(val)),
+                ValType::FuncRef if val == NULL_REF => WasmValue::FuncRef(None),
+                ValType::FuncRef => WasmValue::FuncRef(Some(val as FuncIdx)),
+            }
+        }
+    }
+}
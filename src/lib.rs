@@ -0,0 +1,79 @@
+pub mod dumper;
+pub mod error;
+pub mod interpreter;
+pub mod module;
+pub mod validator;
+
+use error::Error;
+use interpreter::WasmValue;
+use std::path::Path;
+
+/// 解码、校验并实例化 wasm 文件，然后调用指定的导出函数
+pub fn run_file<P: AsRef<Path>>(
+    path: P,
+    export_name: &str,
+    args: &[WasmValue],
+) -> Result<Vec<WasmValue>, Error> {
+    let module = module::WasmReader::decode_file(path)?;
+    validator::Validator::validate(&module)?;
+    let mut vm = interpreter::VM::new(&module);
+    vm.instantiate()?;
+    vm.invoke(export_name, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // (func (export "add") (param i32 i32) (result i32)
+    //   local.get 0 local.get 1 i32.add)
+    const ADD_WASM: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic & version
+        0x01, 0x07, 0x01, 0x60, 0x02, 0x7f, 0x7f, 0x01, 0x7f, // type sec
+        0x03, 0x02, 0x01, 0x00, // func sec
+        0x07, 0x07, 0x01, 0x03, 0x61, 0x64, 0x64, 0x00, 0x00, // export sec
+        0x0a, 0x09, 0x01, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6a,
+        0x0b, // code sec
+    ];
+
+    fn write_temp_file(name: &str, data: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("rasm_{}_{}.wasm", name, std::process::id()));
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_run_file() {
+        let path = write_temp_file("add", ADD_WASM);
+        let ret =
+            run_file(&path, "add", &[WasmValue::I32(2), WasmValue::I32(3)]);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(ret.unwrap(), vec![WasmValue::I32(5)]);
+    }
+
+    #[test]
+    fn test_run_file_export_not_found() {
+        let path = write_temp_file("add_not_found", ADD_WASM);
+        let ret = run_file(&path, "sub", &[]);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(ret, Err(Error::ExportNotFound(name)) if name == "sub"));
+    }
+
+    #[test]
+    fn test_run_file_argument_mismatch() {
+        let path = write_temp_file("add_mismatch", ADD_WASM);
+        let ret = run_file(&path, "add", &[WasmValue::I64(2)]);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(ret, Err(Error::ArgumentMismatch(_))));
+    }
+
+    #[test]
+    fn test_run_file_decode_error() {
+        let path = write_temp_file("add_truncated", &ADD_WASM[..20]);
+        let ret = run_file(&path, "add", &[]);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(ret, Err(Error::Decode(_))));
+    }
+}
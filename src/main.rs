@@ -1,8 +1,12 @@
-mod dumper;
-mod module;
-mod interpreter;
-
 use clap::Parser;
+use rasm::{dumper, interpreter, module, validator};
+use std::io::{Read, Write};
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum Format {
+    Text,
+    Json,
+}
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -11,19 +15,74 @@ struct Args {
     #[clap(short, long, value_parser)]
     dump: bool,
 
-    /// The input wasm file
+    /// Output format of the dump
+    #[clap(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Trap on memory accesses that are not naturally aligned
+    #[clap(long, value_parser)]
+    strict_alignment: bool,
+
+    /// Invoke the named export instead of `main`, printing its results
+    #[clap(long, value_parser)]
+    invoke: Option<String>,
+
+    /// The input wasm file, `-` reads from stdin
     #[clap(short, long, value_parser)]
     file: String,
 }
 
 fn main() {
     let args = Args::parse();
-    let module = module::WasmReader::decode_file(args.file).unwrap();
-    if args.dump {
-        dumper::Dumper::dump(&module);
+    let decoded = if args.file == "-" {
+        // 从标准输入读取，便于在管道中使用
+        let mut buf = Vec::new();
+        std::io::stdin().read_to_end(&mut buf).unwrap();
+        module::WasmReader::decode_bytes(&buf)
     } else {
-        interpreter::VM::exec_main(&module);
+        module::WasmReader::decode_file(args.file)
+    };
+    let module = match decoded {
+        Ok(module) => module,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    if let Err(err) = validator::Validator::validate(&module) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+    if args.dump {
+        match args.format {
+            Format::Text => dumper::Dumper::dump(&module),
+            Format::Json => {
+                // 逐项写出，大模块不必先在内存中构造完整文档
+                let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+                dumper::write_json(&module, &mut out).unwrap();
+                writeln!(out).unwrap();
+            }
+        }
+        return;
+    }
+    let mut vm =
+        interpreter::VM::new(&module).strict_alignment(args.strict_alignment);
+    // 指定了 --invoke 时直接调用该导出函数，模块不必有 main 或起始函数
+    let ret = match &args.invoke {
+        Some(name) => vm.invoke(name, &[]).map(|results| {
+            for result in results {
+                println!("{:?}", result);
+            }
+        }),
+        None => vm.run_main(),
+    };
+    if let Err(err) = ret {
+        eprintln!("{}", err);
+        // 陷入时按陷入类型返回不同的退出码
+        let code = match err {
+            rasm::error::Error::Trap(trap) => trap.exit_code(),
+            _ => 1,
+        };
+        std::process::exit(code);
     }
-    
-    
 }
@@ -0,0 +1,638 @@
+pub mod cache {
+    use crate::error::Error;
+    use crate::module::module::module::{
+        CustomSec, Data, Elem, Export, Global, Import, Locals,
+    };
+    use crate::module::*;
+    use std::any::Any;
+    use std::rc::Rc;
+
+    // 缓存格式与 wasm 二进制格式无关，直接保存解码后的指令树，格式变化时需要增加版本号
+    const CACHE_MAGIC: &[u8; 4] = b"RSMC";
+    pub const CACHE_VERSION: u32 = 6;
+
+    impl Module {
+        /// 将解码后的模块序列化为缓存，之后可以用 from_cache 直接加载
+        pub fn to_cache(&self) -> Vec<u8> {
+            let mut w = CacheWriter { buf: vec![] };
+            w.buf.extend_from_slice(CACHE_MAGIC);
+            w.write_u32(CACHE_VERSION);
+            w.write_u32(self.magic);
+            w.write_u32(self.version);
+            w.write_vec(&self.custom_sec, |w, cs| {
+                w.write_str(&cs.name);
+                w.write_bytes(&cs.bytes);
+            });
+            w.write_vec(&self.type_sec, |w, ft| w.write_func_type(ft));
+            w.write_vec(&self.import_sec, |w, imp| {
+                w.write_str(&imp.module_name);
+                w.write_str(&imp.member_name);
+                match &imp.desc {
+                    ImportDesc::Func(type_idx) => {
+                        w.write_u8(0);
+                        w.write_u32(*type_idx);
+                    }
+                    ImportDesc::Table(table) => {
+                        w.write_u8(1);
+                        w.write_table_type(table);
+                    }
+                    ImportDesc::Mem(mem) => {
+                        w.write_u8(2);
+                        w.write_limits(mem);
+                    }
+                    ImportDesc::Global(global) => {
+                        w.write_u8(3);
+                        w.write_global_type(global);
+                    }
+                }
+            });
+            w.write_vec(&self.func_sec, |w, idx| w.write_u32(*idx));
+            w.write_vec(&self.table_sec, |w, t| w.write_table_type(t));
+            w.write_vec(&self.mem_sec, |w, l| w.write_limits(l));
+            w.write_vec(&self.global_sec, |w, g| {
+                w.write_global_type(&g.global_type);
+                w.write_expr(&g.init_expr);
+            });
+            w.write_vec(&self.export_sec, |w, exp| {
+                w.write_str(&exp.name);
+                let (tag, idx) = match exp.desc {
+                    ExportDesc::Func(idx) => (0, idx),
+                    ExportDesc::Table(idx) => (1, idx),
+                    ExportDesc::Mem(idx) => (2, idx),
+                    ExportDesc::Global(idx) => (3, idx),
+                };
+                w.write_u8(tag);
+                w.write_u32(idx);
+            });
+            w.write_option(self.start_sec);
+            w.write_vec(&self.elem_sec, |w, elem| {
+                w.write_u8(elem.mode as u8);
+                w.write_u32(elem.table);
+                w.write_expr(&elem.offset);
+                w.write_vec(&elem.init, |w, idx| w.write_u32(*idx));
+            });
+            w.write_vec(&self.code_sec, |w, code| {
+                w.write_vec(&code.locals, |w, locals| {
+                    w.write_u32(locals.n);
+                    w.write_u8(locals.val_type as u8);
+                });
+                w.write_expr(&code.expr);
+            });
+            w.write_vec(&self.data_sec, |w, data| {
+                w.write_u32(data.mem);
+                w.write_expr(&data.offset);
+                w.write_bytes(&data.init);
+            });
+            w.buf
+        }
+
+        /// 从 to_cache 生成的缓存中加载模块，缓存版本不匹配或数据损坏时返回错误
+        pub fn from_cache(data: &[u8]) -> Result<Module, Error> {
+            let mut r = CacheReader { data };
+            if r.read_n(4)? != CACHE_MAGIC {
+                return Err(invalid("bad magic"));
+            }
+            let version = r.read_u32()?;
+            if version != CACHE_VERSION {
+                return Err(invalid(&format!(
+                    "version {} is not supported, expected {}",
+                    version, CACHE_VERSION
+                )));
+            }
+            let module = Module {
+                magic: r.read_u32()?,
+                version: r.read_u32()?,
+                custom_sec: r.read_vec(|r| {
+                    Ok(CustomSec {
+                        name: r.read_str()?,
+                        bytes: r.read_bytes()?,
+                    })
+                })?,
+                type_sec: r.read_vec(|r| r.read_func_type())?,
+                import_sec: r.read_vec(|r| {
+                    let module_name = r.read_str()?;
+                    let member_name = r.read_str()?;
+                    let desc = match r.read_u8()? {
+                        0 => ImportDesc::Func(r.read_u32()?),
+                        1 => ImportDesc::Table(r.read_table_type()?),
+                        2 => ImportDesc::Mem(r.read_limits()?),
+                        3 => ImportDesc::Global(r.read_global_type()?),
+                        tag => {
+                            return Err(invalid(&format!("import tag {}", tag)))
+                        }
+                    };
+                    Ok(Import {
+                        module_name,
+                        member_name,
+                        desc,
+                    })
+                })?,
+                func_sec: r.read_vec(|r| r.read_u32())?,
+                table_sec: r.read_vec(|r| r.read_table_type())?,
+                mem_sec: r.read_vec(|r| r.read_limits())?,
+                global_sec: r.read_vec(|r| {
+                    Ok(Global {
+                        global_type: r.read_global_type()?,
+                        init_expr: r.read_expr()?,
+                    })
+                })?,
+                export_sec: r.read_vec(|r| {
+                    let name = r.read_str()?;
+                    let desc = match r.read_u8()? {
+                        0 => ExportDesc::Func(r.read_u32()?),
+                        1 => ExportDesc::Table(r.read_u32()?),
+                        2 => ExportDesc::Mem(r.read_u32()?),
+                        3 => ExportDesc::Global(r.read_u32()?),
+                        tag => {
+                            return Err(invalid(&format!("export tag {}", tag)))
+                        }
+                    };
+                    Ok(Export { name, desc })
+                })?,
+                start_sec: r.read_option()?,
+                elem_sec: r.read_vec(|r| {
+                    let mode = match r.read_u8()? {
+                        0 => ElemMode::Active,
+                        1 => ElemMode::Passive,
+                        2 => ElemMode::Declarative,
+                        tag => {
+                            return Err(invalid(&format!("elem mode {}", tag)))
+                        }
+                    };
+                    Ok(Elem {
+                        mode,
+                        table: r.read_u32()?,
+                        offset: r.read_expr()?,
+                        init: r.read_vec(|r| r.read_u32())?,
+                    })
+                })?,
+                code_sec: r.read_vec(|r| {
+                    Ok(Code {
+                        locals: r.read_vec(|r| {
+                            Ok(Locals {
+                                n: r.read_u32()?,
+                                val_type: r.read_val_type()?,
+                            })
+                        })?,
+                        expr: r.read_expr()?,
+                    })
+                })?,
+                data_sec: r.read_vec(|r| {
+                    Ok(Data {
+                        mem: r.read_u32()?,
+                        offset: r.read_expr()?,
+                        init: r.read_bytes()?,
+                    })
+                })?,
+                original: None,
+                instr_ranges: Vec::new(),
+            };
+            if !r.data.is_empty() {
+                return Err(invalid("trailing bytes"));
+            }
+            Ok(module)
+        }
+    }
+
+    fn invalid(msg: &str) -> Error {
+        Error::InvalidCache(msg.to_string())
+    }
+
+    fn is_mem_instr(opcode: OpCode) -> bool {
+        opcode >= OpCode::I32Load && opcode <= OpCode::I64Store32
+    }
+
+    struct CacheWriter {
+        buf: Vec<u8>,
+    }
+
+    impl CacheWriter {
+        fn write_u8(&mut self, n: u8) {
+            self.buf.push(n);
+        }
+
+        fn write_u32(&mut self, n: u32) {
+            self.buf.extend_from_slice(&n.to_le_bytes());
+        }
+
+        fn write_u64(&mut self, n: u64) {
+            self.buf.extend_from_slice(&n.to_le_bytes());
+        }
+
+        fn write_bytes(&mut self, bytes: &[u8]) {
+            self.write_u32(bytes.len() as u32);
+            self.buf.extend_from_slice(bytes);
+        }
+
+        fn write_str(&mut self, s: &str) {
+            self.write_bytes(s.as_bytes());
+        }
+
+        fn write_option(&mut self, n: Option<u32>) {
+            match n {
+                Some(n) => {
+                    self.write_u8(1);
+                    self.write_u32(n);
+                }
+                None => self.write_u8(0),
+            }
+        }
+
+        fn write_vec<T>(&mut self, items: &[T], f: impl Fn(&mut Self, &T)) {
+            self.write_u32(items.len() as u32);
+            for item in items {
+                f(self, item);
+            }
+        }
+
+        fn write_func_type(&mut self, ft: &FuncType) {
+            self.write_vec(&ft.params_types, |w, vt| w.write_u8(*vt as u8));
+            self.write_vec(&ft.result_types, |w, vt| w.write_u8(*vt as u8));
+        }
+
+        fn write_limits(&mut self, limits: &Limits) {
+            self.write_u64(limits.min as u64);
+            match limits.max {
+                Some(max) => {
+                    self.write_u8(1);
+                    self.write_u64(max as u64);
+                }
+                None => self.write_u8(0),
+            }
+            self.write_option(limits.page_size_log2);
+        }
+
+        fn write_table_type(&mut self, table: &TableType) {
+            self.write_u8(table.elem_type as u8);
+            self.write_limits(&table.limits);
+        }
+
+        fn write_global_type(&mut self, global_type: &GlobalType) {
+            self.write_u8(global_type.val_type as u8);
+            self.write_u8(global_type.mutable as u8);
+        }
+
+        fn write_expr(&mut self, expr: &[Instruction]) {
+            self.write_vec(expr, |w, instr| w.write_instr(instr));
+        }
+
+        // 指令参数的类型由操作码决定，与 WasmReader::read_args 一一对应
+        fn write_instr(&mut self, instr: &Instruction) {
+            self.write_u8(instr.opcode.into());
+            let args: &dyn Any = match instr.args.as_ref() {
+                Some(args) => args.as_ref(),
+                None => return,
+            };
+            match instr.opcode {
+                OpCode::Block | OpCode::Loop => {
+                    let block_args = args.downcast_ref::<BlockArgs>().unwrap();
+                    self.write_u32(block_args.block_type as u32);
+                    self.write_expr(&block_args.instructions);
+                }
+                OpCode::If => {
+                    let if_args = args.downcast_ref::<IfArgs>().unwrap();
+                    self.write_u32(if_args.block_type as u32);
+                    self.write_expr(&if_args.instructions_1);
+                    self.write_expr(&if_args.instructions_2);
+                }
+                OpCode::BrTable => {
+                    let br_table_args =
+                        args.downcast_ref::<BrTableArgs>().unwrap();
+                    self.write_vec(&br_table_args.labels, |w, label| {
+                        w.write_u32(*label)
+                    });
+                    self.write_u32(br_table_args.default);
+                }
+                OpCode::I32Const => {
+                    self.write_u32(*args.downcast_ref::<i32>().unwrap() as u32)
+                }
+                OpCode::I64Const => {
+                    self.write_u64(*args.downcast_ref::<i64>().unwrap() as u64)
+                }
+                OpCode::F32Const => self
+                    .write_u32(args.downcast_ref::<f32>().unwrap().to_bits()),
+                OpCode::F64Const => self
+                    .write_u64(args.downcast_ref::<f64>().unwrap().to_bits()),
+                OpCode::CallIndirect => {
+                    let ci_args =
+                        args.downcast_ref::<CallIndirectArgs>().unwrap();
+                    self.write_u32(ci_args.type_idx);
+                    self.write_u32(ci_args.table_idx);
+                }
+                OpCode::MemorySize | OpCode::MemoryGrow => {
+                    self.write_u8(*args.downcast_ref::<u8>().unwrap())
+                }
+                OpCode::TruncSat => {
+                    let prefixed = args.downcast_ref::<PrefixedArgs>().unwrap();
+                    self.write_u8(prefixed.sub_opcode);
+                    self.write_u32(prefixed.idx);
+                    self.write_u32(prefixed.src_idx);
+                }
+                OpCode::RefNull => {
+                    let (tag, idx) =
+                        match args.downcast_ref::<HeapType>().unwrap() {
+                            HeapType::Func => (0, 0),
+                            HeapType::Extern => (1, 0),
+                            HeapType::Any => (2, 0),
+                            HeapType::Eq => (3, 0),
+                            HeapType::I31 => (4, 0),
+                            HeapType::Struct => (5, 0),
+                            HeapType::Array => (6, 0),
+                            HeapType::None => (7, 0),
+                            HeapType::NoExtern => (8, 0),
+                            HeapType::NoFunc => (9, 0),
+                            HeapType::Type(idx) => (10, *idx),
+                        };
+                    self.write_u8(tag);
+                    self.write_u32(idx);
+                }
+                OpCode::Gc => {
+                    let gc_args = args.downcast_ref::<GcArgs>().unwrap();
+                    self.write_u32(gc_args.sub_opcode);
+                    self.write_u32(gc_args.type_idx);
+                }
+                opcode if is_mem_instr(opcode) => {
+                    let mem_arg = args.downcast_ref::<MemArg>().unwrap();
+                    self.write_u32(mem_arg.align);
+                    self.write_u32(mem_arg.offset);
+                }
+                // 其余带参数的指令参数均为索引
+                _ => self.write_u32(*args.downcast_ref::<u32>().unwrap()),
+            }
+        }
+    }
+
+    struct CacheReader<'a> {
+        data: &'a [u8],
+    }
+
+    impl<'a> CacheReader<'a> {
+        fn read_n(&mut self, n: usize) -> Result<&'a [u8], Error> {
+            if self.data.len() < n {
+                return Err(invalid("unexpected end of cache"));
+            }
+            let (bytes, rest) = self.data.split_at(n);
+            self.data = rest;
+            Ok(bytes)
+        }
+
+        fn read_u8(&mut self) -> Result<u8, Error> {
+            Ok(self.read_n(1)?[0])
+        }
+
+        fn read_u32(&mut self) -> Result<u32, Error> {
+            Ok(u32::from_le_bytes(self.read_n(4)?.try_into().unwrap()))
+        }
+
+        fn read_u64(&mut self) -> Result<u64, Error> {
+            Ok(u64::from_le_bytes(self.read_n(8)?.try_into().unwrap()))
+        }
+
+        fn read_bytes(&mut self) -> Result<Vec<u8>, Error> {
+            let len = self.read_u32()? as usize;
+            Ok(self.read_n(len)?.to_vec())
+        }
+
+        fn read_str(&mut self) -> Result<String, Error> {
+            String::from_utf8(self.read_bytes()?)
+                .map_err(|_| invalid("malformed UTF-8 string"))
+        }
+
+        fn read_option(&mut self) -> Result<Option<u32>, Error> {
+            match self.read_u8()? {
+                0 => Ok(None),
+                _ => Ok(Some(self.read_u32()?)),
+            }
+        }
+
+        fn read_vec<T>(
+            &mut self,
+            f: impl Fn(&mut Self) -> Result<T, Error>,
+        ) -> Result<Vec<T>, Error> {
+            let len = self.read_u32()? as usize;
+            let mut items = Vec::with_capacity(len.min(self.data.len()));
+            for _ in 0..len {
+                items.push(f(self)?);
+            }
+            Ok(items)
+        }
+
+        fn read_val_type(&mut self) -> Result<ValType, Error> {
+            let b = self.read_u8()?;
+            ValType::try_from(b)
+                .map_err(|_| invalid(&format!("value type {:#x}", b)))
+        }
+
+        fn read_func_type(&mut self) -> Result<FuncType, Error> {
+            Ok(FuncType {
+                params_types: self.read_vec(|r| r.read_val_type())?,
+                result_types: self.read_vec(|r| r.read_val_type())?,
+            })
+        }
+
+        fn read_limits(&mut self) -> Result<Limits, Error> {
+            let min = self.read_u64()? as usize;
+            let max = match self.read_u8()? {
+                0 => None,
+                _ => Some(self.read_u64()? as usize),
+            };
+            let page_size_log2 = self.read_option()?;
+            Ok(Limits {
+                min,
+                max,
+                page_size_log2,
+            })
+        }
+
+        fn read_table_type(&mut self) -> Result<TableType, Error> {
+            Ok(TableType {
+                elem_type: self.read_val_type()?,
+                limits: self.read_limits()?,
+            })
+        }
+
+        fn read_global_type(&mut self) -> Result<GlobalType, Error> {
+            Ok(GlobalType {
+                val_type: self.read_val_type()?,
+                mutable: self.read_u8()? != 0,
+            })
+        }
+
+        fn read_expr(&mut self) -> Result<Expr, Error> {
+            self.read_vec(|r| r.read_instr())
+        }
+
+        fn read_instr(&mut self) -> Result<Instruction, Error> {
+            let b = self.read_u8()?;
+            let opcode = OpCode::try_from(b)
+                .map_err(|_| invalid(&format!("opcode {:#x}", b)))?;
+            let args: Option<Rc<dyn Any>> = match opcode {
+                OpCode::Block | OpCode::Loop => Some(Rc::new(BlockArgs {
+                    block_type: self.read_u32()? as BlockType,
+                    instructions: self.read_expr()?,
+                })),
+                OpCode::If => Some(Rc::new(IfArgs {
+                    block_type: self.read_u32()? as BlockType,
+                    instructions_1: self.read_expr()?,
+                    instructions_2: self.read_expr()?,
+                })),
+                OpCode::BrTable => Some(Rc::new(BrTableArgs {
+                    labels: self.read_vec(|r| r.read_u32())?,
+                    default: self.read_u32()?,
+                })),
+                OpCode::Br
+                | OpCode::BrIf
+                | OpCode::Call
+                | OpCode::LocalGet
+                | OpCode::LocalSet
+                | OpCode::LocalTee
+                | OpCode::GlobalGet
+                | OpCode::GlobalSet
+                | OpCode::TableGet
+                | OpCode::TableSet
+                | OpCode::RefFunc => Some(Rc::new(self.read_u32()?)),
+                OpCode::I32Const => Some(Rc::new(self.read_u32()? as i32)),
+                OpCode::I64Const => Some(Rc::new(self.read_u64()? as i64)),
+                OpCode::F32Const => {
+                    Some(Rc::new(f32::from_bits(self.read_u32()?)))
+                }
+                OpCode::F64Const => {
+                    Some(Rc::new(f64::from_bits(self.read_u64()?)))
+                }
+                OpCode::CallIndirect => Some(Rc::new(CallIndirectArgs {
+                    type_idx: self.read_u32()?,
+                    table_idx: self.read_u32()?,
+                })),
+                OpCode::MemorySize | OpCode::MemoryGrow => {
+                    Some(Rc::new(self.read_u8()?))
+                }
+                OpCode::TruncSat => Some(Rc::new(PrefixedArgs {
+                    sub_opcode: self.read_u8()?,
+                    idx: self.read_u32()?,
+                    src_idx: self.read_u32()?,
+                })),
+                OpCode::RefNull => {
+                    let tag = self.read_u8()?;
+                    let idx = self.read_u32()?;
+                    let heap_type = match tag {
+                        0 => HeapType::Func,
+                        1 => HeapType::Extern,
+                        2 => HeapType::Any,
+                        3 => HeapType::Eq,
+                        4 => HeapType::I31,
+                        5 => HeapType::Struct,
+                        6 => HeapType::Array,
+                        7 => HeapType::None,
+                        8 => HeapType::NoExtern,
+                        9 => HeapType::NoFunc,
+                        10 => HeapType::Type(idx),
+                        _ => {
+                            return Err(invalid(&format!("heap type {}", tag)))
+                        }
+                    };
+                    Some(Rc::new(heap_type))
+                }
+                OpCode::Gc => Some(Rc::new(GcArgs {
+                    sub_opcode: self.read_u32()?,
+                    type_idx: self.read_u32()?,
+                })),
+                opcode if is_mem_instr(opcode) => Some(Rc::new(MemArg {
+                    align: self.read_u32()?,
+                    offset: self.read_u32()?,
+                })),
+                _ => None,
+            };
+            Ok(Instruction { opcode, args })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        // (func (export "add") (param i32 i32) (result i32)
+        //   local.get 0 local.get 1 i32.add)
+        const ADD_WASM: &[u8] = &[
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00,
+            0x00, // magic & version
+            0x01, 0x07, 0x01, 0x60, 0x02, 0x7f, 0x7f, 0x01,
+            0x7f, // type sec
+            0x03, 0x02, 0x01, 0x00, // func sec
+            0x07, 0x07, 0x01, 0x03, 0x61, 0x64, 0x64, 0x00,
+            0x00, // export sec
+            0x0a, 0x09, 0x01, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6a,
+            0x0b, // code sec
+        ];
+
+        fn instr<T: Any>(opcode: OpCode, args: T) -> Instruction {
+            Instruction::with_args(opcode, args)
+        }
+
+        #[test]
+        fn test_cache_round_trip() {
+            let module = WasmReader::decode_bytes(ADD_WASM).unwrap();
+            let cached = Module::from_cache(&module.to_cache()).unwrap();
+            assert!(module.diff(&cached).is_empty());
+            let expr = &cached.code_sec[0].expr;
+            let opcodes: Vec<u8> =
+                expr.iter().map(|i| i.opcode.into()).collect();
+            assert_eq!(opcodes, vec![0x20, 0x20, 0x6a]);
+            let local_idx = expr[1].args.as_ref().unwrap();
+            assert_eq!(local_idx.downcast_ref::<u32>(), Some(&1));
+        }
+
+        #[test]
+        fn test_cache_nested_instructions() {
+            let mut module = WasmReader::decode_bytes(ADD_WASM).unwrap();
+            module.code_sec[0].expr = vec![
+                instr(
+                    OpCode::Block,
+                    BlockArgs {
+                        block_type: BLOCK_TYPE_EMPTY,
+                        instructions: vec![
+                            instr(OpCode::I32Const, -1i32),
+                            instr(
+                                OpCode::BrTable,
+                                BrTableArgs {
+                                    labels: vec![0, 0],
+                                    default: 0,
+                                },
+                            ),
+                        ],
+                    },
+                ),
+                instr(OpCode::I32Const, 8i32),
+                instr(
+                    OpCode::I64Load,
+                    MemArg {
+                        align: 3,
+                        offset: 4,
+                    },
+                ),
+                instr(OpCode::F64Const, -0.5f64),
+                instr(OpCode::RefNull, HeapType::Extern),
+                instr(
+                    OpCode::TruncSat,
+                    PrefixedArgs {
+                        sub_opcode: MEMORY_INIT,
+                        idx: 1,
+                        src_idx: 0,
+                    },
+                ),
+            ];
+            let cached = Module::from_cache(&module.to_cache()).unwrap();
+            assert!(module.diff(&cached).is_empty());
+        }
+
+        #[test]
+        fn test_cache_version_mismatch() {
+            let module = WasmReader::decode_bytes(ADD_WASM).unwrap();
+            let mut cache = module.to_cache();
+            cache[4..8].copy_from_slice(&(CACHE_VERSION + 1).to_le_bytes());
+            let ret = Module::from_cache(&cache);
+            assert!(matches!(ret, Err(Error::InvalidCache(_))));
+            let ret = Module::from_cache(&module.to_cache()[..20]);
+            assert!(matches!(ret, Err(Error::InvalidCache(_))));
+        }
+    }
+}
@@ -0,0 +1,357 @@
+pub mod diff {
+    use crate::module::*;
+    use std::fmt;
+
+    /// 两个模块之间的一处结构差异，index 为条目在对应段中的索引
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Difference {
+        Added {
+            section: &'static str,
+            index: usize,
+            item: String,
+        },
+        Removed {
+            section: &'static str,
+            index: usize,
+            item: String,
+        },
+        Changed {
+            section: &'static str,
+            index: usize,
+            old: String,
+            new: String,
+        },
+    }
+
+    impl fmt::Display for Difference {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Difference::Added {
+                    section,
+                    index,
+                    item,
+                } => write!(f, "+ {}[{}]: {}", section, index, item),
+                Difference::Removed {
+                    section,
+                    index,
+                    item,
+                } => write!(f, "- {}[{}]: {}", section, index, item),
+                Difference::Changed {
+                    section,
+                    index,
+                    old,
+                    new,
+                } => write!(f, "~ {}[{}]: {} => {}", section, index, old, new),
+            }
+        }
+    }
+
+    // 将某个段的每个条目描述为一行文本
+    type Describe = fn(&Module) -> Vec<String>;
+
+    impl Module {
+        /// 逐段比较两个模块，返回 other 相对于 self 的差异
+        pub fn diff(&self, other: &Module) -> Vec<Difference> {
+            let mut diffs = vec![];
+            let sections: [(&'static str, Describe); 9] = [
+                ("type", describe_types),
+                ("import", describe_imports),
+                ("function", describe_funcs),
+                ("table", describe_tables),
+                ("memory", describe_mems),
+                ("global", describe_globals),
+                ("export", describe_exports),
+                ("elem", describe_elems),
+                ("data", describe_data),
+            ];
+            for (section, describe) in sections {
+                diff_items(
+                    section,
+                    describe(self),
+                    describe(other),
+                    &mut diffs,
+                );
+            }
+            let start = |m: &Module| -> Vec<String> {
+                m.start_sec
+                    .iter()
+                    .map(|idx| format!("func[{}]", idx))
+                    .collect()
+            };
+            diff_items("start", start(self), start(other), &mut diffs);
+            diffs
+        }
+    }
+
+    fn diff_items(
+        section: &'static str,
+        old: Vec<String>,
+        new: Vec<String>,
+        diffs: &mut Vec<Difference>,
+    ) {
+        for index in 0..old.len().max(new.len()) {
+            match (old.get(index), new.get(index)) {
+                (Some(old), Some(new)) if old != new => {
+                    diffs.push(Difference::Changed {
+                        section,
+                        index,
+                        old: old.clone(),
+                        new: new.clone(),
+                    })
+                }
+                (Some(item), None) => diffs.push(Difference::Removed {
+                    section,
+                    index,
+                    item: item.clone(),
+                }),
+                (None, Some(item)) => diffs.push(Difference::Added {
+                    section,
+                    index,
+                    item: item.clone(),
+                }),
+                _ => {}
+            }
+        }
+    }
+
+    fn describe_types(m: &Module) -> Vec<String> {
+        m.type_sec.iter().map(|t| t.get_signature()).collect()
+    }
+
+    fn describe_imports(m: &Module) -> Vec<String> {
+        m.import_sec
+            .iter()
+            .map(|import| {
+                let desc = match &import.desc {
+                    ImportDesc::Func(type_idx) => {
+                        format!("func type={}", type_idx)
+                    }
+                    ImportDesc::Table(table) => format!("table {}", table),
+                    ImportDesc::Mem(mem) => format!("memory {}", mem),
+                    ImportDesc::Global(global) => format!("global {}", global),
+                };
+                format!(
+                    "{}.{} {}",
+                    import.module_name, import.member_name, desc
+                )
+            })
+            .collect()
+    }
+
+    // 内部函数由签名、局部变量和函数体共同描述
+    fn describe_funcs(m: &Module) -> Vec<String> {
+        m.func_sec
+            .iter()
+            .enumerate()
+            .map(|(i, type_idx)| {
+                let sig = match m.type_sec.get(*type_idx as usize) {
+                    Some(func_type) => func_type.get_signature(),
+                    None => format!("type={}", type_idx),
+                };
+                match m.code_sec.get(i) {
+                    Some(code) => {
+                        let locals: Vec<String> = code
+                            .locals
+                            .iter()
+                            .map(|l| format!("{} x {}", l.val_type, l.n))
+                            .collect();
+                        format!(
+                            "{} locals=[{}] body=[{}]",
+                            sig,
+                            locals.join(", "),
+                            describe_expr(&code.expr)
+                        )
+                    }
+                    None => sig,
+                }
+            })
+            .collect()
+    }
+
+    fn describe_tables(m: &Module) -> Vec<String> {
+        m.table_sec.iter().map(|t| t.to_string()).collect()
+    }
+
+    fn describe_mems(m: &Module) -> Vec<String> {
+        m.mem_sec.iter().map(|l| l.to_string()).collect()
+    }
+
+    fn describe_globals(m: &Module) -> Vec<String> {
+        m.global_sec
+            .iter()
+            .map(|g| {
+                format!(
+                    "{} init=[{}]",
+                    g.global_type,
+                    describe_expr(&g.init_expr)
+                )
+            })
+            .collect()
+    }
+
+    fn describe_exports(m: &Module) -> Vec<String> {
+        m.export_sec
+            .iter()
+            .map(|exp| {
+                let desc = match exp.desc {
+                    ExportDesc::Func(idx) => format!("func[{}]", idx),
+                    ExportDesc::Table(idx) => format!("table[{}]", idx),
+                    ExportDesc::Mem(idx) => format!("memory[{}]", idx),
+                    ExportDesc::Global(idx) => format!("global[{}]", idx),
+                };
+                format!("{} \"{}\"", desc, exp.name)
+            })
+            .collect()
+    }
+
+    fn describe_elems(m: &Module) -> Vec<String> {
+        m.elem_sec
+            .iter()
+            .map(|e| {
+                format!(
+                    "mode={:?} table={} offset=[{}] init={:?}",
+                    e.mode,
+                    e.table,
+                    describe_expr(&e.offset),
+                    e.init
+                )
+            })
+            .collect()
+    }
+
+    fn describe_data(m: &Module) -> Vec<String> {
+        m.data_sec
+            .iter()
+            .map(|d| {
+                format!(
+                    "mem={} offset=[{}] init={:02x?}",
+                    d.mem,
+                    describe_expr(&d.offset),
+                    d.init
+                )
+            })
+            .collect()
+    }
+
+    // 将指令序列转换为文本，用于比较函数体等表达式是否相同
+    fn describe_expr(expr: &Expr) -> String {
+        expr.iter()
+            .map(describe_instr)
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn describe_instr(instr: &Instruction) -> String {
+        let name = instr.get_op_name();
+        let args = match instr.args.as_ref() {
+            Some(args) => args,
+            None => return name,
+        };
+        if let Some(block_args) = args.downcast_ref::<BlockArgs>() {
+            format!(
+                "{} {} ({})",
+                name,
+                block_args.block_type,
+                describe_expr(&block_args.instructions)
+            )
+        } else if let Some(if_args) = args.downcast_ref::<IfArgs>() {
+            format!(
+                "{} {} ({}) ({})",
+                name,
+                if_args.block_type,
+                describe_expr(&if_args.instructions_1),
+                describe_expr(&if_args.instructions_2)
+            )
+        } else if let Some(v) = args.downcast_ref::<u32>() {
+            format!("{} {}", name, v)
+        } else if let Some(v) = args.downcast_ref::<i32>() {
+            format!("{} {}", name, v)
+        } else if let Some(v) = args.downcast_ref::<i64>() {
+            format!("{} {}", name, v)
+        } else if let Some(v) = args.downcast_ref::<f32>() {
+            format!("{} {:#x}", name, v.to_bits())
+        } else if let Some(v) = args.downcast_ref::<f64>() {
+            format!("{} {:#x}", name, v.to_bits())
+        } else if let Some(v) = args.downcast_ref::<u8>() {
+            format!("{} {}", name, v)
+        } else if let Some(mem_arg) = args.downcast_ref::<MemArg>() {
+            format!("{} {}", name, mem_arg)
+        } else if let Some(br_table_args) = args.downcast_ref::<BrTableArgs>() {
+            format!("{} {}", name, br_table_args)
+        } else if let Some(ci_args) = args.downcast_ref::<CallIndirectArgs>() {
+            format!("{} {}", name, ci_args)
+        } else if let Some(prefixed) = args.downcast_ref::<PrefixedArgs>() {
+            format!("{} {}", name, prefixed)
+        } else {
+            name
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::module::module::module::Export;
+
+        fn add_module(export_name: &str, rhs: i32) -> Module {
+            Module {
+                type_sec: vec![FuncType {
+                    params_types: vec![ValType::I32],
+                    result_types: vec![ValType::I32],
+                }],
+                func_sec: vec![0],
+                export_sec: vec![Export {
+                    name: export_name.to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![
+                        Instruction::with_args(OpCode::LocalGet, 0u32),
+                        Instruction::with_args(OpCode::I32Const, rhs),
+                        Instruction::new(OpCode::I32Add),
+                    ],
+                }],
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn test_diff_identical() {
+            assert!(add_module("add", 1)
+                .diff(&add_module("add", 1))
+                .is_empty());
+        }
+
+        #[test]
+        fn test_diff_export_name() {
+            let diffs = add_module("add", 1).diff(&add_module("inc", 1));
+            assert_eq!(
+                diffs,
+                vec![Difference::Changed {
+                    section: "export",
+                    index: 0,
+                    old: "func[0] \"add\"".to_string(),
+                    new: "func[0] \"inc\"".to_string(),
+                }]
+            );
+            assert_eq!(
+                diffs[0].to_string(),
+                "~ export[0]: func[0] \"add\" => func[0] \"inc\""
+            );
+        }
+
+        #[test]
+        fn test_diff_function_body() {
+            let diffs = add_module("add", 1).diff(&add_module("add", 2));
+            assert_eq!(diffs.len(), 1);
+            assert!(matches!(
+                diffs[0],
+                Difference::Changed {
+                    section: "function",
+                    index: 0,
+                    ..
+                }
+            ));
+        }
+    }
+}
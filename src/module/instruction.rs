@@ -1,5 +1,6 @@
 pub mod instruction {
     use crate::module::OpCode;
+    use crate::module::{DATA_DROP, MEMORY_INIT, TABLE_COPY};
     use std::any::Any;
     use std::fmt;
     use std::rc::Rc;
@@ -11,19 +12,91 @@ pub mod instruction {
     }
 
     impl Instruction {
+        /// 没有立即数的指令
+        pub fn new(opcode: OpCode) -> Instruction {
+            Instruction { opcode, args: None }
+        }
+
+        /// 立即数为 args 的指令，args 的类型必须与解码器为该操作码生成的一致
+        pub fn with_args<T: 'static>(opcode: OpCode, args: T) -> Instruction {
+            Instruction {
+                opcode,
+                args: Some(Rc::new(args)),
+            }
+        }
+
+        pub fn with_i32_const(val: i32) -> Instruction {
+            Instruction::with_args(OpCode::I32Const, val)
+        }
+
+        pub fn with_i64_const(val: i64) -> Instruction {
+            Instruction::with_args(OpCode::I64Const, val)
+        }
+
+        pub fn with_f32_const(val: f32) -> Instruction {
+            Instruction::with_args(OpCode::F32Const, val)
+        }
+
+        pub fn with_f64_const(val: f64) -> Instruction {
+            Instruction::with_args(OpCode::F64Const, val)
+        }
+
+        /// 立即数为索引的指令，如 call、local.get、global.set、br
+        pub fn with_index(opcode: OpCode, idx: u32) -> Instruction {
+            Instruction::with_args(opcode, idx)
+        }
+
+        /// 内存访问指令
+        pub fn with_mem_arg(opcode: OpCode, mem_arg: MemArg) -> Instruction {
+            Instruction::with_args(opcode, mem_arg)
+        }
+
+        /// 按类型取出立即数，类型不符时返回 None
+        pub fn args_as<T: 'static>(&self) -> Option<&T> {
+            self.args.as_ref()?.downcast_ref::<T>()
+        }
+
+        /// 替换立即数
+        pub fn set_args<T: 'static>(&mut self, args: T) {
+            self.args = Some(Rc::new(args));
+        }
+
         pub fn get_op_name(&self) -> String {
             self.opcode.to_string()
         }
     }
 
+    // 立即数是 dyn Any，只显示操作码
+    impl fmt::Debug for Instruction {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("Instruction")
+                .field("opcode", &format_args!("{}", self.opcode))
+                .finish_non_exhaustive()
+        }
+    }
+
     pub struct MemArg {
         pub align: u32,
         pub offset: u32,
     }
 
+    impl MemArg {
+        /// align 存储的是对齐字节数以 2 为底的对数，畸形模块中的指数可能超出 u64，此时返回 None
+        pub fn align_bytes(&self) -> Option<u64> {
+            1u64.checked_shl(self.align)
+        }
+    }
+
     impl fmt::Display for MemArg {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "align: {}, offset: {}", self.align, self.offset)
+            match self.align_bytes() {
+                Some(bytes) => {
+                    write!(f, "offset={} align={}", self.offset, bytes)
+                }
+                None => {
+                    write!(f, "offset={} align=2^{}", self.offset, self.align)
+                }
+            }
         }
     }
 
@@ -47,6 +120,82 @@ pub mod instruction {
         pub instructions_2: Vec<Instruction>,
     }
 
+    /// 引用指向的堆类型，抽象堆类型编码为负数，非负数为类型索引
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum HeapType {
+        Func,
+        Extern,
+        Any,
+        Eq,
+        I31,
+        Struct,
+        Array,
+        None,
+        NoExtern,
+        NoFunc,
+        Type(u32),
+    }
+
+    impl fmt::Display for HeapType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                HeapType::Func => write!(f, "func"),
+                HeapType::Extern => write!(f, "extern"),
+                HeapType::Any => write!(f, "any"),
+                HeapType::Eq => write!(f, "eq"),
+                HeapType::I31 => write!(f, "i31"),
+                HeapType::Struct => write!(f, "struct"),
+                HeapType::Array => write!(f, "array"),
+                HeapType::None => write!(f, "none"),
+                HeapType::NoExtern => write!(f, "noextern"),
+                HeapType::NoFunc => write!(f, "nofunc"),
+                HeapType::Type(idx) => write!(f, "{}", idx),
+            }
+        }
+    }
+
+    /// 0xFB 前缀的 GC 指令，目前只解码 struct.new 和 struct.new_default
+    pub struct GcArgs {
+        pub sub_opcode: u32,
+        pub type_idx: u32,
+    }
+
+    /// 0xFC 前缀指令，memory.init 和 data.drop 的 idx 为数据段索引，
+    /// table.copy 的 idx 和 src_idx 分别为目标表和源表的索引，其余指令为 0
+    pub struct PrefixedArgs {
+        pub sub_opcode: u8,
+        pub idx: u32,
+        pub src_idx: u32,
+    }
+
+    impl fmt::Display for PrefixedArgs {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.sub_opcode {
+                MEMORY_INIT | DATA_DROP => {
+                    write!(f, "{} {}", self.sub_opcode, self.idx)
+                }
+                TABLE_COPY => write!(
+                    f,
+                    "{} {} {}",
+                    self.sub_opcode, self.idx, self.src_idx
+                ),
+                _ => write!(f, "{}", self.sub_opcode),
+            }
+        }
+    }
+
+    /// call_indirect 的参数，引用类型提案允许通过非零表索引调用
+    pub struct CallIndirectArgs {
+        pub type_idx: u32,
+        pub table_idx: u32,
+    }
+
+    impl fmt::Display for CallIndirectArgs {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "type={} table={}", self.type_idx, self.table_idx)
+        }
+    }
+
     type LabelIdx = u32;
     pub struct BrTableArgs {
         pub labels: Vec<LabelIdx>,
@@ -58,4 +207,43 @@ pub mod instruction {
             write!(f, "labels: {:?}, default: {}", self.labels, self.default)
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_mem_arg_display() {
+            // i32.load align=4 offset=4，二进制中 align 编码为 2
+            let instr = Instruction::with_args(
+                OpCode::I32Load,
+                MemArg {
+                    align: 2,
+                    offset: 4,
+                },
+            );
+            let mem_arg = instr.args.as_ref().unwrap();
+            let mem_arg = mem_arg.downcast_ref::<MemArg>().unwrap();
+            assert_eq!(
+                format!("{} {}", instr.get_op_name(), mem_arg),
+                "I32Load offset=4 align=4"
+            );
+            let mem_arg = MemArg {
+                align: 64,
+                offset: 0,
+            };
+            assert_eq!(mem_arg.align_bytes(), None);
+            assert_eq!(mem_arg.to_string(), "offset=0 align=2^64");
+        }
+
+        #[test]
+        fn test_args_as() {
+            let mut instr = Instruction::with_index(OpCode::LocalGet, 3);
+            assert_eq!(instr.args_as::<u32>(), Some(&3));
+            assert!(instr.args_as::<i32>().is_none());
+            instr.set_args(5u32);
+            assert_eq!(instr.args_as::<u32>(), Some(&5));
+            assert!(Instruction::new(OpCode::Nop).args_as::<u32>().is_none());
+        }
+    }
 }
@@ -1,24 +1,45 @@
+pub mod cache;
+pub mod diff;
 pub mod instruction;
 pub mod module;
 pub mod opcodes;
+pub use cache::cache::CACHE_VERSION;
+pub use diff::diff::Difference;
 pub use instruction::instruction::BlockArgs;
+pub use instruction::instruction::BrArgs;
 pub use instruction::instruction::BrTableArgs;
+pub use instruction::instruction::CallIndirectArgs;
+pub use instruction::instruction::GcArgs;
+pub use instruction::instruction::HeapType;
 pub use instruction::instruction::IfArgs;
 pub use instruction::instruction::Instruction;
 pub use instruction::instruction::MemArg;
+pub use instruction::instruction::PrefixedArgs;
 pub use instruction::instruction::{
     BlockType, BLOCK_TYPE_EMPTY, BLOCK_TYPE_F32, BLOCK_TYPE_F64,
     BLOCK_TYPE_I32, BLOCK_TYPE_I64,
 };
 pub use module::module::Code;
+pub use module::module::CustomSec;
+pub use module::module::ElemMode;
+pub use module::module::Export;
 pub use module::module::ExportDesc;
 pub use module::module::Expr;
+pub use module::module::FuncIdx;
 pub use module::module::ImportDesc;
+pub use module::module::Locals;
+pub use module::module::MemIdx;
 pub use module::module::MemType;
 pub use module::module::Module;
+pub use module::module::ModuleMetadata;
 pub use module::module::TableType;
 pub use module::module::WasmReader;
+pub use module::module::MAX_NESTING_DEPTH;
 pub use module::module::{
     FuncType, GlobalType, Limits, ValType, MAX_PAGE_COUNT, PAGE_SIZE,
 };
 pub use opcodes::opcodes::OpCode;
+pub use opcodes::opcodes::{
+    DATA_DROP, MEMORY_COPY, MEMORY_FILL, MEMORY_INIT, TABLE_COPY,
+};
+pub use opcodes::opcodes::{STRUCT_NEW, STRUCT_NEW_DEFAULT};
@@ -1,32 +1,42 @@
 pub mod module {
+    use crate::error::Error;
+    use crate::module::BrArgs;
     use crate::module::BrTableArgs;
+    use crate::module::CallIndirectArgs;
+    use crate::module::GcArgs;
+    use crate::module::HeapType;
     use crate::module::IfArgs;
     use crate::module::Instruction;
     use crate::module::MemArg;
     use crate::module::OpCode;
+    use crate::module::PrefixedArgs;
     use crate::module::{
         BlockArgs, BlockType, BLOCK_TYPE_EMPTY, BLOCK_TYPE_F32, BLOCK_TYPE_F64,
         BLOCK_TYPE_I32, BLOCK_TYPE_I64,
     };
+    use crate::module::{
+        DATA_DROP, MEMORY_COPY, MEMORY_FILL, MEMORY_INIT, TABLE_COPY,
+    };
+    use crate::module::{STRUCT_NEW, STRUCT_NEW_DEFAULT};
     use num_enum::TryFromPrimitive;
     use std::any::Any;
+    use std::convert::TryInto;
     use std::fmt;
     use std::fs::File;
     use std::io::prelude::*;
     use std::path::Path;
     use std::rc::Rc;
-    use std::{convert::TryInto, panic};
 
     type TypeIdx = u32;
-    type FuncIdx = u32; // 函数索引空间由外部函数和内部函数共同构成
+    pub type FuncIdx = u32; // 函数索引空间由外部函数和内部函数共同构成
     type TableIdx = u32; // 目前wasm规范只能导入或者定义一个表，所以唯一有效索引只能是 0
-    type MemIdx = u32; // 和表索引空间一样，唯一有效索引只能是 0
+    pub type MemIdx = u32; // 和表索引空间一样，唯一有效索引只能是 0
     type GlobalIdx = u32; // 同样，全局变量索引空间由外部全局变量和内部全局变量共同构成
     type LocalIdx = u32; // 局部变量索引由函数的参数和局部变量构成
     type LableIdx = u32;
 
     // WASM 中只有4种值类型，i32、i64、f32、f64 和一种函数类型
-    #[derive(TryFromPrimitive, Clone, Copy)]
+    #[derive(TryFromPrimitive, Clone, Copy, PartialEq, Eq, Hash, Debug)]
     #[repr(u8)]
     pub enum ValType {
         I32 = 0x7F,
@@ -48,7 +58,7 @@ pub mod module {
         }
     }
 
-    #[derive(Clone, Default)]
+    #[derive(Clone, Default, PartialEq, Eq, Hash)]
     pub struct FuncType {
         pub params_types: Vec<ValType>, // 函数的参数
         pub result_types: Vec<ValType>, // 函数的返回值
@@ -87,15 +97,30 @@ pub mod module {
     }
 
     // Limits 类型用于描述表的元素数量或者内存页数的上下限
-    #[derive(Clone, Copy)]
+    #[derive(Clone, Copy, Default)]
     pub struct Limits {
         pub min: usize,
         pub max: Option<usize>,
+        pub page_size_log2: Option<u32>, // 自定义页大小提案中内存的页大小为 2^page_size_log2 字节，None 为默认的 64KiB
+    }
+
+    impl Limits {
+        /// 内存每页的字节数
+        pub fn page_size(&self) -> usize {
+            match self.page_size_log2 {
+                Some(log2) => 1 << log2,
+                None => PAGE_SIZE,
+            }
+        }
     }
 
     impl fmt::Display for Limits {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{{ min: {}, max: {} }}", self.min, self.max.unwrap_or(0))
+            write!(f, "{{ min: {}, max: {}", self.min, self.max.unwrap_or(0))?;
+            if let Some(log2) = self.page_size_log2 {
+                write!(f, ", page_size: {}", 1u64 << log2)?;
+            }
+            write!(f, " }}")
         }
     }
 
@@ -176,9 +201,18 @@ pub mod module {
         Global(u32),
     }
 
+    /// 元素段的模式，只有活动段在实例化时初始化表
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum ElemMode {
+        Active,
+        Passive,     // 被动段，由 table.init 使用
+        Declarative, // 声明段，只用于声明 ref.func 可以引用的函数
+    }
+
     pub struct Elem {
-        pub table: TableIdx, // 表索引（初始化哪张表），由于目前标准规定模块最多只能导入或者定义一张表，因此表索引必须为零
-        pub offset: Expr,    // 表内偏移量（从哪里开始初始化）
+        pub mode: ElemMode,
+        pub table: TableIdx, // 表索引（初始化哪张表），引用类型提案允许多张表
+        pub offset: Expr,    // 表内偏移量（从哪里开始初始化），非活动段为空
         pub init: Vec<FuncIdx>, // 函数索引列表（给定的初始数据）
     }
 
@@ -215,9 +249,19 @@ pub mod module {
         pub bytes: Vec<u8>,
     }
 
+    impl CustomSec {
+        /// 是否为编译器生成的 DWARF 调试信息段，如 .debug_info、.debug_line
+        pub fn is_debug(&self) -> bool {
+            self.name.starts_with(".debug_")
+        }
+    }
+
     const MAGIC_NUMBER: u32 = 0x6d736100; // "\0asm"
     const VERSION: u32 = 0x00000001; // 1
 
+    const LIMITS_HAS_MAX: u8 = 0x01;
+    const LIMITS_HAS_PAGE_SIZE: u8 = 0x08;
+
     const SEC_CUSTOM_ID: u8 = 0x00;
     const SEC_TYPE_ID: u8 = 0x01;
     const SEC_IMPORT_ID: u8 = 0x02;
@@ -231,6 +275,25 @@ pub mod module {
     const SEC_CODE_ID: u8 = 0x0a;
     const SEC_DATA_ID: u8 = 0x0b;
 
+    fn sec_name(sec_id: u8) -> &'static str {
+        match sec_id {
+            SEC_CUSTOM_ID => "custom",
+            SEC_TYPE_ID => "type",
+            SEC_IMPORT_ID => "import",
+            SEC_FUNC_ID => "function",
+            SEC_TABLE_ID => "table",
+            SEC_MEM_ID => "memory",
+            SEC_GLOBAL_ID => "global",
+            SEC_EXPORT_ID => "export",
+            SEC_START_ID => "start",
+            SEC_ELEM_ID => "element",
+            SEC_CODE_ID => "code",
+            SEC_DATA_ID => "data",
+            _ => "unknown",
+        }
+    }
+
+    #[derive(Default)]
     pub struct Module {
         pub magic: u32,                 // magic number
         pub version: u32,               // version
@@ -246,6 +309,21 @@ pub mod module {
         pub elem_sec: Vec<Elem>, // 元素段，存放表初始化数据
         pub code_sec: Vec<Code>, // 代码段，存放函数的字节码以及对应的局部变量信息
         pub data_sec: Vec<Data>, // 数据段，存放内存初始化数据
+        pub original: Option<Vec<u8>>, // 记录偏移解码时保留的原始字节
+        pub instr_ranges: Vec<Vec<(usize, usize)>>, // 记录偏移解码时每个函数体中按先序排列的指令范围
+    }
+
+    /// 模块的元数据，不包含函数体、元素段和数据段
+    pub struct ModuleMetadata {
+        pub type_sec: Vec<FuncType>,
+        pub import_sec: Vec<Import>,
+        pub func_sec: Vec<TypeIdx>,
+        pub table_sec: Vec<TableType>,
+        pub mem_sec: Vec<MemType>,
+        pub global_sec: Vec<Global>,
+        pub export_sec: Vec<Export>,
+        pub start_sec: Option<FuncIdx>,
+        pub skipped_bytes: usize, // 跳过未解码的段内容的总字节数
     }
 
     impl Module {
@@ -274,501 +352,920 @@ pub mod module {
                 _ => self.type_sec[block_type as usize].clone(),
             }
         }
+
+        /// DWARF 调试信息段，按在模块中出现的顺序排列
+        pub fn debug_sections(&self) -> Vec<&CustomSec> {
+            self.custom_sec.iter().filter(|cs| cs.is_debug()).collect()
+        }
+
+        /// 解析 target_features 自定义段：特性数量之后是若干 (前缀, 特性名)，
+        /// 前缀 '+' 表示使用了该特性，'-' 表示不能使用，'=' 表示链接的所有模块都必须使用
+        /// 段内容不合法时返回 Error::Decode
+        pub fn parse_target_features(
+            sec: &CustomSec,
+        ) -> Result<Vec<(char, String)>, Error> {
+            let mut reader = WasmReader::new(&sec.bytes);
+            let n = reader.read_var_u32()?;
+            (0..n)
+                .map(|_| {
+                    let prefix = reader.read_byte()? as char;
+                    if !matches!(prefix, '+' | '-' | '=') {
+                        return Err(Error::Decode(format!(
+                            "invalid target feature prefix {:?}",
+                            prefix
+                        )));
+                    }
+                    Ok((prefix, reader.read_name()?))
+                })
+                .collect()
+        }
+
+        /// 模块的 target_features 段中声明的特性，没有该段时为空
+        pub fn target_features(&self) -> Result<Vec<(char, String)>, Error> {
+            match self
+                .custom_sec
+                .iter()
+                .find(|cs| cs.name == "target_features")
+            {
+                Some(sec) => Module::parse_target_features(sec),
+                None => Ok(vec![]),
+            }
+        }
+
+        /// 函数名，优先取 name 段中函数名子段（id 1）记录的名字，其次取导出名。
+        /// name 段只用于调试，内容不合法时停止解析，同样退回到导出名
+        pub fn func_name(&self, func_idx: FuncIdx) -> Option<String> {
+            let name_sec = self.custom_sec.iter().find(|cs| cs.name == "name");
+            if let Some(sec) = name_sec {
+                if let Ok(Some(name)) = Module::read_func_name(sec, func_idx) {
+                    return Some(name);
+                }
+            }
+            self.export_sec.iter().find_map(|exp| match exp.desc {
+                ExportDesc::Func(idx) if idx == func_idx => {
+                    Some(exp.name.clone())
+                }
+                _ => None,
+            })
+        }
+
+        fn read_func_name(
+            sec: &CustomSec,
+            func_idx: FuncIdx,
+        ) -> Result<Option<String>, Error> {
+            let mut reader = WasmReader::new(&sec.bytes);
+            while reader.remaining() > 0 {
+                let id = reader.read_byte()?;
+                let payload = reader.read_bytes()?;
+                if id != 1 {
+                    continue;
+                }
+                let mut names = WasmReader::new(&payload);
+                for _ in 0..names.read_var_u32()? {
+                    let idx = names.read_var_u32()?;
+                    let name = names.read_name()?;
+                    if idx == func_idx {
+                        return Ok(Some(name));
+                    }
+                }
+            }
+            Ok(None)
+        }
+
+        /// 解码前的原始字节，只有 decode_bytes_with_offsets 会保留
+        pub fn original_bytes(&self) -> Option<&[u8]> {
+            self.original.as_deref()
+        }
+
+        /// 代码段第 code_idx 个函数体中第 flat_pc 条指令在原始字节中的范围 [start, end)，
+        /// 块指令包含到对应的 end。flat_pc 是指令在函数体先序展开后的位置，end 和 else 不计入。
+        /// 只有 decode_bytes_with_offsets 会记录
+        pub fn encoded_range(
+            &self,
+            code_idx: usize,
+            flat_pc: usize,
+        ) -> Option<(usize, usize)> {
+            self.instr_ranges.get(code_idx)?.get(flat_pc).copied()
+        }
+
+        // 不依赖导入就能确定的偏移量
+        fn static_offset(&self, expr: &Expr) -> Option<u32> {
+            let i32_const = |expr: &Expr| match expr.as_slice() {
+                [instr] if instr.opcode == OpCode::I32Const => {
+                    Some(*instr.args_as::<i32>()? as u32)
+                }
+                _ => None,
+            };
+            match expr.as_slice() {
+                [instr] if instr.opcode == OpCode::GlobalGet => {
+                    // 全局变量索引空间中导入的全局变量在前
+                    let imported = self
+                        .import_sec
+                        .iter()
+                        .filter(|imp| matches!(imp.desc, ImportDesc::Global(_)))
+                        .count();
+                    let idx = (*instr.args_as::<u32>()? as usize)
+                        .checked_sub(imported)?;
+                    let global = self.global_sec.get(idx)?;
+                    if global.global_type.mutable {
+                        return None;
+                    }
+                    i32_const(&global.init_expr)
+                }
+                _ => i32_const(expr),
+            }
+        }
+
+        /// 类似 strings 工具，从数据段中提取长度至少为 min_len 的可打印 ASCII 字符串，
+        /// 返回字符串在线性内存中的地址和内容。偏移量为 i32.const，或者 global.get 一个
+        /// 以 i32.const 初始化的模块内不可变全局变量时可以静态确定地址；偏移量依赖导入的
+        /// 全局变量等其他情况无法确定，这些数据段会被跳过。
+        /// min_len 为 0 时按 1 处理，不返回空字符串
+        pub fn extract_strings(&self, min_len: usize) -> Vec<(usize, String)> {
+            let min_len = min_len.max(1);
+            let mut result = vec![];
+            for data in &self.data_sec {
+                let base = match self.static_offset(&data.offset) {
+                    Some(base) => base as usize,
+                    None => continue,
+                };
+                let mut start = 0;
+                // 末尾追加一个不可打印字节，保证最后一段字符串也会被结算
+                for (i, &b) in data.init.iter().chain([0u8].iter()).enumerate()
+                {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        continue;
+                    }
+                    if i - start >= min_len {
+                        let s = &data.init[start..i];
+                        result.push((
+                            base + start,
+                            String::from_utf8_lossy(s).into_owned(),
+                        ));
+                    }
+                    start = i + 1;
+                }
+            }
+            result
+        }
     }
 
     // LEB128 无符号整数解码
-    fn decode_var_uint(data: &[u8]) -> (u64, usize) {
+    fn decode_var_uint(data: &[u8]) -> DecodeResult<(u64, usize)> {
         let mut result = 0u64;
         for (index, value) in data.iter().enumerate() {
+            // u64 最多编码为 10 个字节，再多会移位溢出
+            if index >= 10 {
+                break;
+            }
             result |= ((*value as u64) & 0x7f) << (index * 7);
             if value & 0x80 == 0 {
                 // 表示已经解码结束
-                return (result, index + 1);
+                return Ok((result, index + 1));
             }
         }
-        panic!("unexpected end of LEB128");
+        Err(Error::Decode("unexpected end of LEB128".to_string()))
     }
 
     // LEB128 有符号整数解码
     // size: 可以为32或者64，表示解码的整数的位数
-    fn decode_var_int(data: &[u8], size: usize) -> (i64, usize) {
+    fn decode_var_int(data: &[u8], size: usize) -> DecodeResult<(i64, usize)> {
         let mut result = 0i64;
         for (index, value) in data.iter().enumerate() {
+            if index >= 10 {
+                break;
+            }
             result |= ((*value as i64) & 0x7f) << (index * 7);
             if value & 0x80 == 0 {
                 // 如果符号位是 1 的话需要给符号位补 1
-                if (index * 7) < size && (*value & 0x40) != 0 {
+                if (index * 7) < size
+                    && (index + 1) * 7 < 64
+                    && (*value & 0x40) != 0
+                {
                     result |= -1 << ((index + 1) * 7);
                 }
-                return (result, index + 1);
+                return Ok((result, index + 1));
             }
         }
-        panic!("unexpected end of LEB128");
+        Err(Error::Decode("unexpected end of LEB128".to_string()))
     }
 
+    /// 解码时 block、loop、if 默认允许的最大嵌套深度，
+    /// 块是递归解码的，这个深度在 debug 构建 2 MB 的测试线程栈中也能解码完
+    pub const MAX_NESTING_DEPTH: usize = 256;
+
+    type DecodeResult<T> = Result<T, Error>;
+
     pub struct WasmReader<'a> {
         data: &'a [u8],
+        depth: usize,        // 当前块的嵌套深度
+        max_depth: usize,    // 超过后停止解码，避免深度嵌套耗尽本地栈
+        end: usize,          // data 末尾在原始输入中的偏移
+        track_offsets: bool, // 是否记录指令在原始输入中的范围
+        // 当前函数体中已读取指令的范围，按先序排列
+        ranges: Option<Vec<(usize, usize)>>,
+        // 已读取的每个函数体的指令范围
+        code_ranges: Vec<Vec<(usize, usize)>>,
     }
 
     impl<'a> WasmReader<'a> {
-        fn new(data: &'a [u8]) -> WasmReader {
-            WasmReader { data }
+        fn new(data: &'a [u8]) -> WasmReader<'a> {
+            WasmReader {
+                data,
+                depth: 0,
+                max_depth: MAX_NESTING_DEPTH,
+                end: data.len(),
+                track_offsets: false,
+                ranges: None,
+                code_ranges: Vec::new(),
+            }
         }
 
-        fn read_byte(&mut self) -> u8 {
-            let result = self.data[0];
-            self.data = &self.data[1..];
-            result
+        // 读取刚由 read_bytes 取出的子数据，继承解码选项和偏移
+        fn sub_reader<'b>(&self, data: &'b [u8]) -> WasmReader<'b> {
+            WasmReader {
+                data,
+                depth: 0,
+                max_depth: self.max_depth,
+                end: self.pos(),
+                track_offsets: self.track_offsets,
+                ranges: self.track_offsets.then(Vec::new),
+                code_ranges: Vec::new(),
+            }
         }
 
-        fn read_u32(&mut self) -> u32 {
-            let (u32_bytes, rest) = self.data.split_at(4);
-            self.data = rest;
-            u32::from_ne_bytes(u32_bytes.try_into().unwrap())
+        // 当前位置在原始输入中的偏移
+        fn pos(&self) -> usize {
+            self.end - self.data.len()
         }
 
-        fn read_f32(&mut self) -> f32 {
-            let (f32_bytes, rest) = self.data.split_at(4);
+        // 取出接下来的 n 个字节，不足时报错
+        fn take(&mut self, n: usize) -> DecodeResult<&'a [u8]> {
+            if n > self.remaining() {
+                return Err(Error::Decode(format!(
+                    "unexpected end: need {} bytes, {} remaining",
+                    n,
+                    self.remaining()
+                )));
+            }
+            let (bytes, rest) = self.data.split_at(n);
             self.data = rest;
-            f32::from_ne_bytes(f32_bytes.try_into().unwrap())
+            Ok(bytes)
         }
 
-        fn read_f64(&mut self) -> f64 {
-            let (f64_bytes, rest) = self.data.split_at(8);
-            self.data = rest;
-            f64::from_ne_bytes(f64_bytes.try_into().unwrap())
+        fn read_byte(&mut self) -> DecodeResult<u8> {
+            Ok(self.take(1)?[0])
         }
 
-        fn read_var_u32(&mut self) -> u32 {
-            let (n, w) = decode_var_uint(self.data);
-            self.data = &self.data[w..];
-            n as u32
+        fn read_u32(&mut self) -> DecodeResult<u32> {
+            let u32_bytes = self.take(4)?;
+            Ok(u32::from_ne_bytes(u32_bytes.try_into().unwrap()))
+        }
+
+        fn read_f32(&mut self) -> DecodeResult<f32> {
+            let f32_bytes = self.take(4)?;
+            Ok(f32::from_ne_bytes(f32_bytes.try_into().unwrap()))
+        }
+
+        fn read_f64(&mut self) -> DecodeResult<f64> {
+            let f64_bytes = self.take(8)?;
+            Ok(f64::from_ne_bytes(f64_bytes.try_into().unwrap()))
         }
 
-        fn read_var_i32(&mut self) -> i32 {
-            let (n, w) = decode_var_int(self.data, 32);
+        fn read_var_u32(&mut self) -> DecodeResult<u32> {
+            let (n, w) = decode_var_uint(self.data)?;
             self.data = &self.data[w..];
-            n as i32
+            Ok(n as u32)
         }
 
-        fn read_var_i64(&mut self) -> i64 {
-            let (n, w) = decode_var_int(self.data, 64);
+        fn read_var_i32(&mut self) -> DecodeResult<i32> {
+            let (n, w) = decode_var_int(self.data, 32)?;
             self.data = &self.data[w..];
-            n
+            Ok(n as i32)
         }
 
-        fn read_bytes(&mut self) -> Vec<u8> {
-            let len = self.read_var_u32();
-            let (bytes, rest) = self.data.split_at(len as usize);
-            self.data = rest;
-            bytes.to_vec()
+        fn read_var_i64(&mut self) -> DecodeResult<i64> {
+            let (n, w) = decode_var_int(self.data, 64)?;
+            self.data = &self.data[w..];
+            Ok(n)
+        }
+
+        fn read_bytes(&mut self) -> DecodeResult<Vec<u8>> {
+            let len = self.read_var_u32()?;
+            // 长度来自输入，必须先检查，否则 split_at 会越界
+            if len as usize > self.remaining() {
+                return Err(Error::Decode(format!(
+                    "byte length {} exceeds remaining {} bytes",
+                    len,
+                    self.remaining()
+                )));
+            }
+            Ok(self.take(len as usize)?.to_vec())
         }
 
-        fn read_name(&mut self) -> String {
-            let bytes = self.read_bytes();
-            String::from_utf8(bytes).unwrap()
+        fn read_name(&mut self) -> DecodeResult<String> {
+            let bytes = self.read_bytes()?;
+            String::from_utf8(bytes)
+                .map_err(|e| Error::Decode(format!("malformed name: {}", e)))
         }
 
         fn remaining(&self) -> usize {
             self.data.len()
         }
 
-        fn read_val_type(&mut self) -> ValType {
-            let val_type: ValType = self.read_byte().try_into().unwrap();
-            val_type
+        // 先读元素个数，再逐个读取元素。个数来自输入，预分配的容量不超过剩余字节数
+        fn read_vec<T>(
+            &mut self,
+            read: fn(&mut Self) -> DecodeResult<T>,
+        ) -> DecodeResult<Vec<T>> {
+            let len = self.read_var_u32()?;
+            let mut result =
+                Vec::with_capacity((len as usize).min(self.remaining()));
+            for _index in 0..len {
+                result.push(read(self)?);
+            }
+            Ok(result)
         }
 
-        fn read_val_types(&mut self) -> Vec<ValType> {
-            let mut result = Vec::with_capacity(self.read_var_u32() as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_val_type());
-            }
-            result
+        fn read_val_type(&mut self) -> DecodeResult<ValType> {
+            let b = self.read_byte()?;
+            ValType::try_from(b).map_err(|_| {
+                Error::Decode(format!("invalid value type: {:#04x}", b))
+            })
         }
 
-        fn read_func_type(&mut self) -> FuncType {
-            let tag = self.read_byte();
-            if tag != 0x60 {
-                panic!("invalid func type tag");
-            }
-            FuncType {
-                params_types: self.read_val_types(),
-                result_types: self.read_val_types(),
-            }
+        fn read_val_types(&mut self) -> DecodeResult<Vec<ValType>> {
+            self.read_vec(WasmReader::read_val_type)
         }
 
-        fn read_import_desc(&mut self) -> ImportDesc {
-            let tag: ImportTag = self.read_byte().try_into().unwrap();
-            match tag {
-                ImportTag::Func => ImportDesc::Func(self.read_var_u32()),
-                ImportTag::Table => ImportDesc::Table(self.read_table_type()),
-                ImportTag::Mem => ImportDesc::Mem(self.read_limits()),
+        fn read_func_type(&mut self) -> DecodeResult<FuncType> {
+            let tag = self.read_byte()?;
+            if tag != 0x60 {
+                return Err(Error::Decode("invalid func type tag".to_string()));
+            }
+            Ok(FuncType {
+                params_types: self.read_val_types()?,
+                result_types: self.read_val_types()?,
+            })
+        }
+
+        fn read_import_desc(&mut self) -> DecodeResult<ImportDesc> {
+            let b = self.read_byte()?;
+            let tag = ImportTag::try_from(b).map_err(|_| {
+                Error::Decode(format!("invalid import desc tag: {}", b))
+            })?;
+            Ok(match tag {
+                ImportTag::Func => ImportDesc::Func(self.read_var_u32()?),
+                ImportTag::Table => ImportDesc::Table(self.read_table_type()?),
+                ImportTag::Mem => ImportDesc::Mem(self.read_limits()?),
                 ImportTag::Global => {
-                    ImportDesc::Global(self.read_global_type())
+                    ImportDesc::Global(self.read_global_type()?)
                 }
-            }
+            })
         }
 
-        fn read_block_type(&mut self) -> BlockType {
-            let block_type = self.read_var_i32();
+        fn read_block_type(&mut self) -> DecodeResult<BlockType> {
+            let block_type = self.read_var_i32()?;
             if block_type < 0 {
                 match block_type {
                     BLOCK_TYPE_I32 | BLOCK_TYPE_I64 | BLOCK_TYPE_F32
                     | BLOCK_TYPE_F64 | BLOCK_TYPE_EMPTY => (),
-                    _ => panic!("malformed block type: {}", block_type),
+                    _ => {
+                        return Err(Error::Decode(format!(
+                            "malformed block type: {}",
+                            block_type
+                        )))
+                    }
                 }
             }
-            block_type
+            Ok(block_type)
         }
 
-        fn read_block_args(&mut self) -> BlockArgs {
-            let block_type = self.read_block_type();
-            let (instructions, end) = self.read_instructions();
+        // 块的指令序列递归解码，进入前检查嵌套深度
+        fn enter_block(&mut self) -> DecodeResult<()> {
+            self.depth += 1;
+            if self.depth > self.max_depth {
+                return Err(Error::Decode(format!(
+                    "block nesting exceeds max depth {}",
+                    self.max_depth
+                )));
+            }
+            Ok(())
+        }
+
+        fn read_block_args(&mut self) -> DecodeResult<BlockArgs> {
+            let block_type = self.read_block_type()?;
+            self.enter_block()?;
+            let (instructions, end) = self.read_instructions()?;
+            self.depth -= 1;
             if end != OpCode::End {
-                panic!("invalid block end: {}", end);
+                return Err(Error::Decode(format!(
+                    "invalid block end: {}",
+                    end
+                )));
             }
-            BlockArgs {
+            Ok(BlockArgs {
                 block_type,
                 instructions,
-            }
+            })
         }
 
-        fn read_if_args(&mut self) -> IfArgs {
-            let block_type = self.read_block_type();
-            let (instructions_1, end) = self.read_instructions();
+        fn read_if_args(&mut self) -> DecodeResult<IfArgs> {
+            let block_type = self.read_block_type()?;
+            self.enter_block()?;
+            let (instructions_1, end) = self.read_instructions()?;
             let mut instructions_2 = Expr::new();
             if end == OpCode::Else {
-                let (instructions, end) = self.read_instructions();
+                let (instructions, end) = self.read_instructions()?;
                 if end != OpCode::End {
-                    panic!("invalid block end: {}", end);
+                    return Err(Error::Decode(format!(
+                        "invalid block end: {}",
+                        end
+                    )));
                 }
                 instructions_2 = instructions;
             }
-            IfArgs {
+            self.depth -= 1;
+            Ok(IfArgs {
                 block_type,
                 instructions_1,
                 instructions_2,
-            }
+            })
         }
 
-        fn read_br_table_args(&mut self) -> BrTableArgs {
-            BrTableArgs {
-                labels: self.read_indices(),
-                default: self.read_var_u32(),
-            }
+        // br 和 br_if 的参数是标签索引
+        fn read_br_args(&mut self) -> DecodeResult<BrArgs> {
+            self.read_var_u32()
+        }
+
+        fn read_br_table_args(&mut self) -> DecodeResult<BrTableArgs> {
+            Ok(BrTableArgs {
+                labels: self.read_indices()?,
+                default: self.read_var_u32()?,
+            })
         }
 
-        fn read_zero(&mut self) -> u8 {
-            let b = self.read_byte();
+        fn read_zero(&mut self) -> DecodeResult<u8> {
+            let b = self.read_byte()?;
             if b != 0 {
-                panic!("zero flag expected, got {}", b);
+                return Err(Error::Decode(format!(
+                    "zero flag expected, got {}",
+                    b
+                )));
+            }
+            Ok(b)
+        }
+
+        // 0xFC 前缀指令的参数为子操作码，部分指令之后还跟着数据段、内存或表的索引
+        fn read_prefixed_args(&mut self) -> DecodeResult<PrefixedArgs> {
+            let sub_opcode = self.read_byte()?;
+            let mut idx = 0;
+            let mut src_idx = 0;
+            match sub_opcode {
+                MEMORY_INIT => {
+                    idx = self.read_var_u32()?;
+                    self.read_zero()?;
+                }
+                DATA_DROP => idx = self.read_var_u32()?,
+                // 目标内存和源内存的索引，目前都只能为 0
+                MEMORY_COPY => {
+                    self.read_zero()?;
+                    self.read_zero()?;
+                }
+                MEMORY_FILL => {
+                    self.read_zero()?;
+                }
+                TABLE_COPY => {
+                    idx = self.read_var_u32()?;
+                    src_idx = self.read_var_u32()?;
+                }
+                _ => {}
+            }
+            Ok(PrefixedArgs {
+                sub_opcode,
+                idx,
+                src_idx,
+            })
+        }
+
+        // 堆类型按 s33 编码，抽象堆类型为单字节的负数
+        fn read_heap_type(&mut self) -> DecodeResult<HeapType> {
+            let heap_type = self.read_var_i64()?;
+            Ok(match heap_type {
+                -0x10 => HeapType::Func,     // 0x70
+                -0x11 => HeapType::Extern,   // 0x6F
+                -0x12 => HeapType::Any,      // 0x6E
+                -0x13 => HeapType::Eq,       // 0x6D
+                -0x14 => HeapType::I31,      // 0x6C
+                -0x15 => HeapType::Struct,   // 0x6B
+                -0x16 => HeapType::Array,    // 0x6A
+                -0x0F => HeapType::None,     // 0x71
+                -0x0E => HeapType::NoExtern, // 0x72
+                -0x0D => HeapType::NoFunc,   // 0x73
+                idx if idx >= 0 && idx <= u32::MAX as i64 => {
+                    HeapType::Type(idx as u32)
+                }
+                _ => {
+                    return Err(Error::Decode(format!(
+                        "malformed heap type: {}",
+                        heap_type
+                    )))
+                }
+            })
+        }
+
+        fn read_gc_args(&mut self) -> DecodeResult<GcArgs> {
+            let sub_opcode = self.read_var_u32()?;
+            match sub_opcode {
+                STRUCT_NEW | STRUCT_NEW_DEFAULT => Ok(GcArgs {
+                    sub_opcode,
+                    type_idx: self.read_var_u32()?,
+                }),
+                _ => Err(Error::Decode(format!(
+                    "unsupported gc instruction: {}",
+                    sub_opcode
+                ))),
             }
-            b
         }
 
-        fn read_call_indirect_args(&mut self) -> u32 {
-            let type_idx = self.read_var_u32();
-            self.read_zero();
-            type_idx
+        fn read_call_indirect_args(
+            &mut self,
+        ) -> DecodeResult<CallIndirectArgs> {
+            Ok(CallIndirectArgs {
+                type_idx: self.read_var_u32()?,
+                table_idx: self.read_var_u32()?,
+            })
         }
 
-        fn read_mem_arg(&mut self) -> MemArg {
-            MemArg {
-                align: self.read_var_u32(),
-                offset: self.read_var_u32(),
-            }
+        fn read_mem_arg(&mut self) -> DecodeResult<MemArg> {
+            Ok(MemArg {
+                align: self.read_var_u32()?,
+                offset: self.read_var_u32()?,
+            })
         }
 
-        fn read_args(&mut self, opcode: &OpCode) -> Option<Rc<dyn Any>> {
-            match opcode {
+        fn read_args(
+            &mut self,
+            opcode: &OpCode,
+        ) -> DecodeResult<Option<Rc<dyn Any>>> {
+            let args: Rc<dyn Any> = match opcode {
                 OpCode::Block | OpCode::Loop => {
-                    Some(Rc::new(self.read_block_args()))
+                    Rc::new(self.read_block_args()?)
                 }
-                OpCode::If => Some(Rc::new(self.read_if_args())),
-                OpCode::Br | OpCode::BrIf => Some(Rc::new(self.read_var_u32())), // label index
-                OpCode::BrTable => Some(Rc::new(self.read_br_table_args())),
-                OpCode::Call => Some(Rc::new(self.read_var_u32())), // function index
+                OpCode::If => Rc::new(self.read_if_args()?),
+                OpCode::Br | OpCode::BrIf => Rc::new(self.read_br_args()?),
+                OpCode::BrTable => Rc::new(self.read_br_table_args()?),
+                OpCode::Call => Rc::new(self.read_var_u32()?), // function index
                 OpCode::CallIndirect => {
-                    Some(Rc::new(self.read_call_indirect_args()))
+                    Rc::new(self.read_call_indirect_args()?)
                 }
                 OpCode::LocalGet | OpCode::LocalSet | OpCode::LocalTee => {
-                    Some(Rc::new(self.read_var_u32()))
+                    Rc::new(self.read_var_u32()?)
                 } // local index
                 OpCode::GlobalGet | OpCode::GlobalSet => {
-                    Some(Rc::new(self.read_var_u32()))
+                    Rc::new(self.read_var_u32()?)
                 } // global index
+                OpCode::TableGet | OpCode::TableSet => {
+                    Rc::new(self.read_var_u32()?)
+                } // table index
+                OpCode::RefFunc => Rc::new(self.read_var_u32()?), // function index
                 OpCode::MemorySize | OpCode::MemoryGrow => {
-                    Some(Rc::new(self.read_zero()))
+                    Rc::new(self.read_zero()?)
                 }
-                OpCode::I32Const => Some(Rc::new(self.read_var_i32())),
-                OpCode::I64Const => Some(Rc::new(self.read_var_i64())),
-                OpCode::F32Const => Some(Rc::new(self.read_f32())),
-                OpCode::F64Const => Some(Rc::new(self.read_f64())),
-                OpCode::TruncSat => Some(Rc::new(self.read_byte())),
+                OpCode::I32Const => Rc::new(self.read_var_i32()?),
+                OpCode::I64Const => Rc::new(self.read_var_i64()?),
+                OpCode::F32Const => Rc::new(self.read_f32()?),
+                OpCode::F64Const => Rc::new(self.read_f64()?),
+                OpCode::TruncSat => Rc::new(self.read_prefixed_args()?),
+                OpCode::RefNull => Rc::new(self.read_heap_type()?),
+                OpCode::Gc => Rc::new(self.read_gc_args()?),
                 _ => {
                     if *opcode >= OpCode::I32Load
                         && *opcode <= OpCode::I64Store32
                     {
-                        return Some(Rc::new(self.read_mem_arg()));
+                        Rc::new(self.read_mem_arg()?)
+                    } else {
+                        return Ok(None);
                     }
-                    None
                 }
+            };
+            Ok(Some(args))
+        }
+
+        fn read_instruction(&mut self) -> DecodeResult<Instruction> {
+            let start = self.pos();
+            let b = self.read_byte()?;
+            let opcode = OpCode::try_from(b).map_err(|_| {
+                Error::Decode(format!("invalid opcode: {:#04x}", b))
+            })?;
+            // 按先序记录：块指令先占位，读完块内的指令后再补上结束位置
+            let slot = match self.ranges.as_mut() {
+                Some(ranges)
+                    if opcode != OpCode::Else && opcode != OpCode::End =>
+                {
+                    ranges.push((start, start));
+                    Some(ranges.len() - 1)
+                }
+                _ => None,
+            };
+            let args = self.read_args(&opcode)?;
+            let end = self.pos();
+            if let (Some(ranges), Some(slot)) = (self.ranges.as_mut(), slot) {
+                ranges[slot].1 = end;
             }
+            Ok(Instruction { opcode, args })
         }
 
-        fn read_instruction(&mut self) -> Instruction {
-            let opcode: OpCode = self.read_byte().try_into().unwrap();
-            let args = self.read_args(&opcode);
-            Instruction { opcode, args }
-        }
-
-        fn read_instructions(&mut self) -> (Expr, OpCode) {
+        fn read_instructions(&mut self) -> DecodeResult<(Expr, OpCode)> {
             let mut instructions = Expr::new();
             loop {
-                let instr = self.read_instruction();
+                let instr = self.read_instruction()?;
                 if instr.opcode == OpCode::Else || instr.opcode == OpCode::End {
-                    return (instructions, instr.opcode);
+                    return Ok((instructions, instr.opcode));
                 }
                 instructions.push(instr);
             }
         }
 
-        fn read_expr(&mut self) -> Expr {
-            let (instrs, end) = self.read_instructions();
+        fn read_expr(&mut self) -> DecodeResult<Expr> {
+            let (instrs, end) = self.read_instructions()?;
             // 确保表达式以 end 结尾
             if end != OpCode::End {
-                panic!("invalid end of expression: {}", end);
+                return Err(Error::Decode(format!(
+                    "invalid end of expression: {}",
+                    end
+                )));
             }
-            instrs
+            Ok(instrs)
         }
 
-        fn read_locals(&mut self) -> Locals {
-            Locals {
-                n: self.read_var_u32(),
-                val_type: self.read_val_type(),
-            }
+        fn read_locals(&mut self) -> DecodeResult<Locals> {
+            Ok(Locals {
+                n: self.read_var_u32()?,
+                val_type: self.read_val_type()?,
+            })
         }
 
-        fn read_locals_vec(&mut self) -> Vec<Locals> {
-            let mut result = Vec::with_capacity(self.read_var_u32() as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_locals());
-            }
-            result
+        fn read_locals_vec(&mut self) -> DecodeResult<Vec<Locals>> {
+            self.read_vec(WasmReader::read_locals)
         }
 
-        fn read_code(&mut self) -> Code {
+        fn read_code(&mut self) -> DecodeResult<Code> {
             // 每个代码项的所有内容
-            let code_data = self.read_bytes();
-            let mut code_reader = WasmReader::new(&code_data);
+            let code_data = self.read_bytes()?;
+            let mut code_reader = self.sub_reader(&code_data);
             let code = Code {
-                locals: code_reader.read_locals_vec(),
-                expr: code_reader.read_expr(),
+                locals: code_reader.read_locals_vec()?,
+                expr: code_reader.read_expr()?,
             };
             if code.get_local_count() >= (u32::MAX as u64) {
-                panic!("local count overflow");
+                return Err(Error::Decode("local count overflow".to_string()));
+            }
+            // 函数体的 end 必须是代码项的最后一个字节
+            if code_reader.remaining() != 0 {
+                return Err(Error::Decode(format!(
+                    "unexpected {} bytes after function end",
+                    code_reader.remaining()
+                )));
             }
-            code
+            if let Some(ranges) = code_reader.ranges {
+                self.code_ranges.push(ranges);
+            }
+            Ok(code)
         }
 
-        fn read_custom_sec(&mut self) -> CustomSec {
-            let data = self.read_bytes();
+        fn read_custom_sec(&mut self) -> DecodeResult<CustomSec> {
+            // 段长度就是 read_bytes 读取的长度前缀，名字和负载都限制在段内
+            let data = self.read_bytes()?;
             let mut reader = WasmReader::new(&data);
-            CustomSec {
-                name: reader.read_name(),
+            Ok(CustomSec {
+                name: reader.read_name()?,
                 bytes: reader.data.to_vec(),
-            }
+            })
         }
 
-        fn read_import(&mut self) -> Import {
-            Import {
-                module_name: self.read_name(),
-                member_name: self.read_name(),
-                desc: self.read_import_desc(),
-            }
+        fn read_import(&mut self) -> DecodeResult<Import> {
+            Ok(Import {
+                module_name: self.read_name()?,
+                member_name: self.read_name()?,
+                desc: self.read_import_desc()?,
+            })
         }
 
-        fn read_limits(&mut self) -> Limits {
-            let tag = self.read_byte();
-            let min = self.read_var_u32();
-            let max = if tag == 0x00 {
+        fn read_limits(&mut self) -> DecodeResult<Limits> {
+            let tag = self.read_byte()?;
+            let min = self.read_var_u32()?;
+            let max = if tag & LIMITS_HAS_MAX == 0 {
                 None
             } else {
-                Some(self.read_var_u32() as usize)
+                Some(self.read_var_u32()? as usize)
+            };
+            // 自定义页大小提案：标志位 0x08 表示随后是页大小以 2 为底的对数
+            let page_size_log2 = if tag & LIMITS_HAS_PAGE_SIZE == 0 {
+                None
+            } else {
+                let log2 = self.read_var_u32()?;
+                if log2 > 16 {
+                    return Err(Error::Decode(format!(
+                        "invalid page size log2: {}",
+                        log2
+                    )));
+                }
+                Some(log2)
             };
-            Limits {
+            Ok(Limits {
                 min: min as usize,
                 max,
-            }
+                page_size_log2,
+            })
         }
 
-        fn read_table_type(&mut self) -> TableType {
-            let elem_type = self.read_val_type();
+        fn read_table_type(&mut self) -> DecodeResult<TableType> {
+            let elem_type = self.read_val_type()?;
             match elem_type {
-                ValType::FuncRef => TableType {
+                ValType::FuncRef => Ok(TableType {
                     elem_type,
-                    limits: self.read_limits(),
-                },
-                _ => panic!("invalid table element type"),
+                    limits: self.read_limits()?,
+                }),
+                _ => {
+                    Err(Error::Decode("invalid table element type".to_string()))
+                }
             }
         }
 
-        fn read_global_type(&mut self) -> GlobalType {
-            GlobalType {
-                val_type: self.read_val_type(),
-                mutable: self.read_byte() == 0x01,
-            }
+        fn read_global_type(&mut self) -> DecodeResult<GlobalType> {
+            Ok(GlobalType {
+                val_type: self.read_val_type()?,
+                mutable: self.read_byte()? == 0x01,
+            })
         }
 
-        fn read_export(&mut self) -> Export {
-            Export {
-                name: self.read_name(),
-                desc: self.read_export_desc(),
-            }
+        fn read_global(&mut self) -> DecodeResult<Global> {
+            Ok(Global {
+                global_type: self.read_global_type()?,
+                init_expr: self.read_expr()?,
+            })
+        }
+
+        fn read_export(&mut self) -> DecodeResult<Export> {
+            Ok(Export {
+                name: self.read_name()?,
+                desc: self.read_export_desc()?,
+            })
         }
 
-        fn read_export_desc(&mut self) -> ExportDesc {
-            let tag = self.read_byte();
-            let value = self.read_var_u32();
+        fn read_export_desc(&mut self) -> DecodeResult<ExportDesc> {
+            let tag = self.read_byte()?;
+            let value = self.read_var_u32()?;
             match tag {
-                0x00 => ExportDesc::Func(value),
-                0x01 => ExportDesc::Table(value),
-                0x02 => ExportDesc::Mem(value),
-                0x03 => ExportDesc::Global(value),
-                _ => panic!("invalid export desc tag: {}", tag),
+                0x00 => Ok(ExportDesc::Func(value)),
+                0x01 => Ok(ExportDesc::Table(value)),
+                0x02 => Ok(ExportDesc::Mem(value)),
+                0x03 => Ok(ExportDesc::Global(value)),
+                _ => Err(Error::Decode(format!(
+                    "invalid export desc tag: {}",
+                    tag
+                ))),
             }
         }
 
-        fn read_elem(&mut self) -> Elem {
-            Elem {
-                table: self.read_var_u32(),
-                offset: self.read_expr(),
-                init: self.read_indices(),
+        // 元素段以标志位开头：bit0 为被动或声明段，bit1 对活动段表示显式表索引、
+        // 对非活动段表示声明段，bit2 为表达式形式的元素
+        fn read_elem(&mut self) -> DecodeResult<Elem> {
+            let flags = self.read_var_u32()?;
+            if flags > 7 {
+                return Err(Error::Decode(format!(
+                    "unsupported element segment flags: {}",
+                    flags
+                )));
+            }
+            let mut table = 0;
+            let mut offset = Expr::new();
+            let mode = if flags & 0x01 == 0 {
+                if flags & 0x02 != 0 {
+                    table = self.read_var_u32()?;
+                }
+                offset = self.read_expr()?;
+                ElemMode::Active
+            } else if flags & 0x02 == 0 {
+                ElemMode::Passive
+            } else {
+                ElemMode::Declarative
+            };
+            if flags & 0x03 != 0 {
+                // 显式表索引和非活动段还有 elemkind（0x00）或引用类型（0x70），目前只支持函数引用
+                let kind = self.read_byte()?;
+                let expected = if flags & 0x04 != 0 { 0x70 } else { 0x00 };
+                if kind != expected {
+                    return Err(Error::Decode(format!(
+                        "unsupported element kind: {}",
+                        kind
+                    )));
+                }
             }
+            let init = if flags & 0x04 != 0 {
+                self.read_elem_exprs()?
+            } else {
+                self.read_indices()?
+            };
+            Ok(Elem {
+                mode,
+                table,
+                offset,
+                init,
+            })
         }
 
-        fn read_indices(&mut self) -> Vec<u32> {
-            let len = self.read_var_u32();
-            let mut result = Vec::with_capacity(len as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_var_u32());
-            }
-            result
+        fn read_elem_exprs(&mut self) -> DecodeResult<Vec<FuncIdx>> {
+            self.read_vec(WasmReader::read_elem_expr)
         }
 
-        fn read_data(&mut self) -> Data {
-            Data {
-                mem: self.read_var_u32(),
-                offset: self.read_expr(),
-                init: self.read_bytes(),
+        // 每个元素都是一个常量表达式，目前只支持 ref.func
+        fn read_elem_expr(&mut self) -> DecodeResult<FuncIdx> {
+            let expr = self.read_expr()?;
+            match expr.as_slice() {
+                [instr] if instr.opcode == OpCode::RefFunc => {
+                    let args = instr.args.as_ref().unwrap();
+                    Ok(*args.downcast_ref::<u32>().unwrap())
+                }
+                _ => Err(Error::Decode(
+                    "unsupported element expression".to_string(),
+                )),
             }
         }
 
+        fn read_indices(&mut self) -> DecodeResult<Vec<u32>> {
+            self.read_vec(WasmReader::read_var_u32)
+        }
+
+        fn read_data(&mut self) -> DecodeResult<Data> {
+            Ok(Data {
+                mem: self.read_var_u32()?,
+                offset: self.read_expr()?,
+                init: self.read_bytes()?,
+            })
+        }
+
         // 类型段解码
-        fn read_type_sec(&mut self) -> Vec<FuncType> {
-            let len = self.read_var_u32();
-            let mut result = Vec::with_capacity(len as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_func_type());
-            }
-            result
+        fn read_type_sec(&mut self) -> DecodeResult<Vec<FuncType>> {
+            self.read_vec(WasmReader::read_func_type)
         }
 
         // 导入段解码
-        fn read_import_sec(&mut self) -> Vec<Import> {
-            let mut result = Vec::with_capacity(self.read_var_u32() as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_import());
-            }
-            result
+        fn read_import_sec(&mut self) -> DecodeResult<Vec<Import>> {
+            self.read_vec(WasmReader::read_import)
         }
 
         // 函数段解码
-        fn read_func_sec(&mut self) -> Vec<FuncIdx> {
-            let mut result = Vec::with_capacity(self.read_var_u32() as usize);
-            for _index in 0..result.capacity() {
-                // 存储的是函数类型在类型段中的索引
-                result.push(self.read_var_u32());
-            }
-            result
+        fn read_func_sec(&mut self) -> DecodeResult<Vec<FuncIdx>> {
+            // 存储的是函数类型在类型段中的索引
+            self.read_indices()
         }
 
         // 表段解码
-        fn read_table_sec(&mut self) -> Vec<TableType> {
-            let mut result = Vec::with_capacity(self.read_var_u32() as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_table_type());
-            }
-            result
+        fn read_table_sec(&mut self) -> DecodeResult<Vec<TableType>> {
+            self.read_vec(WasmReader::read_table_type)
         }
 
         // 内存段解码
-        fn read_mem_sec(&mut self) -> Vec<MemType> {
-            let mut result = Vec::with_capacity(self.read_var_u32() as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_limits());
-            }
-            result
+        fn read_mem_sec(&mut self) -> DecodeResult<Vec<MemType>> {
+            self.read_vec(WasmReader::read_limits)
         }
 
         // Global 段解码
-        fn read_global_sec(&mut self) -> Vec<Global> {
-            let mut result = Vec::with_capacity(self.read_var_u32() as usize);
-            for _index in 0..result.capacity() {
-                result.push(Global {
-                    global_type: self.read_global_type(),
-                    init_expr: self.read_expr(),
-                });
-            }
-            result
+        fn read_global_sec(&mut self) -> DecodeResult<Vec<Global>> {
+            self.read_vec(WasmReader::read_global)
         }
 
         // 导出段解码
-        fn read_export_sec(&mut self) -> Vec<Export> {
-            let mut result = Vec::with_capacity(self.read_var_u32() as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_export());
-            }
-            result
+        fn read_export_sec(&mut self) -> DecodeResult<Vec<Export>> {
+            self.read_vec(WasmReader::read_export)
         }
 
         // 起始段解码
-        fn read_start_sec(&mut self) -> Option<FuncIdx> {
-            Some(self.read_var_u32())
+        fn read_start_sec(&mut self) -> DecodeResult<Option<FuncIdx>> {
+            Ok(Some(self.read_var_u32()?))
         }
 
         // 元素段解码
-        fn read_elem_sec(&mut self) -> Vec<Elem> {
-            let mut result = Vec::with_capacity(self.read_var_u32() as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_elem());
-            }
-            result
+        fn read_elem_sec(&mut self) -> DecodeResult<Vec<Elem>> {
+            self.read_vec(WasmReader::read_elem)
         }
 
         // 代码段解码
-        fn read_code_sec(&mut self) -> Vec<Code> {
-            let len = self.read_var_u32();
-            let mut result = Vec::with_capacity(len as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_code());
-            }
-            result
+        fn read_code_sec(&mut self) -> DecodeResult<Vec<Code>> {
+            self.read_vec(WasmReader::read_code)
         }
 
         // Data 段解码
-        fn read_data_sec(&mut self) -> Vec<Data> {
-            let mut result = Vec::with_capacity(self.read_var_u32() as usize);
-            for _index in 0..result.capacity() {
-                result.push(self.read_data());
-            }
-            result
+        fn read_data_sec(&mut self) -> DecodeResult<Vec<Data>> {
+            self.read_vec(WasmReader::read_data)
         }
 
-        fn read_module(&mut self) -> Module {
+        fn read_module(&mut self) -> DecodeResult<Module> {
             let mut module = Module {
-                magic: self.read_u32(),
-                version: self.read_u32(),
+                magic: self.read_u32()?,
+                version: self.read_u32()?,
                 custom_sec: Vec::new(),
                 type_sec: Vec::new(),
                 import_sec: Vec::new(),
@@ -781,61 +1278,154 @@ pub mod module {
                 elem_sec: Vec::new(),
                 code_sec: Vec::new(),
                 data_sec: Vec::new(),
+                original: None,
+                instr_ranges: Vec::new(),
             };
-            println!("magic: {:x}", module.magic);
-            println!("version: {}", module.version);
-            self.read_sections(&mut module);
-            module
+            self.read_sections(&mut module, false)?;
+            Ok(module)
         }
 
-        fn read_sections(&mut self, module: &mut Module) {
+        // skip_bodies 为 true 时按段长度跳过自定义段、元素段、代码段和数据段，返回跳过的字节数
+        fn read_sections(
+            &mut self,
+            module: &mut Module,
+            skip_bodies: bool,
+        ) -> DecodeResult<usize> {
             let mut prev_sec_id = 0u8;
+            let mut skipped = 0;
             while self.remaining() > 0 {
-                let sec_id = self.read_byte();
+                let sec_id = self.read_byte()?;
                 if sec_id == SEC_CUSTOM_ID {
-                    module.custom_sec.push(self.read_custom_sec());
+                    if skip_bodies {
+                        skipped += self.read_bytes()?.len();
+                    } else {
+                        module.custom_sec.push(self.read_custom_sec()?);
+                    }
                     continue;
                 }
                 if sec_id > SEC_DATA_ID || sec_id <= prev_sec_id {
-                    panic!("invalid section id");
+                    return Err(Error::Decode(format!(
+                        "invalid section id: {}",
+                        sec_id
+                    )));
                 }
                 prev_sec_id = sec_id;
-                let sec_len = self.read_var_u32();
+                let sec_len = self.read_var_u32()?;
+                // 声明的长度不能超过剩余字节数，否则段解析时会越界
+                if sec_len as usize > self.remaining() {
+                    return Err(Error::Decode(format!(
+                        "section {} length {} exceeds remaining {} bytes",
+                        sec_id,
+                        sec_len,
+                        self.remaining()
+                    )));
+                }
+                if skip_bodies
+                    && matches!(sec_id, SEC_ELEM_ID | SEC_CODE_ID | SEC_DATA_ID)
+                {
+                    self.data = &self.data[sec_len as usize..];
+                    skipped += sec_len as usize;
+                    continue;
+                }
                 let reamaining_before_read = self.remaining();
-                self.read_non_custom_sec(sec_id, module);
+                self.read_non_custom_sec(sec_id, module)?;
                 // 检查实际读取的长度和声明的 sec_len 是否一致
-                if reamaining_before_read != self.remaining() + sec_len as usize
-                {
-                    panic!("section length mismatch: {}", sec_id);
+                let consumed = reamaining_before_read - self.remaining();
+                if consumed != sec_len as usize {
+                    return Err(Error::Decode(format!(
+                        "{} section (id {}) length mismatch: declared {} bytes, consumed {} bytes",
+                        sec_name(sec_id),
+                        sec_id,
+                        sec_len,
+                        consumed
+                    )));
                 }
             }
+            Ok(skipped)
         }
 
-        fn read_non_custom_sec(&mut self, sec_id: u8, module: &mut Module) {
+        fn read_non_custom_sec(
+            &mut self,
+            sec_id: u8,
+            module: &mut Module,
+        ) -> DecodeResult<()> {
             match sec_id {
-                SEC_TYPE_ID => module.type_sec = self.read_type_sec(),
-                SEC_IMPORT_ID => module.import_sec = self.read_import_sec(),
-                SEC_FUNC_ID => module.func_sec = self.read_func_sec(),
-                SEC_TABLE_ID => module.table_sec = self.read_table_sec(),
-                SEC_MEM_ID => module.mem_sec = self.read_mem_sec(),
-                SEC_GLOBAL_ID => module.global_sec = self.read_global_sec(),
-                SEC_EXPORT_ID => module.export_sec = self.read_export_sec(),
-                SEC_START_ID => module.start_sec = self.read_start_sec(),
-                SEC_ELEM_ID => module.elem_sec = self.read_elem_sec(),
-                SEC_CODE_ID => module.code_sec = self.read_code_sec(),
-                SEC_DATA_ID => module.data_sec = self.read_data_sec(),
-                _ => panic!("unknown section id: {}", sec_id),
+                SEC_TYPE_ID => module.type_sec = self.read_type_sec()?,
+                SEC_IMPORT_ID => module.import_sec = self.read_import_sec()?,
+                SEC_FUNC_ID => module.func_sec = self.read_func_sec()?,
+                SEC_TABLE_ID => module.table_sec = self.read_table_sec()?,
+                SEC_MEM_ID => module.mem_sec = self.read_mem_sec()?,
+                SEC_GLOBAL_ID => module.global_sec = self.read_global_sec()?,
+                SEC_EXPORT_ID => module.export_sec = self.read_export_sec()?,
+                SEC_START_ID => module.start_sec = self.read_start_sec()?,
+                SEC_ELEM_ID => module.elem_sec = self.read_elem_sec()?,
+                SEC_CODE_ID => module.code_sec = self.read_code_sec()?,
+                SEC_DATA_ID => module.data_sec = self.read_data_sec()?,
+                _ => {
+                    return Err(Error::Decode(format!(
+                        "unknown section id: {}",
+                        sec_id
+                    )))
+                }
             }
+            Ok(())
         }
 
         pub fn decode_file<T: AsRef<Path>>(
             file_name: T,
-        ) -> std::io::Result<Module> {
+        ) -> Result<Module, Error> {
             let mut file = File::open(file_name.as_ref())?;
             let mut buf = Vec::new();
             file.read_to_end(&mut buf)?;
-            let mut wasm_reader = WasmReader::new(&buf);
-            Ok(wasm_reader.read_module())
+            WasmReader::decode_bytes(&buf)
+        }
+
+        pub fn decode_bytes(data: &[u8]) -> Result<Module, Error> {
+            let mut wasm_reader = WasmReader::new(data);
+            wasm_reader.read_module()
+        }
+
+        /// 与 decode_bytes 相同，但块嵌套深度超过 max_depth 时报错
+        pub fn decode_bytes_with_max_depth(
+            data: &[u8],
+            max_depth: usize,
+        ) -> Result<Module, Error> {
+            let mut wasm_reader = WasmReader::new(data);
+            wasm_reader.max_depth = max_depth;
+            wasm_reader.read_module()
+        }
+
+        /// 与 decode_bytes 相同，同时记录函数体中每条指令的字节范围并保留原始字节
+        pub fn decode_bytes_with_offsets(data: &[u8]) -> Result<Module, Error> {
+            let mut wasm_reader = WasmReader::new(data);
+            wasm_reader.track_offsets = true;
+            let mut module = wasm_reader.read_module()?;
+            module.original = Some(data.to_vec());
+            module.instr_ranges = wasm_reader.code_ranges;
+            Ok(module)
+        }
+
+        /// 只解码类型、导入、函数、表、内存、全局变量、导出和起始段，
+        /// 函数体和初始化数据按段长度直接跳过
+        pub fn decode_metadata(data: &[u8]) -> Result<ModuleMetadata, Error> {
+            let mut reader = WasmReader::new(data);
+            let mut module = Module {
+                magic: reader.read_u32()?,
+                version: reader.read_u32()?,
+                ..Default::default()
+            };
+            let skipped_bytes = reader.read_sections(&mut module, true)?;
+            Ok(ModuleMetadata {
+                type_sec: module.type_sec,
+                import_sec: module.import_sec,
+                func_sec: module.func_sec,
+                table_sec: module.table_sec,
+                mem_sec: module.mem_sec,
+                global_sec: module.global_sec,
+                export_sec: module.export_sec,
+                start_sec: module.start_sec,
+                skipped_bytes,
+            })
         }
     }
 
@@ -853,15 +1443,18 @@ pub mod module {
                 0b1_0000011,
                 0b0_0000001,
             ];
-            assert_eq!(decode_var_uint(&data[5..]), (0b0000001, 1));
-            assert_eq!(decode_var_uint(&data[4..]), (0b1_0000011, 2));
-            assert_eq!(decode_var_uint(&data[3..]), (0b1_0000011_0000111, 3));
+            assert_eq!(decode_var_uint(&data[5..]).unwrap(), (0b0000001, 1));
+            assert_eq!(decode_var_uint(&data[4..]).unwrap(), (0b1_0000011, 2));
+            assert_eq!(
+                decode_var_uint(&data[3..]).unwrap(),
+                (0b1_0000011_0000111, 3)
+            );
             assert_eq!(
-                decode_var_uint(&data[2..]),
+                decode_var_uint(&data[2..]).unwrap(),
                 (0b1_0000011_0000111_0001111, 4)
             );
             assert_eq!(
-                decode_var_uint(&data[1..]),
+                decode_var_uint(&data[1..]).unwrap(),
                 (0b1_0000011_0000111_0001111_0011111, 5)
             );
         }
@@ -869,7 +1462,7 @@ pub mod module {
         #[test]
         fn test_decode_var_int() {
             let data = vec![0b1_1000000, 0b1_0111011, 0b0_1111000];
-            assert_eq!(decode_var_int(&data[..], 32), (-123456, 3));
+            assert_eq!(decode_var_int(&data[..], 32).unwrap(), (-123456, 3));
         }
 
         #[test]
@@ -884,16 +1477,530 @@ pub mod module {
                 0x6f,
             ];
             let mut reader = WasmReader::new(&data);
-            assert_eq!(reader.read_byte(), 0x01);
-            assert_eq!(reader.read_u32(), 0x05040302);
-            assert_eq!(reader.read_f32(), 1.5);
-            assert_eq!(reader.read_f64(), 1.5);
-            assert_eq!(reader.read_var_u32(), 624485);
-            assert_eq!(reader.read_var_i32(), -123456);
-            assert_eq!(reader.read_var_i64(), -123456);
-            assert_eq!(reader.read_bytes(), [0x01, 0x02, 0x03]);
-            assert_eq!(reader.read_name(), "foo");
+            assert_eq!(reader.read_byte().unwrap(), 0x01);
+            assert_eq!(reader.read_u32().unwrap(), 0x05040302);
+            assert_eq!(reader.read_f32().unwrap(), 1.5);
+            assert_eq!(reader.read_f64().unwrap(), 1.5);
+            assert_eq!(reader.read_var_u32().unwrap(), 624485);
+            assert_eq!(reader.read_var_i32().unwrap(), -123456);
+            assert_eq!(reader.read_var_i64().unwrap(), -123456);
+            assert_eq!(reader.read_bytes().unwrap(), [0x01, 0x02, 0x03]);
+            assert_eq!(reader.read_name().unwrap(), "foo");
+            assert_eq!(reader.remaining(), 0);
+        }
+
+        #[test]
+        fn test_read_br_args() {
+            // block br_if 0 br 0 end end
+            let data = vec![0x02, 0x40, 0x0d, 0x00, 0x0c, 0x00, 0x0b, 0x0b];
+            let mut reader = WasmReader::new(&data);
+            let (expr, _) = reader.read_instructions().unwrap();
+            let args = expr[0].args.as_ref().unwrap();
+            let block_args = args.downcast_ref::<BlockArgs>().unwrap();
+            for instr in &block_args.instructions {
+                let args = instr.args.as_ref().unwrap();
+                assert_eq!(args.downcast_ref::<BrArgs>(), Some(&0));
+            }
+        }
+
+        #[test]
+        fn test_read_memory_copy() {
+            // memory.copy 0 0 end
+            let data = vec![0xfc, 0x0a, 0x00, 0x00, 0x0b];
+            let mut reader = WasmReader::new(&data);
+            let (expr, _) = reader.read_instructions().unwrap();
+            assert_eq!(expr.len(), 1);
+            let args = expr[0].args.as_ref().unwrap();
+            let args = args.downcast_ref::<PrefixedArgs>().unwrap();
+            assert_eq!(args.sub_opcode, MEMORY_COPY);
+            assert_eq!(reader.remaining(), 0);
+        }
+
+        #[test]
+        fn test_read_table_copy() {
+            // table.copy 1 0 end
+            let data = vec![0xfc, 0x0e, 0x01, 0x00, 0x0b];
+            let mut reader = WasmReader::new(&data);
+            let (expr, _) = reader.read_instructions().unwrap();
+            let args = expr[0].args.as_ref().unwrap();
+            let args = args.downcast_ref::<PrefixedArgs>().unwrap();
+            assert_eq!(args.sub_opcode, TABLE_COPY);
+            assert_eq!((args.idx, args.src_idx), (1, 0));
+            assert_eq!(reader.remaining(), 0);
+        }
+
+        #[test]
+        fn test_read_memory_fill() {
+            // memory.fill 0 end
+            let data = vec![0xfc, 0x0b, 0x00, 0x0b];
+            let mut reader = WasmReader::new(&data);
+            let (expr, _) = reader.read_instructions().unwrap();
+            assert_eq!(expr.len(), 1);
+            let args = expr[0].args.as_ref().unwrap();
+            let args = args.downcast_ref::<PrefixedArgs>().unwrap();
+            assert_eq!(args.sub_opcode, MEMORY_FILL);
+            assert_eq!(reader.remaining(), 0);
+        }
+
+        #[test]
+        fn test_section_len_exceeds_remaining() {
+            // 类型段声明 16 字节，实际只有 3 字节
+            let data = vec![
+                0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x10,
+                0x01, 0x60, 0x00,
+            ];
+            assert!(matches!(
+                WasmReader::decode_bytes(&data),
+                Err(Error::Decode(msg)) if msg == "section 1 length 16 exceeds remaining 3 bytes"
+            ));
+        }
+
+        #[test]
+        fn test_decode_truncated() {
+            // (func (result f64) f64.const 0)，在代码段中任意位置截断都报告解码错误，
+            // 而不是越界 panic
+            let data = vec![
+                0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05,
+                0x01, 0x60, 0x00, 0x01, 0x7c, 0x03, 0x02, 0x01, 0x00, 0x0a,
+                0x0d, 0x01, 0x0b, 0x00, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x0b,
+            ];
+            assert!(WasmReader::decode_bytes(&data).is_ok());
+            for len in (1..8).chain(20..data.len()) {
+                assert!(matches!(
+                    WasmReader::decode_bytes(&data[..len]),
+                    Err(Error::Decode(_))
+                ));
+            }
+        }
+
+        #[test]
+        fn test_section_len_mismatch() {
+            // 类型段声明 6 字节，内容只有 4 字节，多出的 2 字节属于下一个段
+            let data = vec![
+                0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06,
+                0x01, 0x60, 0x00, 0x00, 0x03, 0x01,
+            ];
+            assert!(matches!(
+                WasmReader::decode_bytes(&data),
+                Err(Error::Decode(msg)) if msg == "type section (id 1) length mismatch: declared 6 bytes, consumed 4 bytes"
+            ));
+        }
+
+        #[test]
+        fn test_read_name_len_exceeds_remaining() {
+            // 名字声明 200 字节，实际只有 2 字节
+            let data = vec![0xc8, 0x01, 0x61, 0x62];
+            let mut reader = WasmReader::new(&data);
+            assert!(matches!(
+                reader.read_name(),
+                Err(Error::Decode(msg)) if msg == "byte length 200 exceeds remaining 2 bytes"
+            ));
+        }
+
+        #[test]
+        fn test_read_custom_sec() {
+            // 自定义段 "ab"，负载为 01 02 03，之后是一个空的类型段
+            let data = vec![
+                0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x00, 0x06,
+                0x02, 0x61, 0x62, 0x01, 0x02, 0x03, 0x01, 0x01, 0x00,
+            ];
+            let module = WasmReader::decode_bytes(&data).unwrap();
+            assert_eq!(module.custom_sec.len(), 1);
+            assert_eq!(module.custom_sec[0].name, "ab");
+            assert_eq!(module.custom_sec[0].bytes, vec![0x01, 0x02, 0x03]);
+            assert!(module.type_sec.is_empty());
+        }
+
+        #[test]
+        fn test_decode_metadata() {
+            let leb = |mut n: usize| {
+                let mut out = vec![];
+                loop {
+                    let byte = (n & 0x7f) as u8;
+                    n >>= 7;
+                    if n == 0 {
+                        out.push(byte);
+                        return out;
+                    }
+                    out.push(byte | 0x80);
+                }
+            };
+            let section = |id: u8, payload: Vec<u8>| {
+                let mut out = vec![id];
+                out.extend(leb(payload.len()));
+                out.extend(payload);
+                out
+            };
+            // 导入 env.f，定义 200 个函数，每个函数体为 100 个 nop，导出 func 1
+            let n = 200;
+            let mut code = leb(n);
+            for _ in 0..n {
+                let mut body = vec![0x00];
+                body.extend(vec![0x01; 100]);
+                body.push(0x0b);
+                code.extend(leb(body.len()));
+                code.extend(body);
+            }
+            let mut funcs = leb(n);
+            funcs.extend(vec![0x00; n]);
+            let mut data = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+            data.extend(section(1, vec![0x01, 0x60, 0x00, 0x00]));
+            data.extend(section(
+                2,
+                vec![0x01, 0x03, 0x65, 0x6e, 0x76, 0x01, 0x66, 0x00, 0x00],
+            ));
+            data.extend(section(3, funcs));
+            data.extend(section(7, vec![0x01, 0x01, 0x67, 0x00, 0x01]));
+            let code_len = code.len();
+            data.extend(section(10, code));
+
+            let meta = WasmReader::decode_metadata(&data).unwrap();
+            let module = WasmReader::decode_bytes(&data).unwrap();
+            assert_eq!(meta.import_sec.len(), module.import_sec.len());
+            assert_eq!(meta.import_sec[0].module_name, "env");
+            assert_eq!(meta.import_sec[0].member_name, "f");
+            assert_eq!(meta.export_sec.len(), module.export_sec.len());
+            assert_eq!(meta.export_sec[0].name, module.export_sec[0].name);
+            assert_eq!(meta.func_sec.len(), n);
+            assert_eq!(module.code_sec.len(), n);
+            assert_eq!(meta.skipped_bytes, code_len);
+        }
+
+        #[test]
+        fn test_nesting_too_deep() {
+            // 10 万层 block，超过默认深度后立即报错，不会栈溢出
+            let n = 100_000;
+            let mut data = vec![];
+            for _ in 0..n {
+                data.extend([0x02, 0x40]);
+            }
+            data.extend(vec![0x0b; n + 1]);
+            let mut reader = WasmReader::new(&data);
+            assert!(matches!(
+                reader.read_instructions(),
+                Err(Error::Decode(msg)) if msg == "block nesting exceeds max depth 256"
+            ));
+        }
+
+        #[test]
+        fn test_nesting_at_max_depth() {
+            // 默认最大深度的嵌套能在测试线程的栈中解码完
+            let n = MAX_NESTING_DEPTH;
+            let mut data = vec![];
+            for _ in 0..n {
+                data.extend([0x02, 0x40]);
+            }
+            data.extend(vec![0x0b; n + 1]);
+            let mut reader = WasmReader::new(&data);
+            let (expr, _) = reader.read_instructions().unwrap();
+            assert_eq!(expr.len(), 1);
+            assert_eq!(reader.depth, 0);
+        }
+
+        #[test]
+        fn test_nesting_within_max_depth() {
+            // 8 层 block 嵌套
+            let mut data = vec![];
+            for _ in 0..8 {
+                data.extend([0x02, 0x40]);
+            }
+            data.extend(vec![0x0b; 9]);
+            let mut reader = WasmReader::new(&data);
+            reader.max_depth = 8;
+            let (expr, _) = reader.read_instructions().unwrap();
+            assert_eq!(expr.len(), 1);
+            assert_eq!(reader.depth, 0);
+        }
+
+        #[test]
+        fn test_debug_sections() {
+            let custom = |name: &str, payload_len: usize| {
+                let mut sec = vec![0x00, (1 + name.len() + payload_len) as u8];
+                sec.push(name.len() as u8);
+                sec.extend(name.as_bytes());
+                sec.extend(vec![0xaa; payload_len]);
+                sec
+            };
+            let mut data = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+            data.extend(custom(".debug_info", 12));
+            data.extend(custom("name", 3));
+            data.extend(custom(".debug_line", 7));
+            let module = WasmReader::decode_bytes(&data).unwrap();
+            assert_eq!(module.custom_sec.len(), 3);
+            let debug: Vec<(&str, usize)> = module
+                .debug_sections()
+                .iter()
+                .map(|cs| (cs.name.as_str(), cs.bytes.len()))
+                .collect();
+            assert_eq!(debug, vec![(".debug_info", 12), (".debug_line", 7)]);
+        }
+
+        #[test]
+        fn test_target_features() {
+            // target_features 段：2 个特性，+mutable-globals 和 =bulk-memory
+            let mut payload = vec![0x02];
+            for (prefix, name) in
+                [(b'+', "mutable-globals"), (b'=', "bulk-memory")]
+            {
+                payload.push(prefix);
+                payload.push(name.len() as u8);
+                payload.extend(name.as_bytes());
+            }
+            let name = "target_features";
+            let mut data = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+            data.extend([0x00, (1 + name.len() + payload.len()) as u8]);
+            data.push(name.len() as u8);
+            data.extend(name.as_bytes());
+            data.extend(payload);
+            let module = WasmReader::decode_bytes(&data).unwrap();
+            let features = module.target_features().unwrap();
+            assert_eq!(features.len(), 2);
+            assert_eq!(features[1], ('=', "bulk-memory".to_string()));
+            assert_eq!(
+                Module::parse_target_features(&module.custom_sec[0]).unwrap(),
+                features
+            );
+            assert!(Module::default().target_features().unwrap().is_empty());
+            // 前缀不合法或名字被截断时报告解码错误
+            for bytes in [vec![0x01, b'*', 0x01, b'a'], vec![0x01, b'+', 0x05]]
+            {
+                let sec = CustomSec {
+                    name: name.to_string(),
+                    bytes,
+                };
+                assert!(matches!(
+                    Module::parse_target_features(&sec),
+                    Err(Error::Decode(_))
+                ));
+            }
+        }
+
+        #[test]
+        fn test_func_name() {
+            let name_sec = |bytes: Vec<u8>| Module {
+                custom_sec: vec![CustomSec {
+                    name: "name".to_string(),
+                    bytes,
+                }],
+                export_sec: vec![Export {
+                    name: "run".to_string(),
+                    desc: ExportDesc::Func(0),
+                }],
+                ..Default::default()
+            };
+            // 函数名子段：func 0 名为 "f"
+            let module = name_sec(vec![0x01, 0x04, 0x01, 0x00, 0x01, b'f']);
+            assert_eq!(module.func_name(0), Some("f".to_string()));
+            assert_eq!(module.func_name(1), None);
+            // 子段长度越界、名字不是 UTF-8 时退回到导出名
+            for bytes in [
+                vec![0x01, 0x10, 0x01],
+                vec![0x01, 0x04, 0x01, 0x00, 0x01, 0xff],
+            ] {
+                let module = name_sec(bytes);
+                assert_eq!(module.func_name(0), Some("run".to_string()));
+                assert_eq!(module.func_name(1), None);
+            }
+        }
+
+        #[test]
+        fn test_read_code_trailing_bytes() {
+            // 代码项长度为 4：0 个局部变量、nop、end，之后多出一个字节
+            let data = vec![0x04, 0x00, 0x01, 0x0b, 0x01];
+            let mut reader = WasmReader::new(&data);
+            assert!(matches!(
+                reader.read_code(),
+                Err(Error::Decode(msg)) if msg.ends_with("after function end")
+            ));
+        }
+
+        #[test]
+        fn test_read_call_indirect_table_idx() {
+            // call_indirect 3 1 end
+            let data = vec![0x11, 0x03, 0x01, 0x0b];
+            let mut reader = WasmReader::new(&data);
+            let (expr, _) = reader.read_instructions().unwrap();
+            let args = expr[0].args.as_ref().unwrap();
+            let args = args.downcast_ref::<CallIndirectArgs>().unwrap();
+            assert_eq!(args.type_idx, 3);
+            assert_eq!(args.table_idx, 1);
+        }
+
+        #[test]
+        fn test_read_elem_exprs() {
+            // flags=4: i32.const 0 end, [ref.func 1 end, ref.func 0 end]
+            let data = vec![
+                0x04, 0x41, 0x00, 0x0b, 0x02, 0xd2, 0x01, 0x0b, 0xd2, 0x00,
+                0x0b,
+            ];
+            let mut reader = WasmReader::new(&data);
+            let elem = reader.read_elem().unwrap();
+            assert_eq!(elem.table, 0);
+            assert_eq!(elem.init, vec![1, 0]);
+            assert_eq!(reader.remaining(), 0);
+            // flags=6: table 1, i32.const 0 end, funcref, [ref.func 2 end]
+            let data = vec![
+                0x06, 0x01, 0x41, 0x00, 0x0b, 0x70, 0x01, 0xd2, 0x02, 0x0b,
+            ];
+            let mut reader = WasmReader::new(&data);
+            let elem = reader.read_elem().unwrap();
+            assert_eq!(elem.table, 1);
+            assert_eq!(elem.init, vec![2]);
+            assert_eq!(reader.remaining(), 0);
+        }
+
+        #[test]
+        fn test_read_non_active_elem() {
+            // flags=1: elemkind, [func 2 func 0]
+            // flags=3: elemkind, [func 1]，即 (elem declare func 1)
+            // flags=7: funcref, [ref.func 3 end]
+            for (data, mode, init) in [
+                (
+                    vec![0x01, 0x00, 0x02, 0x02, 0x00],
+                    ElemMode::Passive,
+                    vec![2, 0],
+                ),
+                (vec![0x03, 0x00, 0x01, 0x01], ElemMode::Declarative, vec![1]),
+                (
+                    vec![0x07, 0x70, 0x01, 0xd2, 0x03, 0x0b],
+                    ElemMode::Declarative,
+                    vec![3],
+                ),
+            ] {
+                let mut reader = WasmReader::new(&data);
+                let elem = reader.read_elem().unwrap();
+                assert_eq!(elem.mode, mode);
+                assert!(elem.offset.is_empty());
+                assert_eq!(elem.init, init);
+                assert_eq!(reader.remaining(), 0);
+            }
+        }
+
+        #[test]
+        fn test_read_memory_init() {
+            // memory.init 2 0 end
+            let data = vec![0xfc, 0x08, 0x02, 0x00, 0x0b];
+            let mut reader = WasmReader::new(&data);
+            let (expr, _) = reader.read_instructions().unwrap();
+            let args = expr[0].args.as_ref().unwrap();
+            let args = args.downcast_ref::<PrefixedArgs>().unwrap();
+            assert_eq!(args.sub_opcode, MEMORY_INIT);
+            assert_eq!(args.idx, 2);
+            assert_eq!(reader.remaining(), 0);
+        }
+
+        #[test]
+        fn test_read_ref_null() {
+            // ref.null extern ref.null func ref.null 3 struct.new 1 end
+            let data = vec![
+                0xd0, 0x6f, 0xd0, 0x70, 0xd0, 0x03, 0xfb, 0x00, 0x01, 0x0b,
+            ];
+            let mut reader = WasmReader::new(&data);
+            let (expr, _) = reader.read_instructions().unwrap();
+            let heap_types: Vec<HeapType> = expr[..3]
+                .iter()
+                .map(|instr| {
+                    let args = instr.args.as_ref().unwrap();
+                    *args.downcast_ref::<HeapType>().unwrap()
+                })
+                .collect();
+            assert_eq!(
+                heap_types,
+                vec![HeapType::Extern, HeapType::Func, HeapType::Type(3)]
+            );
+            let args = expr[3].args.as_ref().unwrap();
+            let gc_args = args.downcast_ref::<GcArgs>().unwrap();
+            assert_eq!(gc_args.sub_opcode, STRUCT_NEW);
+            assert_eq!(gc_args.type_idx, 1);
+            assert_eq!(reader.remaining(), 0);
+        }
+
+        #[test]
+        fn test_extract_strings() {
+            let data = |offset: Expr, init: &[u8]| Data {
+                mem: 0,
+                offset,
+                init: init.to_vec(),
+            };
+            let global_get =
+                |idx| vec![Instruction::with_index(OpCode::GlobalGet, idx)];
+            let global = |mutable, val| Global {
+                global_type: GlobalType {
+                    val_type: ValType::I32,
+                    mutable,
+                },
+                init_expr: vec![Instruction::with_i32_const(val)],
+            };
+            // global 0 为导入的全局变量，global 1 不可变，global 2 可变
+            let module = Module {
+                import_sec: vec![Import {
+                    module_name: "env".to_string(),
+                    member_name: "base".to_string(),
+                    desc: ImportDesc::Global(GlobalType {
+                        val_type: ValType::I32,
+                        mutable: false,
+                    }),
+                }],
+                global_sec: vec![global(false, 4096), global(true, 8192)],
+                data_sec: vec![
+                    data(
+                        vec![Instruction::with_i32_const(1024)],
+                        b"\x00\x01hello, world\x00ab\xffwasm",
+                    ),
+                    data(global_get(1), b"global"),
+                    // 地址依赖导入的或可变的全局变量，无法静态确定
+                    data(global_get(0), b"skipped"),
+                    data(global_get(2), b"skipped"),
+                ],
+                ..Default::default()
+            };
+            assert_eq!(
+                module.extract_strings(4),
+                vec![
+                    (1026, "hello, world".to_string()),
+                    (1042, "wasm".to_string()),
+                    (4096, "global".to_string())
+                ]
+            );
+            assert_eq!(module.extract_strings(5).len(), 2);
+            // 连续的不可打印字节之间不会产生空字符串
+            assert_eq!(module.extract_strings(0), module.extract_strings(1));
+            assert!(module
+                .extract_strings(0)
+                .iter()
+                .all(|(_, s)| !s.is_empty()));
+            assert_eq!(module.extract_strings(1)[1], (1039, "ab".to_string()));
+        }
+
+        #[test]
+        fn test_decode_with_offsets() {
+            // (func (result i32) (block (result i32) (i32.const 300)))
+            let data = vec![
+                0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00,
+                0x00, // magic & version
+                0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7f, // type sec
+                0x03, 0x02, 0x01, 0x00, // func sec
+                0x0a, 0x0a, 0x01, 0x08, 0x00, 0x02, 0x7f, 0x41, 0xac, 0x02,
+                0x0b, 0x0b, // code sec
+            ];
+            let module = WasmReader::decode_bytes_with_offsets(&data).unwrap();
+            assert_eq!(module.original_bytes(), Some(&data[..]));
+            // 先序展开后 0 为 block，1 为块内的 i32.const
+            assert_eq!(module.encoded_range(0, 0), Some((24, 30)));
+            let (start, end) = module.encoded_range(0, 1).unwrap();
+            assert_eq!((start, end), (26, 29));
+            assert_eq!(module.encoded_range(0, 2), None);
+            // 按范围截取的字节能重新解码出相同的常量
+            let mut reader = WasmReader::new(&data[start..end]);
+            let decoded = reader.read_instruction().unwrap();
+            assert!(decoded.opcode == OpCode::I32Const);
+            assert_eq!(decoded.args_as::<i32>(), Some(&300));
             assert_eq!(reader.remaining(), 0);
+            // 普通解码不记录偏移
+            let module = WasmReader::decode_bytes(&data).unwrap();
+            assert!(module.original_bytes().is_none());
+            assert!(module.encoded_range(0, 0).is_none());
         }
 
         #[test]
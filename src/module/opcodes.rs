@@ -36,6 +36,8 @@ pub mod opcodes {
         LocalTee = 0x22,          // local.tee x
         GlobalGet = 0x23,         // global.get x
         GlobalSet = 0x24,         // global.set x
+        TableGet = 0x25,          // table.get x
+        TableSet = 0x26,          // table.set x
         I32Load = 0x28,           // i32.load m
         I64Load = 0x29,           // i64.load m
         F32Load = 0x2A,           // f32.load m
@@ -193,9 +195,23 @@ pub mod opcodes {
         I64Extend8S = 0xC2,       // i64.extend8_s
         I64Extend16S = 0xC3,      // i64.extend16_s
         I64Extend32S = 0xC4,      // i64.extend32_s
+        RefNull = 0xD0,           // ref.null ht
+        RefFunc = 0xD2,           // ref.func x
+        Gc = 0xFB,                // GC 提案中的指令
         TruncSat = 0xFC,          // <i32|64>.trunc_sat_<f32|64>_<s|u>
     }
 
+    // 0xFC 前缀指令的子操作码，0 到 7 为非陷入的浮点数截断指令
+    pub const MEMORY_INIT: u8 = 0x08; // memory.init x
+    pub const DATA_DROP: u8 = 0x09; // data.drop x
+    pub const MEMORY_COPY: u8 = 0x0A; // memory.copy
+    pub const MEMORY_FILL: u8 = 0x0B; // memory.fill
+    pub const TABLE_COPY: u8 = 0x0E; // table.copy x y
+
+    // 0xFB 前缀指令的子操作码
+    pub const STRUCT_NEW: u32 = 0x00; // struct.new x
+    pub const STRUCT_NEW_DEFAULT: u32 = 0x01; // struct.new_default x
+
     impl fmt::Display for OpCode {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "{}", self.as_ref())
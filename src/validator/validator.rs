@@ -0,0 +1,927 @@
+pub mod validator {
+    use crate::module::*;
+    use std::collections::HashSet;
+    use std::fmt;
+
+    #[derive(Debug)]
+    pub struct ValidationError {
+        pub message: String,
+    }
+
+    impl ValidationError {
+        fn new(message: String) -> ValidationError {
+            ValidationError { message }
+        }
+    }
+
+    impl fmt::Display for ValidationError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "validation error: {}", self.message)
+        }
+    }
+
+    pub struct Validator<'a> {
+        module: &'a Module,
+    }
+
+    impl<'a> Validator<'a> {
+        pub fn validate(module: &Module) -> Result<(), ValidationError> {
+            let v = Validator { module };
+            v.validate_code_sec()?;
+            v.validate_const_exprs()?;
+            v.validate_globals()?;
+            v.validate_global_sets()?;
+            v.validate_br_tables()?;
+            v.validate_selects()?;
+            Ok(())
+        }
+
+        /// 在 validate 的基础上执行引用类型提案的额外检查：
+        /// 函数体中 ref.func 引用的函数必须在元素段、导出或全局变量初始值中声明过
+        pub fn validate_strict(module: &Module) -> Result<(), ValidationError> {
+            Validator::validate(module)?;
+            let v = Validator { module };
+            v.validate_ref_funcs()?;
+            Ok(())
+        }
+
+        // 可以被 ref.func 引用的函数
+        fn declared_funcs(&self) -> HashSet<FuncIdx> {
+            let module = self.module;
+            let mut funcs: HashSet<FuncIdx> = module
+                .elem_sec
+                .iter()
+                .flat_map(|elem| elem.init.iter().copied())
+                .collect();
+            for export in &module.export_sec {
+                if let ExportDesc::Func(idx) = export.desc {
+                    funcs.insert(idx);
+                }
+            }
+            for global in &module.global_sec {
+                for instr in &global.init_expr {
+                    if instr.opcode == OpCode::RefFunc {
+                        funcs.insert(idx_arg(instr) as FuncIdx);
+                    }
+                }
+            }
+            funcs
+        }
+
+        fn validate_ref_funcs(&self) -> Result<(), ValidationError> {
+            let declared = self.declared_funcs();
+            for code in &self.module.code_sec {
+                check_ref_funcs(&code.expr, &declared)?;
+            }
+            Ok(())
+        }
+
+        // 函数段只记录内部函数的签名，代码段记录对应的函数体，两者必须一一对应
+        fn validate_code_sec(&self) -> Result<(), ValidationError> {
+            let func_count = self.module.func_sec.len();
+            let code_count = self.module.code_sec.len();
+            if func_count != code_count {
+                return Err(ValidationError::new(format!(
+                    "function and code section have inconsistent lengths: {} functions, {} code entries",
+                    func_count, code_count
+                )));
+            }
+            Ok(())
+        }
+
+        // 全局变量索引空间：先是导入的全局变量，然后是内部全局变量
+        fn global_types(&self) -> (Vec<GlobalType>, usize) {
+            let mut types: Vec<GlobalType> = self
+                .module
+                .import_sec
+                .iter()
+                .filter_map(|import| match import.desc {
+                    ImportDesc::Global(global_type) => Some(global_type),
+                    _ => None,
+                })
+                .collect();
+            let imported_count = types.len();
+            types.extend(self.module.global_sec.iter().map(|g| g.global_type));
+            (types, imported_count)
+        }
+
+        // 全局变量初始值以及元素段、数据段的偏移量必须是常量表达式
+        fn validate_const_exprs(&self) -> Result<(), ValidationError> {
+            let module = self.module;
+            let exprs = module
+                .global_sec
+                .iter()
+                .map(|g| ("global", &g.init_expr))
+                .chain(module.elem_sec.iter().map(|e| ("elem", &e.offset)))
+                .chain(module.data_sec.iter().map(|d| ("data", &d.offset)));
+            for (kind, expr) in exprs {
+                for instr in expr {
+                    if !is_const_instr(instr.opcode) {
+                        return Err(ValidationError::new(format!(
+                            "{} constant expression contains non-constant instruction {}",
+                            kind, instr.opcode
+                        )));
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        // 内部全局变量的初始化表达式只能引用导入的不可变全局变量
+        fn validate_globals(&self) -> Result<(), ValidationError> {
+            let (types, imported_count) = self.global_types();
+            for (i, global) in self.module.global_sec.iter().enumerate() {
+                for instr in &global.init_expr {
+                    if instr.opcode != OpCode::GlobalGet {
+                        continue;
+                    }
+                    let idx = idx_arg(instr);
+                    if idx >= imported_count {
+                        return Err(ValidationError::new(format!(
+                            "global {} initializer references non-imported global {}",
+                            imported_count + i,
+                            idx
+                        )));
+                    }
+                    if types[idx].mutable {
+                        return Err(ValidationError::new(format!(
+                            "global {} initializer references mutable global {}",
+                            imported_count + i,
+                            idx
+                        )));
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        // global.set 只能作用于可变全局变量
+        fn validate_global_sets(&self) -> Result<(), ValidationError> {
+            let (types, _) = self.global_types();
+            for code in &self.module.code_sec {
+                check_global_sets(&code.expr, &types)?;
+            }
+            Ok(())
+        }
+
+        // br_table 的所有目标标签（包括默认标签）必须具有相同的类型，
+        // 否则跳转到不同标签时转发的值个数不同，会破坏操作数栈
+        fn validate_br_tables(&self) -> Result<(), ValidationError> {
+            let module = self.module;
+            for (code, type_idx) in module.code_sec.iter().zip(&module.func_sec)
+            {
+                let func_type = match module.type_sec.get(*type_idx as usize) {
+                    Some(func_type) => func_type,
+                    None => {
+                        return Err(ValidationError::new(format!(
+                            "unknown type {}",
+                            type_idx
+                        )))
+                    }
+                };
+                // 函数体本身是最外层的标签，类型为函数的结果类型
+                let mut labels = vec![func_type.result_types.clone()];
+                self.check_br_tables(&code.expr, &mut labels)?;
+            }
+            Ok(())
+        }
+
+        // labels 为从外到内各层标签的类型，loop 的标签类型为参数类型，其余为结果类型
+        fn check_br_tables(
+            &self,
+            instrs: &[Instruction],
+            labels: &mut Vec<Vec<ValType>>,
+        ) -> Result<(), ValidationError> {
+            for instr in instrs {
+                let args = instr.args.as_ref();
+                match instr.opcode {
+                    OpCode::BrTable => {
+                        let args = args
+                            .unwrap()
+                            .downcast_ref::<BrTableArgs>()
+                            .unwrap();
+                        let label_type = |idx: u32| {
+                            let depth =
+                                labels.len().checked_sub(idx as usize + 1);
+                            match depth {
+                                Some(depth) => Ok(&labels[depth]),
+                                None => Err(ValidationError::new(format!(
+                                    "unknown label {}",
+                                    idx
+                                ))),
+                            }
+                        };
+                        let expected = label_type(args.default)?;
+                        for idx in &args.labels {
+                            if label_type(*idx)? != expected {
+                                return Err(ValidationError::new(format!(
+                                    "br_table label {} and default label {} have inconsistent types",
+                                    idx, args.default
+                                )));
+                            }
+                        }
+                    }
+                    OpCode::Block | OpCode::Loop => {
+                        let block_args =
+                            args.unwrap().downcast_ref::<BlockArgs>().unwrap();
+                        let bt =
+                            block_type(self.module, block_args.block_type)?;
+                        labels.push(if instr.opcode == OpCode::Loop {
+                            bt.params_types
+                        } else {
+                            bt.result_types
+                        });
+                        self.check_br_tables(&block_args.instructions, labels)?;
+                        labels.pop();
+                    }
+                    OpCode::If => {
+                        let if_args =
+                            args.unwrap().downcast_ref::<IfArgs>().unwrap();
+                        let bt = block_type(self.module, if_args.block_type)?;
+                        labels.push(bt.result_types);
+                        self.check_br_tables(&if_args.instructions_1, labels)?;
+                        self.check_br_tables(&if_args.instructions_2, labels)?;
+                        labels.pop();
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+
+        // 无类型的 select 的两个操作数必须是相同的数值类型，引用类型只能用带类型的 select。
+        // 只跟踪能静态确定的操作数类型，无法确定的视为任意类型，不会误报
+        fn validate_selects(&self) -> Result<(), ValidationError> {
+            let module = self.module;
+            let imported = module.import_sec.iter().filter_map(|import| {
+                match import.desc {
+                    ImportDesc::Func(type_idx) => Some(type_idx),
+                    _ => None,
+                }
+            });
+            let func_types = imported
+                .chain(module.func_sec.iter().copied())
+                .map(|type_idx| module.type_sec.get(type_idx as usize))
+                .collect();
+            let (global_types, _) = self.global_types();
+            let mut ctx = FuncCtx {
+                module,
+                func_types,
+                global_types,
+                params: &[],
+                locals: &[],
+            };
+            for (code, type_idx) in module.code_sec.iter().zip(&module.func_sec)
+            {
+                let func_type = &module.type_sec[*type_idx as usize];
+                ctx.params = &func_type.params_types;
+                ctx.locals = &code.locals;
+                check_selects(&code.expr, &mut vec![], &ctx)?;
+            }
+            Ok(())
+        }
+    }
+
+    // 推导函数体中操作数类型所需的信息
+    struct FuncCtx<'a> {
+        module: &'a Module,
+        func_types: Vec<Option<&'a FuncType>>, // 函数索引空间中每个函数的签名
+        global_types: Vec<GlobalType>,
+        params: &'a [ValType],
+        locals: &'a [Locals],
+    }
+
+    impl FuncCtx<'_> {
+        fn local_type(&self, idx: usize) -> Option<ValType> {
+            if idx < self.params.len() {
+                return Some(self.params[idx]);
+            }
+            let mut base = self.params.len();
+            for locals in self.locals {
+                base += locals.n as usize;
+                if idx < base {
+                    return Some(locals.val_type);
+                }
+            }
+            None
+        }
+    }
+
+    fn pop_type(stack: &mut Vec<Option<ValType>>, n: usize) {
+        stack.truncate(stack.len().saturating_sub(n));
+    }
+
+    fn push_types(stack: &mut Vec<Option<ValType>>, types: &[ValType]) {
+        stack.extend(types.iter().map(|t| Some(*t)));
+    }
+
+    // stack 记录当前块内操作数的类型，None 表示无法确定；弹出超过已知部分时同样视为无法确定
+    fn check_selects(
+        instrs: &[Instruction],
+        stack: &mut Vec<Option<ValType>>,
+        ctx: &FuncCtx,
+    ) -> Result<(), ValidationError> {
+        for instr in instrs {
+            let args = instr.args.as_ref();
+            match instr.opcode {
+                OpCode::Select => {
+                    pop_type(stack, 1);
+                    let t2 = stack.pop().flatten();
+                    let t1 = stack.pop().flatten();
+                    if let (Some(t1), Some(t2)) = (t1, t2) {
+                        if t1 != t2 {
+                            return Err(ValidationError::new(format!(
+                                "select operands have different types {} and {}",
+                                t1, t2
+                            )));
+                        }
+                    }
+                    let val_type = t1.or(t2);
+                    if val_type == Some(ValType::FuncRef) {
+                        return Err(ValidationError::new(format!(
+                            "untyped select cannot be used with reference type {}",
+                            ValType::FuncRef
+                        )));
+                    }
+                    stack.push(val_type);
+                }
+                OpCode::Block | OpCode::Loop => {
+                    let block_args =
+                        args.unwrap().downcast_ref::<BlockArgs>().unwrap();
+                    let bt = block_type(ctx.module, block_args.block_type)?;
+                    pop_type(stack, bt.params_types.len());
+                    let mut inner = vec![];
+                    push_types(&mut inner, &bt.params_types);
+                    check_selects(&block_args.instructions, &mut inner, ctx)?;
+                    push_types(stack, &bt.result_types);
+                }
+                OpCode::If => {
+                    let if_args =
+                        args.unwrap().downcast_ref::<IfArgs>().unwrap();
+                    let bt = block_type(ctx.module, if_args.block_type)?;
+                    pop_type(stack, 1 + bt.params_types.len());
+                    for instrs in
+                        [&if_args.instructions_1, &if_args.instructions_2]
+                    {
+                        let mut inner = vec![];
+                        push_types(&mut inner, &bt.params_types);
+                        check_selects(instrs, &mut inner, ctx)?;
+                    }
+                    push_types(stack, &bt.result_types);
+                }
+                // 之后的指令不可达，栈上的类型任意
+                OpCode::Unreachable
+                | OpCode::Br
+                | OpCode::BrTable
+                | OpCode::Return => stack.clear(),
+                OpCode::BrIf
+                | OpCode::Drop
+                | OpCode::LocalSet
+                | OpCode::GlobalSet => pop_type(stack, 1),
+                OpCode::LocalGet => {
+                    stack.push(ctx.local_type(idx_arg(instr)));
+                }
+                OpCode::LocalTee => {
+                    pop_type(stack, 1);
+                    stack.push(ctx.local_type(idx_arg(instr)));
+                }
+                OpCode::GlobalGet => {
+                    let global = ctx.global_types.get(idx_arg(instr));
+                    stack.push(global.map(|g| g.val_type));
+                }
+                OpCode::Call | OpCode::CallIndirect => {
+                    let func_type = if instr.opcode == OpCode::Call {
+                        ctx.func_types.get(idx_arg(instr)).copied().flatten()
+                    } else {
+                        pop_type(stack, 1);
+                        let args = args
+                            .unwrap()
+                            .downcast_ref::<CallIndirectArgs>()
+                            .unwrap();
+                        ctx.module.type_sec.get(args.type_idx as usize)
+                    };
+                    match func_type {
+                        Some(ft) => {
+                            pop_type(stack, ft.params_types.len());
+                            push_types(stack, &ft.result_types);
+                        }
+                        None => stack.clear(),
+                    }
+                }
+                opcode => match stack_effect(opcode) {
+                    Some((n, result)) => {
+                        pop_type(stack, n);
+                        if let Some(result) = result {
+                            stack.push(Some(result));
+                        }
+                    }
+                    // 不了解的指令，之前的类型都不再可信
+                    None => stack.clear(),
+                },
+            }
+        }
+        Ok(())
+    }
+
+    // 数值、内存和表指令弹出的操作数个数以及结果类型
+    fn stack_effect(opcode: OpCode) -> Option<(usize, Option<ValType>)> {
+        use OpCode::*;
+        use ValType::{FuncRef, F32, F64, I32, I64};
+        let effect = match opcode {
+            I32Const => (0, Some(I32)),
+            I64Const => (0, Some(I64)),
+            F32Const => (0, Some(F32)),
+            F64Const => (0, Some(F64)),
+            // load 和 store
+            I32Load | I32Load8S | I32Load8U | I32Load16S | I32Load16U => {
+                (1, Some(I32))
+            }
+            I64Load | I64Load8S | I64Load8U | I64Load16S | I64Load16U
+            | I64Load32S | I64Load32U => (1, Some(I64)),
+            F32Load => (1, Some(F32)),
+            F64Load => (1, Some(F64)),
+            I32Store | I64Store | F32Store | F64Store | I32Store8
+            | I32Store16 | I64Store8 | I64Store16 | I64Store32 => (2, None),
+            MemorySize => (0, Some(I32)),
+            MemoryGrow => (1, Some(I32)),
+            // 一元运算、类型转换和比较
+            I32Eqz | I64Eqz | I32Clz | I32Ctz | I32PopCnt | I32WrapI64
+            | I32TruncF32S | I32TruncF32U | I32TruncF64S | I32TruncF64U
+            | I32ReinterpretF32 | I32Extend8S | I32Extend16S => (1, Some(I32)),
+            I32Eq | I32Ne | I32LtS | I32LtU | I32GtS | I32GtU | I32LeS
+            | I32LeU | I32GeS | I32GeU | I64Eq | I64Ne | I64LtS | I64LtU
+            | I64GtS | I64GtU | I64LeS | I64LeU | I64GeS | I64GeU | F32Eq
+            | F32Ne | F32Lt | F32Gt | F32Le | F32Ge | F64Eq | F64Ne | F64Lt
+            | F64Gt | F64Le | F64Ge | I32Add | I32Sub | I32Mul | I32DivS
+            | I32DivU | I32RemS | I32RemU | I32And | I32Or | I32Xor
+            | I32Shl | I32ShrS | I32ShrU | I32Rotl | I32Rotr => (2, Some(I32)),
+            I64Clz | I64Ctz | I64PopCnt | I64ExtendI32S | I64ExtendI32U
+            | I64TruncF32S | I64TruncF32U | I64TruncF64S | I64TruncF64U
+            | I64ReinterpretF64 | I64Extend8S | I64Extend16S | I64Extend32S => {
+                (1, Some(I64))
+            }
+            I64Add | I64Sub | I64Mul | I64DivS | I64DivU | I64RemS
+            | I64RemU | I64And | I64Or | I64Xor | I64Shl | I64ShrS
+            | I64ShrU | I64Rotl | I64Rotr => (2, Some(I64)),
+            F32Abs | F32Neg | F32Ceil | F32Floor | F32Trunc | F32Nearest
+            | F32Sqrt | F32ConvertI32S | F32ConvertI32U | F32ConvertI64S
+            | F32ConvertI64U | F32DemoteF64 | F32ReinterpretI32 => {
+                (1, Some(F32))
+            }
+            F32Add | F32Sub | F32Mul | F32Div | F32Min | F32Max
+            | F32CopySign => (2, Some(F32)),
+            F64Abs | F64Neg | F64Ceil | F64Floor | F64Trunc | F64Nearest
+            | F64Sqrt | F64ConvertI32S | F64ConvertI32U | F64ConvertI64S
+            | F64ConvertI64U | F64PromoteF32 | F64ReinterpretI64 => {
+                (1, Some(F64))
+            }
+            F64Add | F64Sub | F64Mul | F64Div | F64Min | F64Max
+            | F64CopySign => (2, Some(F64)),
+            // 引用和表
+            RefNull | RefFunc => (0, Some(FuncRef)),
+            TableGet => (1, Some(FuncRef)),
+            TableSet => (2, None),
+            _ => return None,
+        };
+        Some(effect)
+    }
+
+    // 扩展常量提案允许在常量表达式中使用 i32/i64 的 add、sub、mul
+    fn is_const_instr(opcode: OpCode) -> bool {
+        matches!(
+            opcode,
+            OpCode::I32Const
+                | OpCode::I64Const
+                | OpCode::F32Const
+                | OpCode::F64Const
+                | OpCode::GlobalGet
+                | OpCode::RefNull
+                | OpCode::RefFunc
+                | OpCode::I32Add
+                | OpCode::I32Sub
+                | OpCode::I32Mul
+                | OpCode::I64Add
+                | OpCode::I64Sub
+                | OpCode::I64Mul
+        )
+    }
+
+    // 块类型为非负数时是类型段的索引，需先检查是否越界
+    fn block_type(
+        module: &Module,
+        block_type: BlockType,
+    ) -> Result<FuncType, ValidationError> {
+        if block_type >= 0 && module.type_sec.get(block_type as usize).is_none()
+        {
+            return Err(ValidationError::new(format!(
+                "unknown block type {}",
+                block_type
+            )));
+        }
+        Ok(module.get_block_type(block_type))
+    }
+
+    fn idx_arg(instr: &Instruction) -> usize {
+        *instr.args.as_ref().unwrap().downcast_ref::<u32>().unwrap() as usize
+    }
+
+    fn check_global_sets(
+        instrs: &[Instruction],
+        types: &[GlobalType],
+    ) -> Result<(), ValidationError> {
+        for instr in instrs {
+            match instr.opcode {
+                OpCode::GlobalSet => {
+                    let idx = idx_arg(instr);
+                    match types.get(idx) {
+                        None => {
+                            return Err(ValidationError::new(format!(
+                                "unknown global {}",
+                                idx
+                            )))
+                        }
+                        Some(global_type) if !global_type.mutable => {
+                            return Err(ValidationError::new(format!(
+                                "global.set of immutable global {}",
+                                idx
+                            )))
+                        }
+                        _ => {}
+                    }
+                }
+                OpCode::Block | OpCode::Loop => {
+                    let args = instr.args.as_ref().unwrap();
+                    let block_args = args.downcast_ref::<BlockArgs>().unwrap();
+                    check_global_sets(&block_args.instructions, types)?;
+                }
+                OpCode::If => {
+                    let args = instr.args.as_ref().unwrap();
+                    let if_args = args.downcast_ref::<IfArgs>().unwrap();
+                    check_global_sets(&if_args.instructions_1, types)?;
+                    check_global_sets(&if_args.instructions_2, types)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn check_ref_funcs(
+        instrs: &[Instruction],
+        declared: &HashSet<FuncIdx>,
+    ) -> Result<(), ValidationError> {
+        for instr in instrs {
+            match instr.opcode {
+                OpCode::RefFunc => {
+                    let idx = idx_arg(instr) as FuncIdx;
+                    if !declared.contains(&idx) {
+                        return Err(ValidationError::new(format!(
+                            "ref.func of undeclared function {}",
+                            idx
+                        )));
+                    }
+                }
+                OpCode::Block | OpCode::Loop => {
+                    let args = instr.args.as_ref().unwrap();
+                    let block_args = args.downcast_ref::<BlockArgs>().unwrap();
+                    check_ref_funcs(&block_args.instructions, declared)?;
+                }
+                OpCode::If => {
+                    let args = instr.args.as_ref().unwrap();
+                    let if_args = args.downcast_ref::<IfArgs>().unwrap();
+                    check_ref_funcs(&if_args.instructions_1, declared)?;
+                    check_ref_funcs(&if_args.instructions_2, declared)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::module::module::module::{Elem, Global, Import};
+
+        fn empty_code() -> Code {
+            Code {
+                locals: vec![],
+                expr: vec![],
+            }
+        }
+
+        #[test]
+        fn test_code_sec_len_mismatch() {
+            let module = Module {
+                type_sec: vec![FuncType::default()],
+                func_sec: vec![0, 0, 0],
+                code_sec: vec![empty_code(), empty_code()],
+                ..Default::default()
+            };
+            let err = Validator::validate(&module).unwrap_err();
+            assert!(err.message.contains("3 functions"));
+            assert!(err.message.contains("2 code entries"));
+        }
+
+        #[test]
+        fn test_code_sec_len_match() {
+            let module = Module {
+                type_sec: vec![FuncType::default()],
+                func_sec: vec![0, 0],
+                code_sec: vec![empty_code(), empty_code()],
+                ..Default::default()
+            };
+            assert!(Validator::validate(&module).is_ok());
+        }
+
+        fn global_instr(opcode: OpCode, idx: u32) -> Instruction {
+            Instruction::with_args(opcode, idx)
+        }
+
+        fn ref_func(func_idx: u32) -> Instruction {
+            Instruction::with_index(OpCode::RefFunc, func_idx)
+        }
+
+        fn import_global(mutable: bool) -> Import {
+            Import {
+                module_name: "env".to_string(),
+                member_name: "g".to_string(),
+                desc: ImportDesc::Global(GlobalType {
+                    val_type: ValType::I32,
+                    mutable,
+                }),
+            }
+        }
+
+        fn global_from_import() -> Global {
+            Global {
+                global_type: GlobalType {
+                    val_type: ValType::I32,
+                    mutable: false,
+                },
+                init_expr: vec![global_instr(OpCode::GlobalGet, 0)],
+            }
+        }
+
+        #[test]
+        fn test_global_init_from_mutable_import() {
+            let module = Module {
+                import_sec: vec![import_global(true)],
+                global_sec: vec![global_from_import()],
+                ..Default::default()
+            };
+            let err = Validator::validate(&module).unwrap_err();
+            assert!(err.message.contains("mutable global 0"));
+        }
+
+        #[test]
+        fn test_global_init_from_immutable_import() {
+            let module = Module {
+                import_sec: vec![import_global(false)],
+                global_sec: vec![global_from_import()],
+                ..Default::default()
+            };
+            assert!(Validator::validate(&module).is_ok());
+        }
+
+        fn i32_const(val: i32) -> Instruction {
+            Instruction::with_args(OpCode::I32Const, val)
+        }
+
+        fn i32_global(init_expr: Vec<Instruction>) -> Global {
+            Global {
+                global_type: GlobalType {
+                    val_type: ValType::I32,
+                    mutable: false,
+                },
+                init_expr,
+            }
+        }
+
+        #[test]
+        fn test_global_init_extended_const() {
+            let module = Module {
+                global_sec: vec![i32_global(vec![
+                    i32_const(10),
+                    i32_const(5),
+                    Instruction::new(OpCode::I32Mul),
+                ])],
+                ..Default::default()
+            };
+            assert!(Validator::validate(&module).is_ok());
+        }
+
+        #[test]
+        fn test_global_init_non_const() {
+            let module = Module {
+                global_sec: vec![i32_global(vec![
+                    i32_const(10),
+                    i32_const(5),
+                    Instruction::new(OpCode::I32DivS),
+                ])],
+                ..Default::default()
+            };
+            let err = Validator::validate(&module).unwrap_err();
+            assert!(err.message.contains("non-constant instruction"));
+        }
+
+        #[test]
+        fn test_global_set_immutable_import() {
+            let module = Module {
+                type_sec: vec![FuncType::default()],
+                import_sec: vec![import_global(false)],
+                func_sec: vec![0],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![
+                        Instruction::with_args(OpCode::I32Const, 1i32),
+                        global_instr(OpCode::GlobalSet, 0),
+                    ],
+                }],
+                ..Default::default()
+            };
+            let err = Validator::validate(&module).unwrap_err();
+            assert!(err.message.contains("immutable global 0"));
+        }
+
+        #[test]
+        fn test_ref_func_declared() {
+            // func 0 通过 ref.func 引用 func 1
+            let module_with = |elem_sec| Module {
+                type_sec: vec![FuncType::default()],
+                func_sec: vec![0, 0],
+                elem_sec,
+                code_sec: vec![
+                    Code {
+                        locals: vec![],
+                        expr: vec![ref_func(1), Instruction::new(OpCode::Drop)],
+                    },
+                    empty_code(),
+                ],
+                ..Default::default()
+            };
+            let module = module_with(vec![]);
+            assert!(Validator::validate(&module).is_ok());
+            let err = Validator::validate_strict(&module).unwrap_err();
+            assert!(err.message.contains("undeclared function 1"));
+
+            let module = module_with(vec![Elem {
+                mode: ElemMode::Active,
+                table: 0,
+                offset: vec![i32_const(0)],
+                init: vec![1],
+            }]);
+            assert!(Validator::validate_strict(&module).is_ok());
+
+            // 同样的模块，用 (elem declare func 1) 声明
+            let data = [
+                0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00,
+                0x00, // magic & version
+                0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // type sec
+                0x03, 0x03, 0x02, 0x00, 0x00, // func sec
+                0x09, 0x05, 0x01, 0x03, 0x00, 0x01, 0x01, // elem sec
+                0x0a, 0x0a, 0x02, 0x05, 0x00, 0xd2, 0x01, 0x1a, 0x0b, 0x02,
+                0x00, 0x0b, // code sec
+            ];
+            let module = WasmReader::decode_bytes(&data).unwrap();
+            assert_eq!(module.elem_sec[0].mode, ElemMode::Declarative);
+            assert!(Validator::validate_strict(&module).is_ok());
+        }
+
+        // (func (result i32)
+        //   (block (result i32)
+        //     (block
+        //       (i32.const 7) (i32.const 0) (br_table labels default))))
+        fn br_table_module(labels: Vec<u32>, default: u32) -> Module {
+            let br_table = BrTableArgs { labels, default };
+            let inner = BlockArgs {
+                block_type: BLOCK_TYPE_EMPTY,
+                instructions: vec![
+                    i32_const(7),
+                    i32_const(0),
+                    Instruction::with_args(OpCode::BrTable, br_table),
+                ],
+            };
+            let outer = BlockArgs {
+                block_type: BLOCK_TYPE_I32,
+                instructions: vec![Instruction::with_args(
+                    OpCode::Block,
+                    inner,
+                )],
+            };
+            Module {
+                type_sec: vec![FuncType {
+                    params_types: vec![],
+                    result_types: vec![ValType::I32],
+                }],
+                func_sec: vec![0],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![Instruction::with_args(OpCode::Block, outer)],
+                }],
+                ..Default::default()
+            }
+        }
+
+        fn select_module(lhs: Instruction, rhs: Instruction) -> Module {
+            Module {
+                type_sec: vec![FuncType {
+                    params_types: vec![ValType::I64],
+                    result_types: vec![],
+                }],
+                func_sec: vec![0],
+                code_sec: vec![Code {
+                    locals: vec![Locals {
+                        n: 1,
+                        val_type: ValType::F32,
+                    }],
+                    expr: vec![
+                        lhs,
+                        rhs,
+                        i32_const(1),
+                        Instruction::new(OpCode::Select),
+                        Instruction::new(OpCode::Drop),
+                    ],
+                }],
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn test_select_operand_types() {
+            let local_get =
+                |idx: u32| Instruction::with_args(OpCode::LocalGet, idx);
+            // i32 和 i64 不同
+            let module = select_module(i32_const(1), local_get(0));
+            let err = Validator::validate(&module).unwrap_err();
+            assert!(err.message.contains("different types i32 and i64"));
+            // i32.add 的结果与 f32 局部变量不同
+            let add = Instruction::with_args(
+                OpCode::Block,
+                BlockArgs {
+                    block_type: BLOCK_TYPE_I32,
+                    instructions: vec![
+                        i32_const(1),
+                        i32_const(2),
+                        Instruction::new(OpCode::I32Add),
+                    ],
+                },
+            );
+            let module = select_module(add, local_get(1));
+            let err = Validator::validate(&module).unwrap_err();
+            assert!(err.message.contains("different types i32 and f32"));
+            // 引用类型只能用带类型的 select
+            let ref_null =
+                || Instruction::with_args(OpCode::RefNull, HeapType::Func);
+            let module = select_module(ref_null(), ref_null());
+            let err = Validator::validate(&module).unwrap_err();
+            assert!(err.message.contains("reference type funcref"));
+            let module = select_module(local_get(0), local_get(0));
+            assert!(Validator::validate(&module).is_ok());
+            // 块类型越界时报错而不是 panic
+            let ctx = FuncCtx {
+                module: &module,
+                func_types: vec![],
+                global_types: vec![],
+                params: &[],
+                locals: &[],
+            };
+            let block = BlockArgs {
+                block_type: 1,
+                instructions: vec![],
+            };
+            let instrs = [Instruction::with_args(OpCode::Block, block)];
+            let err = check_selects(&instrs, &mut vec![], &ctx).unwrap_err();
+            assert!(err.message.contains("unknown block type 1"));
+        }
+
+        #[test]
+        fn test_br_table_label_types() {
+            // 标签 0 没有结果，标签 1 和 2 的结果为 i32
+            let module = br_table_module(vec![0], 1);
+            let err = Validator::validate(&module).unwrap_err();
+            assert!(err.message.contains("inconsistent types"));
+            let module = br_table_module(vec![1, 2], 1);
+            assert!(Validator::validate(&module).is_ok());
+            let module = br_table_module(vec![3], 1);
+            let err = Validator::validate(&module).unwrap_err();
+            assert!(err.message.contains("unknown label 3"));
+            // 块类型引用了不存在的类型
+            let block = BlockArgs {
+                block_type: 5,
+                instructions: vec![],
+            };
+            let module = Module {
+                type_sec: vec![FuncType::default()],
+                func_sec: vec![0],
+                code_sec: vec![Code {
+                    locals: vec![],
+                    expr: vec![Instruction::with_args(OpCode::Block, block)],
+                }],
+                ..Default::default()
+            };
+            let err = Validator::validate(&module).unwrap_err();
+            assert!(err.message.contains("unknown block type 5"));
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// (func (export "add") (param i32 i32) (result i32)
+//   local.get 0 local.get 1 i32.add)
+const ADD_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic & version
+    0x01, 0x07, 0x01, 0x60, 0x02, 0x7f, 0x7f, 0x01, 0x7f, // type sec
+    0x03, 0x02, 0x01, 0x00, // func sec
+    0x07, 0x07, 0x01, 0x03, 0x61, 0x64, 0x64, 0x00, 0x00, // export sec
+    0x0a, 0x09, 0x01, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6a,
+    0x0b, // code sec
+];
+
+// (func (export "main") unreachable)
+const UNREACHABLE_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic & version
+    0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // type sec
+    0x03, 0x02, 0x01, 0x00, // func sec
+    0x07, 0x08, 0x01, 0x04, 0x6d, 0x61, 0x69, 0x6e, 0x00,
+    0x00, // export sec
+    0x0a, 0x05, 0x01, 0x03, 0x00, 0x00, 0x0b, // code sec
+];
+
+// (func (export "compute") (result i32) i32.const 42)
+const COMPUTE_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic & version
+    0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7f, // type sec
+    0x03, 0x02, 0x01, 0x00, // func sec
+    0x07, 0x0b, 0x01, 0x07, 0x63, 0x6f, 0x6d, 0x70, 0x75, 0x74, 0x65, 0x00,
+    0x00, // export sec
+    0x0a, 0x06, 0x01, 0x04, 0x00, 0x41, 0x2a, 0x0b, // code sec
+];
+
+#[test]
+fn test_dump_from_stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rasm"))
+        .args(["--dump", "--file", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(ADD_WASM).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Export[1]:"));
+    assert!(stdout.contains("func[0]: name = add"));
+}
+
+#[test]
+fn test_dump_json() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rasm"))
+        .args(["--dump", "--format", "json", "--file", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(ADD_WASM).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with('{'));
+    assert!(stdout.contains("{\"name\":\"add\",\"kind\":\"func\",\"index\":0}"));
+}
+
+#[test]
+fn test_trap_exit_code() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rasm"))
+        .args(["--file", "-"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(UNREACHABLE_WASM)
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(134));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("trap: unreachable"));
+}
+
+#[test]
+fn test_invoke_without_main() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rasm"))
+        .args(["--invoke", "compute", "--file", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(COMPUTE_WASM).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{}", stderr);
+    assert!(!stderr.contains("No start sec!"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.trim(), "I32(42)");
+}
@@ -0,0 +1,377 @@
+use rasm::error::Error;
+use rasm::interpreter::{Trap, WasmValue, VM};
+use rasm::module::{
+    Code, Export, ExportDesc, FuncType, Instruction, Module, OpCode, ValType,
+};
+
+// 期望结果：具体的值、任意 NaN 或陷入
+enum Expect {
+    Value(WasmValue),
+    NaN,
+    Trap(Trap),
+}
+
+// 一条用例：以 args 为操作数执行 op，返回值类型为 result
+struct Case {
+    op: OpCode,
+    args: &'static [WasmValue],
+    result: ValType,
+    expect: Expect,
+}
+
+use Expect::*;
+use ValType::{F32 as TF32, F64 as TF64, I32 as TI32, I64 as TI64};
+use WasmValue::{F32, F64, I32, I64};
+
+const CASES: &[Case] = &[
+    // 整数溢出按补码回绕
+    Case {
+        op: OpCode::I32Add,
+        args: &[I32(i32::MAX), I32(1)],
+        result: TI32,
+        expect: Value(I32(i32::MIN)),
+    },
+    Case {
+        op: OpCode::I32Sub,
+        args: &[I32(i32::MIN), I32(1)],
+        result: TI32,
+        expect: Value(I32(i32::MAX)),
+    },
+    Case {
+        op: OpCode::I32Mul,
+        args: &[I32(0x10000), I32(0x10000)],
+        result: TI32,
+        expect: Value(I32(0)),
+    },
+    Case {
+        op: OpCode::I64Add,
+        args: &[I64(i64::MAX), I64(1)],
+        result: TI64,
+        expect: Value(I64(i64::MIN)),
+    },
+    // 除法向零取整，余数符号与被除数相同
+    Case {
+        op: OpCode::I32DivS,
+        args: &[I32(-7), I32(2)],
+        result: TI32,
+        expect: Value(I32(-3)),
+    },
+    Case {
+        op: OpCode::I32RemS,
+        args: &[I32(-7), I32(2)],
+        result: TI32,
+        expect: Value(I32(-1)),
+    },
+    Case {
+        op: OpCode::I32DivU,
+        args: &[I32(-1), I32(2)],
+        result: TI32,
+        expect: Value(I32(0x7fff_ffff)),
+    },
+    Case {
+        op: OpCode::I64RemU,
+        args: &[I64(-1), I64(10)],
+        result: TI64,
+        expect: Value(I64(5)),
+    },
+    // 除零陷入
+    Case {
+        op: OpCode::I32DivS,
+        args: &[I32(7), I32(0)],
+        result: TI32,
+        expect: Trap(Trap::IntegerDivideByZero),
+    },
+    Case {
+        op: OpCode::I32DivU,
+        args: &[I32(7), I32(0)],
+        result: TI32,
+        expect: Trap(Trap::IntegerDivideByZero),
+    },
+    Case {
+        op: OpCode::I32RemS,
+        args: &[I32(7), I32(0)],
+        result: TI32,
+        expect: Trap(Trap::IntegerDivideByZero),
+    },
+    Case {
+        op: OpCode::I64DivS,
+        args: &[I64(1), I64(0)],
+        result: TI64,
+        expect: Trap(Trap::IntegerDivideByZero),
+    },
+    // MIN / -1 溢出陷入，而 MIN % -1 结果为 0
+    Case {
+        op: OpCode::I32DivS,
+        args: &[I32(i32::MIN), I32(-1)],
+        result: TI32,
+        expect: Trap(Trap::IntegerOverflow),
+    },
+    Case {
+        op: OpCode::I32RemS,
+        args: &[I32(i32::MIN), I32(-1)],
+        result: TI32,
+        expect: Value(I32(0)),
+    },
+    Case {
+        op: OpCode::I64DivS,
+        args: &[I64(i64::MIN), I64(-1)],
+        result: TI64,
+        expect: Trap(Trap::IntegerOverflow),
+    },
+    Case {
+        op: OpCode::I64RemS,
+        args: &[I64(i64::MIN), I64(-1)],
+        result: TI64,
+        expect: Value(I64(0)),
+    },
+    // 移位位数按类型位宽取模
+    Case {
+        op: OpCode::I32Shl,
+        args: &[I32(1), I32(33)],
+        result: TI32,
+        expect: Value(I32(2)),
+    },
+    Case {
+        op: OpCode::I32ShrS,
+        args: &[I32(-8), I32(1)],
+        result: TI32,
+        expect: Value(I32(-4)),
+    },
+    Case {
+        op: OpCode::I32ShrU,
+        args: &[I32(-8), I32(1)],
+        result: TI32,
+        expect: Value(I32(0x7fff_fffc)),
+    },
+    Case {
+        op: OpCode::I64Shl,
+        args: &[I64(1), I64(65)],
+        result: TI64,
+        expect: Value(I64(2)),
+    },
+    Case {
+        op: OpCode::I64ShrU,
+        args: &[I64(-1), I64(63)],
+        result: TI64,
+        expect: Value(I64(1)),
+    },
+    Case {
+        op: OpCode::I32Rotl,
+        args: &[I32(i32::MIN + 1), I32(1)],
+        result: TI32,
+        expect: Value(I32(3)),
+    },
+    // 位计数
+    Case {
+        op: OpCode::I32Clz,
+        args: &[I32(0)],
+        result: TI32,
+        expect: Value(I32(32)),
+    },
+    Case {
+        op: OpCode::I32Ctz,
+        args: &[I32(0)],
+        result: TI32,
+        expect: Value(I32(32)),
+    },
+    Case {
+        op: OpCode::I32PopCnt,
+        args: &[I32(-1)],
+        result: TI32,
+        expect: Value(I32(32)),
+    },
+    Case {
+        op: OpCode::I32Eqz,
+        args: &[I32(0)],
+        result: TI32,
+        expect: Value(I32(1)),
+    },
+    // 类型转换
+    Case {
+        op: OpCode::I32WrapI64,
+        args: &[I64(0x1_0000_0005)],
+        result: TI32,
+        expect: Value(I32(5)),
+    },
+    Case {
+        op: OpCode::I64ExtendI32S,
+        args: &[I32(-1)],
+        result: TI64,
+        expect: Value(I64(-1)),
+    },
+    Case {
+        op: OpCode::I64ExtendI32U,
+        args: &[I32(-1)],
+        result: TI64,
+        expect: Value(I64(0xffff_ffff)),
+    },
+    // 无符号截断的合法范围为 (-1, 2^64)
+    Case {
+        op: OpCode::I64TruncF64U,
+        args: &[F64(1.8e19)],
+        result: TI64,
+        expect: Value(I64(18_000_000_000_000_000_000u64 as i64)),
+    },
+    Case {
+        op: OpCode::I64TruncF64U,
+        args: &[F64(18_446_744_073_709_549_568.0)],
+        result: TI64,
+        expect: Value(I64(-2048)),
+    },
+    Case {
+        op: OpCode::I64TruncF64U,
+        args: &[F64(1.9e19)],
+        result: TI64,
+        expect: Trap(Trap::IntegerOverflow),
+    },
+    Case {
+        op: OpCode::I64TruncF64U,
+        args: &[F64(18_446_744_073_709_551_616.0)],
+        result: TI64,
+        expect: Trap(Trap::IntegerOverflow),
+    },
+    Case {
+        op: OpCode::I64TruncF64U,
+        args: &[F64(-0.5)],
+        result: TI64,
+        expect: Value(I64(0)),
+    },
+    Case {
+        op: OpCode::I64TruncF64U,
+        args: &[F64(-1.0)],
+        result: TI64,
+        expect: Trap(Trap::IntegerOverflow),
+    },
+    Case {
+        op: OpCode::I64TruncF64U,
+        args: &[F64(f64::NAN)],
+        result: TI64,
+        expect: Trap(Trap::InvalidConversionToInteger),
+    },
+    // NaN 的产生、传播和比较
+    Case {
+        op: OpCode::F32Div,
+        args: &[F32(0.0), F32(0.0)],
+        result: TF32,
+        expect: NaN,
+    },
+    Case {
+        op: OpCode::F32Add,
+        args: &[F32(f32::NAN), F32(1.0)],
+        result: TF32,
+        expect: NaN,
+    },
+    Case {
+        op: OpCode::F64Sqrt,
+        args: &[F64(-1.0)],
+        result: TF64,
+        expect: NaN,
+    },
+    Case {
+        op: OpCode::F64Lt,
+        args: &[F64(f64::NAN), F64(1.0)],
+        result: TI32,
+        expect: Value(I32(0)),
+    },
+    Case {
+        op: OpCode::F32Ne,
+        args: &[F32(f32::NAN), F32(f32::NAN)],
+        result: TI32,
+        expect: Value(I32(1)),
+    },
+    // min/max 的任一操作数为 NaN 时结果为 NaN
+    Case {
+        op: OpCode::F32Min,
+        args: &[F32(f32::NAN), F32(1.0)],
+        result: TF32,
+        expect: NaN,
+    },
+    Case {
+        op: OpCode::F32Max,
+        args: &[F32(1.0), F32(f32::NAN)],
+        result: TF32,
+        expect: NaN,
+    },
+    Case {
+        op: OpCode::F64Min,
+        args: &[F64(1.0), F64(f64::NAN)],
+        result: TF64,
+        expect: NaN,
+    },
+    Case {
+        op: OpCode::F64Max,
+        args: &[F64(f64::NAN), F64(1.0)],
+        result: TF64,
+        expect: NaN,
+    },
+    Case {
+        op: OpCode::F32CopySign,
+        args: &[F32(1.0), F32(-0.0)],
+        result: TF32,
+        expect: Value(F32(-1.0)),
+    },
+];
+
+fn const_instr(val: WasmValue) -> Instruction {
+    match val {
+        I32(v) => Instruction::with_i32_const(v),
+        I64(v) => Instruction::with_i64_const(v),
+        F32(v) => Instruction::with_f32_const(v),
+        F64(v) => Instruction::with_f64_const(v),
+        WasmValue::FuncRef(_) => panic!("funcref has no const instruction"),
+    }
+}
+
+// 只包含导出函数 main 的模块，main 依次压入参数后执行 op
+fn case_module(case: &Case) -> Module {
+    let mut expr: Vec<Instruction> =
+        case.args.iter().map(|v| const_instr(*v)).collect();
+    expr.push(Instruction::new(case.op));
+    Module {
+        type_sec: vec![FuncType {
+            params_types: vec![],
+            result_types: vec![case.result],
+        }],
+        func_sec: vec![0],
+        export_sec: vec![Export {
+            name: "main".to_string(),
+            desc: ExportDesc::Func(0),
+        }],
+        code_sec: vec![Code {
+            locals: vec![],
+            expr,
+        }],
+        ..Default::default()
+    }
+}
+
+fn is_nan(val: &WasmValue) -> bool {
+    match val {
+        F32(v) => v.is_nan(),
+        F64(v) => v.is_nan(),
+        _ => false,
+    }
+}
+
+#[test]
+fn test_conformance() {
+    assert!(CASES.len() >= 20);
+    let mut failures = vec![];
+    for (i, case) in CASES.iter().enumerate() {
+        let module = case_module(case);
+        let ret = VM::new(&module).invoke("main", &[]);
+        let ok = match (&case.expect, &ret) {
+            (Value(expected), Ok(vals)) => {
+                // 按位比较，区分 -0.0 和 0.0
+                vals.len() == 1 && vals[0].to_u64() == expected.to_u64()
+            }
+            (NaN, Ok(vals)) => vals.len() == 1 && is_nan(&vals[0]),
+            (Trap(expected), Err(Error::Trap(trap))) => trap == expected,
+            _ => false,
+        };
+        if !ok {
+            failures.push(format!("case {} {}: got {:?}", i, case.op, ret));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}